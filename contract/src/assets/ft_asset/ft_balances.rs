@@ -13,28 +13,67 @@ impl InternalFTData {
 #[near_bindgen]
 impl Keypom {
     /// Standard function for accepting FTs to then be claimable as part of linkdrops.
+    ///
+    /// The `msg` can either be the drop ID or a JSON `ExtDepositMsg`. Any tokens beyond what the keys in the drop still need
+    /// Will be returned to the sender unless `refund_overflow` is set to false. FT balances are shared by every key in the drop
+    /// So deposits that target a specific key or use are rejected. Everything is returned if the drop has no matching FT asset.
     pub fn ft_on_transfer(
         &mut self,
+        sender_id: AccountId,
         amount: U128,
-        msg: DropId,
+        msg: String,
     ) -> PromiseOrValue<U128> {
-        self.assert_no_global_freeze();
-        
-        let drop_id = msg;
-        let asset_id = env::predecessor_account_id();
-        let mut drop: InternalDrop = self.drop_by_id.get(&drop_id).expect("Drop not found");
+        self.assert_not_paused(PausableAction::deposits);
 
-        let mut asset = drop.asset_by_id.get(&asset_id.to_string()).expect("Asset not found");
+        let ExtDepositMsg {
+            drop_id,
+            refund_overflow,
+            ..
+        } = ExtDepositMsg::parse_fungible(msg);
+        let asset_id = env::predecessor_account_id().to_string();
+        let Some(mut drop) = self.drop_by_id.get(&drop_id) else {
+            near_sdk::log!("Drop {} not found. Refunding {} FTs", drop_id, amount.0);
+            return PromiseOrValue::Value(amount);
+        };
+        drop.assert_can_deposit(&sender_id);
+
+        let mut asset = drop
+            .asset_by_id
+            .get(&asset_id)
+            .unwrap_or(InternalAsset::none);
         // Ensure asset is fungible token and then call the internal function
-        if let InternalAsset::ft(ft_data) = &mut asset {
-            ft_data.add_to_balance_avail(&amount.0);
-            near_sdk::log!("Added {} FTs to drop ID {}. New asset amount: {}", amount.0, drop_id, ft_data.balance_avail);
+        let InternalAsset::ft(ft_data) = &mut asset else {
+            near_sdk::log!(
+                "No FT asset for {} in drop {}. Refunding {} FTs",
+                asset_id,
+                drop_id,
+                amount.0
+            );
+            return PromiseOrValue::Value(amount);
         };
 
-        drop.asset_by_id.insert(&asset_id.to_string(), &asset);
+        let mut amount_to_refund = 0;
+        if refund_overflow.unwrap_or(true) {
+            let outstanding = drop
+                .get_tokens_needed_for_asset(&asset_id)
+                .saturating_sub(ft_data.balance_avail);
+            amount_to_refund = amount.0.saturating_sub(outstanding);
+        }
+
+        let amount_to_add = amount.0 - amount_to_refund;
+        ft_data.add_to_balance_avail(&amount_to_add);
+        near_sdk::log!(
+            "Added {} FTs to drop ID {}. Refunding {}. New asset amount: {}",
+            amount_to_add,
+            drop_id,
+            amount_to_refund,
+            ft_data.balance_avail
+        );
+
+        drop.asset_by_id.insert(&asset_id, &asset);
 
         self.drop_by_id.insert(&drop_id, &drop);
 
-        PromiseOrValue::Value(U128(0))
+        PromiseOrValue::Value(U128(amount_to_refund))
     }
-}
\ No newline at end of file
+}
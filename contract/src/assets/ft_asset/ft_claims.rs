@@ -1,4 +1,4 @@
-use near_sdk::{serde_json::json, Gas, GasWeight};
+use near_sdk::{serde_json::json, Gas, GasWeight, PromiseResult};
 
 use crate::*;
 
@@ -12,17 +12,30 @@ pub const MIN_GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(5);
 /// Minimum Gas required to register a user on the FT contract
 /// 5 TGas
 pub const MIN_GAS_FOR_STORAGE_DEPOSIT: Gas = Gas::from_tgas(5);
+/// Minimum Gas required to check whether a user is registered on the FT contract
+/// 3 TGas
+pub const MIN_GAS_FOR_STORAGE_BALANCE_OF: Gas = Gas::from_tgas(3);
+/// Gas needed to execute the logic in `on_ft_registration_checked` (including firing the transfer and resolve)
+/// 2 TGas + 2 * CCC gas (since there are 2 CCCs)
+/// 12 TGas
+pub const GAS_FOR_FT_REGISTRATION_CHECK: Gas =
+    Gas::from_gas(Gas::from_tgas(2).as_gas() + 2 * MIN_BASE_GAS_FOR_RECEIPT_SPIN_UP.as_gas());
+/// Gas needed to credit the funder with an unused registration cost
+/// 3 TGas
+pub const GAS_FOR_RESOLVE_UNUSED_REGISTRATION: Gas = Gas::from_tgas(3);
 
 impl InternalFTData {
-    /// Attempt to transfer FTs to a given address (will cover registration automatically).
+    /// Attempt to transfer FTs to a given address. Depending on the registration policy, the claimer will be registered
+    /// Automatically, registered only if needed, or must already be registered.
     /// If the transfer fails, the FTs will be returned to the available balance
     /// Should *only* be invoked if the available balance is greater than or equal to the transfer amount.
     pub fn claim_ft_asset(
         &mut self,
         receiver_id: &AccountId,
         transfer_amount: &Balance,
+        funder_id: &AccountId,
     ) -> Option<Promise> {
-        if !self.enough_balance(&transfer_amount) {
+        if !self.enough_balance(transfer_amount) {
             near_sdk::log!(
                 "not enough balance to transfer. Found {} but needed {}. Skipping asset claim.",
                 self.balance_avail,
@@ -34,30 +47,143 @@ impl InternalFTData {
         // Decrement the available balance and then invoke the transfer
         self.balance_avail -= transfer_amount;
 
-        // Create a new batch promise to pay storage and transfer FTs to the new account ID
-        let mut batch_transfer = Promise::new(self.contract_id.clone());
+        match self.registration_policy {
+            FTRegistrationPolicy::always => Some(ft_transfer_with_registration(
+                &self.contract_id,
+                receiver_id,
+                *transfer_amount,
+                Some(self.registration_cost),
+            )),
+            FTRegistrationPolicy::claimer_registered => Some(ft_transfer_with_registration(
+                &self.contract_id,
+                receiver_id,
+                *transfer_amount,
+                None,
+            )),
+            // Check whether the claimer is registered first and decide whether to register them in the callback
+            FTRegistrationPolicy::when_needed => Some(
+                Promise::new(self.contract_id.clone())
+                    .function_call_weight(
+                        "storage_balance_of".to_string(),
+                        json!({ "account_id": receiver_id }).to_string().into(),
+                        NearToken::from_yoctonear(0),
+                        MIN_GAS_FOR_STORAGE_BALANCE_OF,
+                        GasWeight(0),
+                    )
+                    .then(
+                        Keypom::ext(env::current_account_id())
+                            .with_static_gas(GAS_FOR_FT_REGISTRATION_CHECK)
+                            .with_unused_gas_weight(1)
+                            .on_ft_registration_checked(
+                                self.contract_id.clone(),
+                                receiver_id.clone(),
+                                U128(*transfer_amount),
+                                U128(self.registration_cost),
+                                funder_id.clone(),
+                            ),
+                    ),
+            ),
+        }
+    }
+}
+
+/// Create a batch promise that (optionally) pays for the receiver's storage and then transfers the FTs
+fn ft_transfer_with_registration(
+    contract_id: &AccountId,
+    receiver_id: &AccountId,
+    transfer_amount: Balance,
+    registration_cost: Option<Balance>,
+) -> Promise {
+    let mut batch_transfer = Promise::new(contract_id.clone());
 
-        // Pay the required storage as outlined in the AccountData. This will run first and then we send the fungible tokens
-        // Call the function with the min GAS and then attach 1/5 of the unspent GAS to the call
+    // Pay the required storage as outlined in the AccountData. This will run first and then we send the fungible tokens
+    if let Some(registration_cost) = registration_cost {
         batch_transfer = batch_transfer.function_call_weight(
             "storage_deposit".to_string(),
             json!({ "account_id": receiver_id }).to_string().into(),
-            NearToken::from_near(self.registration_cost),
+            NearToken::from_yoctonear(registration_cost),
             MIN_GAS_FOR_STORAGE_DEPOSIT,
             GasWeight(0),
         );
+    }
 
-        // Send the fungible tokens (after the storage attached_deposit is finished since these run sequentially)
-        // Call the function with the min GAS and then attach 1/5 of the unspent GAS to the call
-        batch_transfer = batch_transfer.function_call_weight(
-            "ft_transfer".to_string(),
-            json!({ "receiver_id": receiver_id, "amount": transfer_amount.to_string(), "memo": "Keypom FT Tokens" }).to_string().into(),
-            NearToken::from_near(1),
-            MIN_GAS_FOR_FT_TRANSFER,
-            GasWeight(0)
-        );
+    // Send the fungible tokens (after the storage attached_deposit is finished since these run sequentially)
+    batch_transfer.function_call_weight(
+        "ft_transfer".to_string(),
+        json!({ "receiver_id": receiver_id, "amount": transfer_amount.to_string(), "memo": "Keypom FT Tokens" }).to_string().into(),
+        NearToken::from_yoctonear(1),
+        MIN_GAS_FOR_FT_TRANSFER,
+        GasWeight(0)
+    )
+}
 
-        Some(batch_transfer)
+/// Whether the `storage_balance_of` call that triggered the current callback found a registered account.
+/// If the check failed for whatever reason, the account is treated as unregistered.
+pub(crate) fn was_storage_registered() -> bool {
+    match env::promise_result(0) {
+        PromiseResult::Successful(value) => {
+            near_sdk::serde_json::from_slice::<near_sdk::serde_json::Value>(&value)
+                .map(|balance| !balance.is_null())
+                .unwrap_or(false)
+        }
+        PromiseResult::Failed => false,
     }
 }
 
+#[near_bindgen]
+impl Keypom {
+    /// Callback after `storage_balance_of` for claims with the `when_needed` registration policy.
+    /// Registers the receiver only if they aren't already registered and then transfers the FTs.
+    #[private]
+    pub fn on_ft_registration_checked(
+        &mut self,
+        ft_contract_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        registration_cost: U128,
+        funder_id: AccountId,
+    ) -> Promise {
+        if !was_storage_registered() {
+            return ft_transfer_with_registration(
+                &ft_contract_id,
+                &receiver_id,
+                amount.0,
+                Some(registration_cost.0),
+            );
+        }
+
+        near_sdk::log!(
+            "{} is already registered on {}",
+            receiver_id,
+            ft_contract_id
+        );
+        // The registration cost is only credited back once the transfer goes through. If it fails,
+        // The standard refund in `on_assets_claimed` will take care of the registration cost.
+        ft_transfer_with_registration(&ft_contract_id, &receiver_id, amount.0, None).then(
+            Keypom::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_UNUSED_REGISTRATION)
+                .on_claimed_without_registration(funder_id, registration_cost),
+        )
+    }
+
+    /// Credit the unused registration cost back to the funder once an FT (or wrapped NEAR) transfer succeeded.
+    /// Panics if the transfer failed so that the failure propagates to `on_assets_claimed`.
+    #[private]
+    pub fn on_claimed_without_registration(
+        &mut self,
+        funder_id: AccountId,
+        registration_cost: U128,
+    ) {
+        require!(
+            matches!(env::promise_result(0), PromiseResult::Successful(_)),
+            "Transfer failed"
+        );
+
+        near_sdk::log!(
+            "Crediting unused registration cost of {} back to {}",
+            registration_cost.0,
+            funder_id
+        );
+        self.internal_modify_user_balance(&funder_id, registration_cost.0, false);
+    }
+}
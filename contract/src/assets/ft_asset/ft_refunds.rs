@@ -21,7 +21,7 @@ impl Keypom {
         ft_contract_id: AccountId,
         tokens_to_withdraw: U128,
     ) {
-        self.assert_no_global_freeze();
+        self.assert_not_paused(PausableAction::withdrawals);
 
         // get the drop object
         let mut drop = self.drop_by_id.get(&drop_id).expect("No drop found");
@@ -118,7 +118,7 @@ impl InternalFTData {
         // All FTs can be refunded at once. Funder responsible for registering themselves
         ext_ft_contract::ext(self.contract_id.clone())
             // Call ft transfer with 1 yoctoNEAR. 1/2 unspent GAS will be added on top
-            .with_attached_deposit(NearToken::from_near(1))
+            .with_attached_deposit(NearToken::from_yoctonear(1))
             .with_static_gas(MIN_GAS_FOR_FT_TRANSFER)
             .ft_transfer(
                 refund_to.clone(),
@@ -1,5 +1,23 @@
 use crate::*;
 
+/// How should claimers be registered on the FT contract before tokens are transferred?
+#[allow(non_camel_case_types)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum FTRegistrationPolicy {
+    /// Check `storage_balance_of` first and only pay for registration if the claimer isn't registered.
+    /// Any unused registration cost is credited back to the funder's balance.
+    #[default]
+    when_needed,
+    /// Always fire `storage_deposit` before transferring the tokens
+    always,
+    /// The claimer must already be registered on the FT contract. The funder is never charged for registration.
+    claimer_registered,
+}
+
 #[derive(BorshSerialize, BorshDeserialize, PanicOnDefault, Clone)]
 #[borsh(crate = "near_sdk::borsh")]
 pub struct InternalFTData {
@@ -10,6 +28,8 @@ pub struct InternalFTData {
     pub balance_avail: Balance,
     /// How much it costs to register a new user on the FT contract
     pub registration_cost: Balance,
+    /// Whether claimers should be registered on the FT contract and who pays for it
+    pub registration_policy: FTRegistrationPolicy,
 }
 
 // Implement a custom serialization that converts both `balance_avail` and `registration_cost` to a `U128` for the frontend
@@ -18,21 +38,35 @@ impl Serialize for InternalFTData {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("InternalFTData", 3)?;
+        let mut state = serializer.serialize_struct("InternalFTData", 4)?;
         state.serialize_field("contract_id", &self.contract_id)?;
         state.serialize_field("balance_avail", &U128(self.balance_avail))?;
         state.serialize_field("registration_cost", &U128(self.registration_cost))?;
+        state.serialize_field("registration_policy", &self.registration_policy)?;
         state.end()
     }
 }
 
 impl InternalFTData {
     /// Initialize a new set of FT data. The available balance is initialize to 0 at the start
-    pub fn new(contract_id: AccountId, registration_cost: Balance) -> Self {
+    pub fn new(
+        contract_id: AccountId,
+        registration_cost: Balance,
+        registration_policy: FTRegistrationPolicy,
+    ) -> Self {
         Self {
             contract_id,
             balance_avail: 0,
             registration_cost,
+            registration_policy,
+        }
+    }
+
+    /// How much $NEAR the funder is charged for registration on every claim. Claimers that must already be registered cost nothing.
+    pub fn get_charged_registration_cost(&self) -> Balance {
+        match self.registration_policy {
+            FTRegistrationPolicy::claimer_registered => 0,
+            _ => self.registration_cost,
         }
     }
 
@@ -48,7 +82,7 @@ impl InternalFTData {
 
     /// Query how much gas is required for a single claim
     pub fn get_required_asset_gas(&self) -> Gas {
-        Gas::from_gas(MIN_GAS_FOR_FT_TRANSFER.as_gas() + MIN_GAS_FOR_STORAGE_DEPOSIT.as_gas())
+        Gas::from_gas(MIN_GAS_FOR_FT_TRANSFER.as_gas() + self.get_registration_gas().as_gas())
     }
 
     /// Query how much gas is required for a single claim
@@ -56,7 +90,21 @@ impl InternalFTData {
         Gas::from_gas(
             GAS_FOR_FT_CLAIM_LOGIC.as_gas()
                 + MIN_GAS_FOR_FT_TRANSFER.as_gas()
-                + MIN_GAS_FOR_STORAGE_DEPOSIT.as_gas(),
+                + self.get_registration_gas().as_gas(),
         )
     }
+
+    /// Gas required to (potentially) register the claimer based on the registration policy
+    fn get_registration_gas(&self) -> Gas {
+        match self.registration_policy {
+            FTRegistrationPolicy::when_needed => Gas::from_gas(
+                MIN_GAS_FOR_STORAGE_BALANCE_OF.as_gas()
+                    + GAS_FOR_FT_REGISTRATION_CHECK.as_gas()
+                    + MIN_GAS_FOR_STORAGE_DEPOSIT.as_gas()
+                    + GAS_FOR_RESOLVE_UNUSED_REGISTRATION.as_gas(),
+            ),
+            FTRegistrationPolicy::always => MIN_GAS_FOR_STORAGE_DEPOSIT,
+            FTRegistrationPolicy::claimer_registered => Gas::from_gas(0),
+        }
+    }
 }
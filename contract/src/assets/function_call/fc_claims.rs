@@ -1,3 +1,5 @@
+use near_sdk::PromiseResult;
+
 use crate::*;
 
 /// Which methods are prohibited from being called by an FC drop
@@ -15,11 +17,20 @@ pub const DEFAULT_PROHIBITED_FC_METHODS: [&str; 6] = [
 pub const GAS_FOR_FC_CLAIM_LOGIC: Gas = Gas::from_tgas(2);
 /// For every length of the args, add this much gas
 pub const GAS_PER_ARG_LENGTH: Gas = Gas::from_gas(200000000);
+/// Gas needed to execute the logic in `on_fc_method_resolved` (excluding the methods it fires)
+/// 5 TGas
+pub const GAS_FOR_FC_METHOD_RESOLVE: Gas = Gas::from_tgas(5);
+/// Gas attached to `on_fc_chain_halted` which fails the FC asset once its chain was stopped
+/// 2 TGas
+pub const GAS_FOR_FC_CHAIN_HALTED: Gas = Gas::from_tgas(2);
+/// Placeholder inserted into the args of a method that depends on the previous method's return value.
+/// It's replaced with the actual value in `on_fc_method_resolved`
+pub const PREVIOUS_RESULT_MARKER: &str = "KEYPOM_PREVIOUS_RESULT";
 
 impl FCData {
     /// Loop through each method and create a promise to call the method
     /// Each of these methods will be executed 1 after the next.
-    /// If anything goes wrong, there's nothing the contract will do to refund.
+    /// Unless a method has `halt_on_failure` set or depends on the previous result, there's nothing the contract will do to refund if anything goes wrong.
     pub fn claim_fc_asset(
         &mut self,
         mut fc_args: AssetSpecificFCArgs,
@@ -28,7 +39,7 @@ impl FCData {
         key_id: String,
         funder_id: AccountId,
     ) -> Option<Promise> {
-        let mut promises = Vec::new();
+        let mut prepared_methods = Vec::new();
         let num_fc_args = fc_args
             .as_ref()
             .and_then(|a| Some(a.len()))
@@ -62,6 +73,24 @@ impl FCData {
                 }
             }
 
+            let previous_result_field = method
+                .keypom_args
+                .as_ref()
+                .and_then(|a| a.previous_result_field.clone());
+            // The previous result can only be piped in if the method right before this one is actually fired
+            if previous_result_field.is_some()
+                && prepared_methods
+                    .last()
+                    .map(|m: &PreparedFCMethod| m.method_idx as usize + 1 != idx)
+                    .unwrap_or(true)
+            {
+                near_sdk::log!(
+                    "Previous method was skipped so its result cannot be piped into method {}. Skipping method.",
+                    idx
+                );
+                continue;
+            }
+
             let receiver_id = match determine_receiver_id(
                 &method.receiver_to_claimer,
                 &method.receiver_id,
@@ -74,17 +103,209 @@ impl FCData {
                 }
             };
 
-            let promise = Promise::new(receiver_id).function_call_weight(
-                method.method_name.clone(),
-                actual_args.into(),
-                NearToken::from_yoctonear(method.attached_deposit.0),
-                method.attached_gas,
-                GasWeight(1),
+            prepared_methods.push(PreparedFCMethod {
+                method_idx: idx as u32,
+                receiver_id,
+                method_name: method.method_name.clone(),
+                args: actual_args,
+                attached_deposit: method.attached_deposit,
+                attached_gas: method.attached_gas,
+                halt_on_failure: method.halt_on_failure.unwrap_or(false),
+                uses_previous_result: previous_result_field.is_some(),
+                previous_result_path: method
+                    .keypom_args
+                    .as_ref()
+                    .and_then(|a| a.previous_result_path.clone()),
+            });
+        }
+
+        chain_fc_methods(prepared_methods, funder_id)
+    }
+}
+
+/// Whether a callback to `on_fc_method_resolved` needs to be inserted after a method.
+/// This is the case if it should halt on failure or if the next method depends on its return value.
+pub(crate) fn needs_fc_resolve_callback(
+    halt_on_failure: bool,
+    next_uses_previous_result: Option<bool>,
+) -> bool {
+    match next_uses_previous_result {
+        Some(uses_previous_result) => halt_on_failure || uses_previous_result,
+        None => false,
+    }
+}
+
+/// Chain the prepared methods 1 after the next. If a method should halt on failure or the next method depends on its result
+/// (and there are methods after it), A callback to `on_fc_method_resolved` is inserted which will fire the rest of the methods.
+pub(crate) fn chain_fc_methods(
+    methods: Vec<PreparedFCMethod>,
+    funder_id: AccountId,
+) -> Option<Promise> {
+    let mut chain: Option<Promise> = None;
+    let mut methods = methods.into_iter();
+
+    while let Some(method) = methods.next() {
+        let promise = Promise::new(method.receiver_id.clone()).function_call_weight(
+            method.method_name.clone(),
+            method.args.clone().into(),
+            NearToken::from_yoctonear(method.attached_deposit.0),
+            method.attached_gas,
+            GasWeight(1),
+        );
+        chain = Some(match chain {
+            Some(chain) => chain.then(promise),
+            None => promise,
+        });
+
+        let remaining_methods: Vec<PreparedFCMethod> = methods.clone().collect();
+        if needs_fc_resolve_callback(
+            method.halt_on_failure,
+            remaining_methods.first().map(|m| m.uses_previous_result),
+        ) {
+            // The callback needs enough gas to fire every remaining method (and any callbacks between them)
+            // Or to fail the asset if the chain is halted
+            let gas_for_remaining = remaining_methods.iter().enumerate().fold(
+                GAS_FOR_FC_METHOD_RESOLVE.as_gas() + GAS_FOR_FC_CHAIN_HALTED.as_gas(),
+                |gas, (i, m)| {
+                    let needs_callback = needs_fc_resolve_callback(
+                        m.halt_on_failure,
+                        remaining_methods.get(i + 1).map(|n| n.uses_previous_result),
+                    );
+                    gas + m.attached_gas.as_gas()
+                        + MIN_BASE_GAS_FOR_RECEIPT_SPIN_UP.as_gas()
+                        + needs_callback as u64
+                            * (GAS_FOR_FC_METHOD_RESOLVE.as_gas()
+                                + GAS_FOR_FC_CHAIN_HALTED.as_gas())
+                },
             );
-            promises.push(promise);
+
+            return chain.map(|chain| {
+                chain.then(
+                    Keypom::ext(env::current_account_id())
+                        .with_static_gas(Gas::from_gas(gas_for_remaining))
+                        .with_unused_gas_weight(1)
+                        .on_fc_method_resolved(method, remaining_methods, funder_id),
+                )
+            });
         }
+    }
 
-        promises.into_iter().reduce(|a, b| a.then(b))
+    chain
+}
+
+/// Remove any methods at the front of the list that depend on the result of a method that wasn't successfully resolved.
+/// Returns the total attached deposit of the removed methods.
+fn skip_dependent_fc_methods(remaining_methods: &mut Vec<PreparedFCMethod>) -> Balance {
+    let mut amount_skipped = 0;
+    while remaining_methods
+        .first()
+        .map(|m| m.uses_previous_result)
+        .unwrap_or(false)
+    {
+        let skipped = remaining_methods.remove(0);
+        near_sdk::log!(
+            "Skipping FC method {} ({} on {}) since the result it depends on is unavailable",
+            skipped.method_idx,
+            skipped.method_name,
+            skipped.receiver_id
+        );
+        amount_skipped += skipped.attached_deposit.0;
     }
+
+    amount_skipped
 }
 
+#[near_bindgen]
+impl Keypom {
+    /// Callback inserted after an FC method with `halt_on_failure` set or whose result is piped into the next method.
+    /// If the method succeeded, its return value is inserted into the next method's args (if needed) and the remaining methods are fired.
+    /// If it failed and should halt, the chain is stopped, the attached deposits of the failed and skipped methods are refunded to the funder
+    /// And the FC asset is failed through `on_fc_chain_halted` so that `on_assets_claimed` treats the claim as failed.
+    /// Otherwise, only the methods depending on its result are skipped and refunded.
+    #[private]
+    pub fn on_fc_method_resolved(
+        &mut self,
+        resolved_method: PreparedFCMethod,
+        remaining_methods: Vec<PreparedFCMethod>,
+        funder_id: AccountId,
+    ) -> PromiseOrValue<bool> {
+        let mut remaining_methods = remaining_methods;
+        let result = env::promise_result(0);
+        let succeeded = matches!(result, PromiseResult::Successful(_));
+
+        if !succeeded && resolved_method.halt_on_failure {
+            // The deposit of the failed method is refunded to the contract so it should be credited back as well
+            let amount_to_refund = remaining_methods
+                .iter()
+                .fold(resolved_method.attached_deposit.0, |total, m| {
+                    total + m.attached_deposit.0
+                });
+            near_sdk::log!(
+                "FC method {} ({} on {}) failed. Skipping {} remaining methods and refunding {} yoctoNEAR to {}",
+                resolved_method.method_idx,
+                resolved_method.method_name,
+                resolved_method.receiver_id,
+                remaining_methods.len(),
+                amount_to_refund,
+                funder_id
+            );
+            self.internal_modify_user_balance(&funder_id, amount_to_refund, false);
+
+            // Panicking here would revert the refund so the failure is surfaced in a separate receipt instead
+            return PromiseOrValue::Promise(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_FC_CHAIN_HALTED)
+                    .with_unused_gas_weight(0)
+                    .on_fc_chain_halted(resolved_method.method_idx),
+            );
+        }
+
+        let mut amount_to_refund = 0;
+        if !succeeded {
+            // The deposit of the failed method is refunded to the contract so it should be credited back as well
+            amount_to_refund += resolved_method.attached_deposit.0;
+        }
+
+        if let Some(next_method) = remaining_methods
+            .first_mut()
+            .filter(|m| m.uses_previous_result)
+        {
+            let resolved = match &result {
+                PromiseResult::Successful(value) => {
+                    extract_previous_result(value, &next_method.previous_result_path)
+                        .and_then(|v| set_previous_result(&mut next_method.args, v))
+                }
+                _ => Err(format!(
+                    "FC method {} failed so its result cannot be piped into the next method",
+                    resolved_method.method_idx
+                )),
+            };
+
+            match resolved {
+                Ok(_) => next_method.uses_previous_result = false,
+                Err(e) => near_sdk::log!("Error piping previous result: {:?}", e),
+            }
+        }
+        amount_to_refund += skip_dependent_fc_methods(&mut remaining_methods);
+
+        if amount_to_refund > 0 {
+            near_sdk::log!("Refunding {} yoctoNEAR to {}", amount_to_refund, funder_id);
+            self.internal_modify_user_balance(&funder_id, amount_to_refund, false);
+        }
+
+        match chain_fc_methods(remaining_methods, funder_id) {
+            Some(promise) => PromiseOrValue::Promise(promise),
+            // Methods that don't halt on failure never fail the asset
+            None => PromiseOrValue::Value(true),
+        }
+    }
+
+    /// Always fails. Fired once an FC chain was halted so that the asset's promise resolves as failed
+    #[private]
+    pub fn on_fc_chain_halted(&mut self, failed_method_idx: u32) {
+        env::panic_str(&format!(
+            "FC chain halted after method {} failed",
+            failed_method_idx
+        ));
+    }
+}
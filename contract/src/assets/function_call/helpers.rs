@@ -111,6 +111,52 @@ pub(crate) fn insert_keypom_arg(
     Ok(())
 }
 
+/// Given the raw return value of the previous method, navigate to the value at the specified path.
+/// Strings are returned as is while any other JSON value is stringified.
+pub(crate) fn extract_previous_result(
+    result: &[u8],
+    path: &Option<String>
+) -> Result<String, String> {
+    let try_json: Result<Value, _> = serde_json::from_slice(result);
+    if try_json.is_err() {
+        return Err("Cannot cast previous result to JSON. Skipping method.".to_string());
+    }
+
+    let mut value = try_json.unwrap();
+    if let Some(path) = path {
+        for segment in path.split('.') {
+            let next = match &value {
+                Value::Object(obj) => obj.get(segment).cloned(),
+                Value::Array(arr) => segment.parse::<usize>().ok().and_then(|i| arr.get(i).cloned()),
+                _ => None
+            };
+
+            value = next.ok_or(format!("Path {} not found in previous result. Skipping method.", path))?;
+        }
+    }
+
+    match value {
+        Value::String(s) => Ok(s),
+        Value::Null => Err("Previous result is null. Skipping method.".to_string()),
+        other => Ok(other.to_string())
+    }
+}
+
+/// Replace the previous result marker that was inserted by `insert_keypom_arg` with the actual value.
+pub(crate) fn set_previous_result(
+    output_args: &mut String,
+    value: String
+) -> Result<(), String> {
+    let marker = format!("\"{}\"", PREVIOUS_RESULT_MARKER);
+    // The marker is always inserted after any funder or user args so the last occurrence is the one Keypom added
+    let idx = output_args.rfind(&marker).ok_or("Previous result marker not found in args. Skipping method.".to_string())?;
+    let val_to_insert = to_string(&Value::String(value)).unwrap();
+    output_args.replace_range(idx..idx + marker.len(), &val_to_insert);
+
+    near_sdk::log!("Args after inserting previous result: {}", output_args);
+    Ok(())
+}
+
 pub(crate) fn handle_user_args_rules (
     output_args: &mut String, 
     user_args_rule: &Option<UserArgsRule>,
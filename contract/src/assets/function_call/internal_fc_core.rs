@@ -18,6 +18,15 @@ impl FCData {
             "Must have at least 1 method in FC assets"
         );
 
+        require!(
+            methods[0]
+                .keypom_args
+                .as_ref()
+                .and_then(|a| a.previous_result_field.as_ref())
+                .is_none(),
+            "First FC method cannot use the previous result"
+        );
+
         for method in methods.iter() {
             // Check if the method is prohibited
             require!(
@@ -53,7 +62,7 @@ impl FCData {
     pub fn get_required_asset_gas(&self) -> Gas {
         // Loop through all the methods, tally up their attached gas and then:
         // Total Method Gas + MIN_BASE_GAS_FOR_ONE_CCC * num_methods + GAS_FOR_FC_CLAIM_LOGIC + num_methods + the length of the arguments
-        let mut total_gas = 0;
+        let mut total_gas = self.get_resolve_callback_gas();
         for method in self.methods.iter() {
             total_gas += method.attached_gas.as_gas();
             total_gas +=
@@ -67,7 +76,7 @@ impl FCData {
     pub fn get_total_required_gas(&self) -> Gas {
         // Loop through all the methods, tally up their attached gas and then:
         // Total Method Gas + MIN_BASE_GAS_FOR_ONE_CCC * num_methods + GAS_FOR_FC_CLAIM_LOGIC + num_methods + the length of the arguments
-        let mut total_gas = 0;
+        let mut total_gas = self.get_resolve_callback_gas();
         for method in self.methods.iter() {
             let arg_len = method.args.len() as u64;
             total_gas += GAS_PER_ARG_LENGTH.as_gas() * arg_len;
@@ -78,4 +87,30 @@ impl FCData {
 
         Gas::from_gas(total_gas)
     }
+
+    /// Gas required for the `on_fc_method_resolved` callbacks inserted after methods that halt on failure or whose result is piped
+    /// Into the next method (and for failing the asset if the chain is halted). The last method never needs a callback since there is nothing left to fire.
+    fn get_resolve_callback_gas(&self) -> u64 {
+        let num_callbacks = self
+            .methods
+            .windows(2)
+            .filter(|pair| {
+                needs_fc_resolve_callback(
+                    pair[0].halt_on_failure.unwrap_or(false),
+                    Some(
+                        pair[1]
+                            .keypom_args
+                            .as_ref()
+                            .and_then(|a| a.previous_result_field.as_ref())
+                            .is_some(),
+                    ),
+                )
+            })
+            .count() as u64;
+
+        num_callbacks
+            * (GAS_FOR_FC_METHOD_RESOLVE.as_gas()
+                + GAS_FOR_FC_CHAIN_HALTED.as_gas()
+                + 2 * MIN_BASE_GAS_FOR_RECEIPT_SPIN_UP.as_gas())
+    }
 }
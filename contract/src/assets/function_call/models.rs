@@ -24,6 +24,34 @@ pub struct MethodData {
     /// What permissions does the user have when providing custom arguments to the function call?
     /// By default, the user cannot provide any custom arguments
     pub user_args_rule: Option<UserArgsRule>,
+    /// If set to true, Keypom will check the result of this method before firing the next one. If it failed,
+    /// The remaining methods are skipped and their attached deposits are refunded to the funder.
+    pub halt_on_failure: Option<bool>,
+}
+
+/// A method whose arguments and receiver have been resolved and is ready to be fired.
+/// Used to pass the remaining methods to `on_fc_method_resolved` when halting on failure or piping results.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PreparedFCMethod {
+    /// Index of the method in the FC asset
+    pub method_idx: u32,
+    /// Contract that will be called
+    pub receiver_id: AccountId,
+    /// Method to call on receiver_id contract
+    pub method_name: String,
+    /// Final arguments (stringified JSON) including any keypom and user args
+    pub args: String,
+    /// Amount of yoctoNEAR to attach along with the call
+    pub attached_deposit: U128,
+    /// How much gas to attach to this method call.
+    pub attached_gas: Gas,
+    /// Whether the chain should halt if this method fails
+    pub halt_on_failure: bool,
+    /// Whether the args still contain the previous result marker that needs to be resolved before firing
+    pub uses_previous_result: bool,
+    /// Path into the previous method's return value to insert into the args
+    pub previous_result_path: Option<String>,
 }
 
 /// Keypom Args struct to be sent to external contracts
@@ -43,6 +71,15 @@ pub struct KeypomInjectedArgs {
     // Specifies what field the funder id should go in when calling the function. To insert into nested objects, use periods to separate. For example, to insert into args.metadata.field, you would specify "metadata.field"
     // If Some(string), attach the funder ID to the args. Else, don't attach.
     pub funder_id_field: Option<String>,
+    /// Specifies what field the return value of the previous method should go in when calling the function.
+    /// The value is resolved in a Keypom callback once the previous method has finished. If that method failed or its result
+    /// couldn't be read, this method is skipped and its attached deposit is refunded to the funder. Cannot be set on the first method.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_result_field: Option<String>,
+    /// Path into the previous method's JSON return value to insert. Use periods to separate nested fields and array indices.
+    /// For example, "tokens.0.token_id" would insert result.tokens[0].token_id. If None, the entire return value is inserted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_result_path: Option<String>,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, Debug)]
@@ -57,4 +94,3 @@ pub enum UserArgsRule {
     FunderPreferred,
     UserPreferred,
 }
-
@@ -1,7 +1,11 @@
 pub mod ft_asset;
 pub mod nft_asset;
+pub mod mt_asset;
+pub mod wnear_asset;
 pub mod function_call;
 
 pub use ft_asset::*;
 pub use function_call::*;
-pub use nft_asset::*;
\ No newline at end of file
+pub use mt_asset::*;
+pub use nft_asset::*;
+pub use wnear_asset::*;
\ No newline at end of file
@@ -0,0 +1,63 @@
+use crate::*;
+
+#[derive(BorshSerialize, BorshDeserialize, PanicOnDefault, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct InternalMTData {
+    /// Account ID of the multi token contract
+    pub contract_id: AccountId,
+    /// Which token on the multi token contract this asset represents
+    pub token_id: String,
+    /// Number of tokens that are available for transfer.
+    /// To make tokens available for transfer, you must send them via `mt_transfer_call`.
+    pub balance_avail: Balance,
+}
+
+// Implement a custom serialization that converts `balance_avail` to a `U128` for the frontend
+impl Serialize for InternalMTData {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("InternalMTData", 3)?;
+        state.serialize_field("contract_id", &self.contract_id)?;
+        state.serialize_field("token_id", &self.token_id)?;
+        state.serialize_field("balance_avail", &U128(self.balance_avail))?;
+        state.end()
+    }
+}
+
+impl InternalMTData {
+    /// Initialize a new set of MT data. The available balance is initialize to 0 at the start
+    pub fn new(contract_id: AccountId, token_id: String) -> Self {
+        Self {
+            contract_id,
+            token_id,
+            balance_avail: 0,
+        }
+    }
+
+    /// Query whether or not enough multi tokens have been deposited to cover the claim
+    pub fn enough_balance(&self, amount: &Balance) -> bool {
+        &self.balance_avail >= amount
+    }
+
+    /// Get the asset ID for this asset. Since a multi token contract holds many tokens, the ID is a combination of the contract and token ID
+    pub fn get_asset_id(&self) -> AssetId {
+        get_mt_asset_id(&self.contract_id, &self.token_id)
+    }
+
+    /// Query how much gas is required for a single claim
+    pub fn get_required_asset_gas(&self) -> Gas {
+        MIN_GAS_FOR_MT_TRANSFER
+    }
+
+    /// Query how much gas is required for a single claim
+    pub fn get_total_required_gas(&self) -> Gas {
+        Gas::from_gas(GAS_FOR_MT_CLAIM_LOGIC.as_gas() + MIN_GAS_FOR_MT_TRANSFER.as_gas())
+    }
+}
+
+/// Asset ID for a given token on a multi token contract
+pub fn get_mt_asset_id(contract_id: &AccountId, token_id: &str) -> AssetId {
+    format!("{}{}:{}", MT_ASSET_PREFIX, contract_id, token_id)
+}
@@ -0,0 +1,7 @@
+pub mod internal_mt_core;
+pub mod mt_claims;
+pub mod mt_balances;
+pub mod mt_refunds;
+
+pub use internal_mt_core::*;
+pub use mt_claims::*;
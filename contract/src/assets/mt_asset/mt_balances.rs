@@ -0,0 +1,95 @@
+use crate::*;
+
+impl InternalMTData {
+    /// Add to the available balance. This should only ever be invoked in:
+    /// * `mt_on_transfer` (when the transfer is successful).
+    /// * `mt_resolve_refund` (when the refund failed).
+    /// * `on_assets_claimed` (when the MT claim failed and we need to refund).
+    pub fn add_to_balance_avail(&mut self, amount: &Balance) {
+        self.balance_avail += amount;
+    }
+}
+
+#[near_bindgen]
+impl Keypom {
+    /// Standard NEP-245 function for accepting multi tokens to then be claimable as part of linkdrops.
+    ///
+    /// The `msg` can either be the drop ID or a JSON `ExtDepositMsg`. Returns how many of each token should be refunded.
+    /// Any tokens beyond what the keys in the drop still need are returned unless `refund_overflow` is set to false.
+    /// Tokens without a matching MT asset in the drop are returned in full.
+    /// Like FTs, MT balances are shared by every key in the drop so deposits that target a specific key or use are rejected.
+    pub fn mt_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_ids: Vec<AccountId>,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        self.assert_not_paused(PausableAction::deposits);
+        require!(
+            token_ids.len() == amounts.len(),
+            "Token IDs and amounts must be the same length"
+        );
+        near_sdk::log!(
+            "Receiving MTs from {} (previous owners {:?})",
+            sender_id,
+            previous_owner_ids
+        );
+
+        let ExtDepositMsg {
+            drop_id,
+            refund_overflow,
+            ..
+        } = ExtDepositMsg::parse_fungible(msg);
+        let contract_id = env::predecessor_account_id();
+        let mut drop: InternalDrop = self.drop_by_id.get(&drop_id).expect("Drop not found");
+        drop.assert_can_deposit(&sender_id);
+
+        let mut refunds = Vec::with_capacity(token_ids.len());
+        for (token_id, amount) in token_ids.iter().zip(amounts.iter()) {
+            let asset_id = get_mt_asset_id(&contract_id, token_id);
+            let mut asset = drop
+                .asset_by_id
+                .get(&asset_id)
+                .unwrap_or(InternalAsset::none);
+            // Ensure asset is a multi token and then call the internal function
+            let InternalAsset::mt(mt_data) = &mut asset else {
+                near_sdk::log!(
+                    "No MT asset for {} in drop {}. Refunding {} MTs",
+                    asset_id,
+                    drop_id,
+                    amount.0
+                );
+                refunds.push(*amount);
+                continue;
+            };
+
+            let mut amount_to_refund = 0;
+            if refund_overflow.unwrap_or(true) {
+                let outstanding = drop
+                    .get_tokens_needed_for_asset(&asset_id)
+                    .saturating_sub(mt_data.balance_avail);
+                amount_to_refund = amount.0.saturating_sub(outstanding);
+            }
+
+            let amount_to_add = amount.0 - amount_to_refund;
+            mt_data.add_to_balance_avail(&amount_to_add);
+            near_sdk::log!(
+                "Added {} of MT {} to drop ID {}. Refunding {}. New asset amount: {}",
+                amount_to_add,
+                token_id,
+                drop_id,
+                amount_to_refund,
+                mt_data.balance_avail
+            );
+
+            drop.asset_by_id.insert(&asset_id, &asset);
+            refunds.push(U128(amount_to_refund));
+        }
+
+        self.drop_by_id.insert(&drop_id, &drop);
+
+        PromiseOrValue::Value(refunds)
+    }
+}
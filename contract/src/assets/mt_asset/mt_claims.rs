@@ -0,0 +1,48 @@
+use near_sdk::{serde_json::json, Gas, GasWeight};
+
+use crate::*;
+
+/// Gas needed to execute any logic in the mt claim function
+/// 5 TGas
+pub const GAS_FOR_MT_CLAIM_LOGIC: Gas = Gas::from_tgas(5);
+/// Minimum Gas required to perform a simple transfer of multi tokens.
+/// 10 TGas
+pub const MIN_GAS_FOR_MT_TRANSFER: Gas = Gas::from_tgas(10);
+
+impl InternalMTData {
+    /// Attempt to transfer multi tokens to a given address.
+    /// If the transfer fails, the tokens will be returned to the available balance
+    /// Should *only* be invoked if the available balance is greater than or equal to the transfer amount.
+    pub fn claim_mt_asset(
+        &mut self,
+        receiver_id: &AccountId,
+        transfer_amount: &Balance,
+    ) -> Option<Promise> {
+        if !self.enough_balance(transfer_amount) {
+            near_sdk::log!(
+                "not enough balance to transfer. Found {} but needed {}. Skipping asset claim.",
+                self.balance_avail,
+                transfer_amount
+            );
+            return None;
+        }
+
+        // Decrement the available balance and then invoke the transfer
+        self.balance_avail -= transfer_amount;
+
+        Some(Promise::new(self.contract_id.clone()).function_call_weight(
+            "mt_transfer".to_string(),
+            json!({
+                "receiver_id": receiver_id,
+                "token_id": self.token_id,
+                "amount": transfer_amount.to_string(),
+                "memo": "Keypom MT Tokens"
+            })
+            .to_string()
+            .into(),
+            NearToken::from_yoctonear(1),
+            MIN_GAS_FOR_MT_TRANSFER,
+            GasWeight(0),
+        ))
+    }
+}
@@ -0,0 +1,135 @@
+use near_sdk::PromiseResult;
+
+use crate::*;
+
+/// Minimum Gas required to resolve the refund of multi tokens.
+/// 5 TGas
+const MIN_GAS_FOR_RESOLVE_REFUND: Gas = Gas::from_tgas(5);
+
+#[near_bindgen]
+impl Keypom {
+    /// Allows the funder to withdraw multi tokens from a drop.
+    pub fn withdraw_mt_balance(
+        &mut self,
+        drop_id: DropId,
+        mt_contract_id: AccountId,
+        token_id: String,
+        tokens_to_withdraw: U128,
+    ) {
+        self.assert_not_paused(PausableAction::withdrawals);
+
+        // get the drop object
+        let mut drop = self.drop_by_id.get(&drop_id).expect("No drop found");
+        let funder_id = &drop.funder_id;
+
+        require!(
+            funder_id == &env::predecessor_account_id(),
+            "Only drop funder can withdraw multi tokens"
+        );
+
+        let asset_id = get_mt_asset_id(&mt_contract_id, &token_id);
+        let mut asset: InternalAsset = drop
+            .asset_by_id
+            .get(&asset_id)
+            .expect("Asset not found");
+        // Ensure asset is a multi token and then call the internal function
+        if let InternalAsset::mt(mt_data) = &mut asset {
+            mt_data.mt_refund(&drop_id, tokens_to_withdraw.into(), &drop.funder_id);
+        };
+
+        drop.asset_by_id.insert(&asset_id, &asset);
+
+        self.drop_by_id.insert(&drop_id, &drop);
+    }
+
+    #[private]
+    pub fn mt_resolve_refund(
+        &mut self,
+        drop_id: DropId,
+        asset_id: AssetId,
+        tokens_to_transfer: Balance,
+    ) -> bool {
+        let transfer_succeeded = matches!(env::promise_result(0), PromiseResult::Successful(_));
+
+        if transfer_succeeded {
+            near_sdk::log!(
+                "Successfully refunded {} MTs for drop ID {}",
+                tokens_to_transfer,
+                drop_id,
+            );
+
+            return true;
+        }
+
+        near_sdk::log!(
+            "Failed to refund {} MTs for drop ID {}",
+            tokens_to_transfer,
+            drop_id,
+        );
+
+        // Transfer failed so we need to add the tokens back to the available balance
+        let mut drop = self.drop_by_id.get(&drop_id).expect("no drop for ID");
+        let mut internal_asset = drop.asset_by_id.get(&asset_id).expect("no asset for ID");
+
+        if let InternalAsset::mt(ref mut mt_asset) = internal_asset {
+            mt_asset.add_to_balance_avail(&tokens_to_transfer);
+            drop.asset_by_id.insert(&asset_id, &internal_asset);
+        } else {
+            panic!("asset is not MT");
+        }
+
+        self.drop_by_id.insert(&drop_id, &drop);
+
+        false
+    }
+}
+
+impl InternalMTData {
+    /// Refund multi tokens from the drop back to the funder
+    pub fn mt_refund(
+        &mut self,
+        drop_id: &DropId,
+        tokens_to_transfer: Balance,
+        refund_to: &AccountId,
+    ) {
+        require!(
+            self.enough_balance(&tokens_to_transfer),
+            format!(
+                "not enough balance to transfer. Found {} but needed {}",
+                self.balance_avail, tokens_to_transfer
+            )
+        );
+
+        near_sdk::log!("Refunding {} MTs to {}", tokens_to_transfer, refund_to);
+
+        // Temporarily decrease the available balance
+        // Once the MTs are transferred, we will check whether it failed and refund there
+        self.balance_avail -= tokens_to_transfer;
+
+        Promise::new(self.contract_id.clone())
+            .function_call_weight(
+                "mt_transfer".to_string(),
+                json!({
+                    "receiver_id": refund_to,
+                    "token_id": self.token_id,
+                    "amount": tokens_to_transfer.to_string(),
+                    "memo": "Keypom Refund"
+                })
+                .to_string()
+                .into(),
+                NearToken::from_yoctonear(1),
+                MIN_GAS_FOR_MT_TRANSFER,
+                GasWeight(0),
+            )
+            .then(
+                Keypom::ext(env::current_account_id())
+                    .with_static_gas(MIN_GAS_FOR_RESOLVE_REFUND)
+                    .mt_resolve_refund(
+                        drop_id.to_string(),
+                        self.get_asset_id(),
+                        tokens_to_transfer,
+                    ),
+            )
+            .as_return();
+    }
+}
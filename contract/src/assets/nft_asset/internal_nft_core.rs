@@ -1,5 +1,36 @@
 use crate::*;
 
+/// In what order should NFTs in the pool be handed out to claimers?
+#[allow(non_camel_case_types)]
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum NFTDistribution {
+    /// The most recently deposited NFT is claimed first
+    #[default]
+    lifo,
+    /// The first deposited NFT is claimed first
+    fifo,
+    /// A random NFT from the pool is claimed, seeded from the block's random seed
+    random,
+}
+
+/// A token ID that is reserved for a specific key (and optionally a specific use of that key)
+/// Rather than being handed out from the shared pool.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct PinnedNFT {
+    /// The token ID that is reserved
+    pub token_id: TokenId,
+    /// The ID of the key that will receive the token
+    pub key_id: u64,
+    /// Which use of the key will receive the token. If not specified, the next claim of the key for this asset will receive it
+    pub use_number: Option<UseNumber>,
+}
+
 #[derive(BorshSerialize, BorshDeserialize, PanicOnDefault, Debug, Serialize, Clone)]
 #[serde(crate = "near_sdk::serde")]
 #[borsh(crate = "near_sdk::borsh")]
@@ -8,20 +39,26 @@ pub struct InternalNFTData {
     pub contract_id: AccountId,
     /// Token IDs that are available to be claimed and have been sent via `nft_transfer_call`
     pub token_ids: Vec<TokenId>,
+    /// In what order the token IDs are handed out to claimers
+    pub distribution: NFTDistribution,
+    /// Token IDs that are reserved for specific keys and are not part of the shared pool
+    pub pinned_tokens: Vec<PinnedNFT>,
 }
 
 impl InternalNFTData {
-    /// Initialize a new set of FT data. The available balance is initialize to 0 at the start
-    pub fn new(contract_id: AccountId) -> Self {
+    /// Initialize a new set of NFT data. There are no token IDs available at the start
+    pub fn new(contract_id: AccountId, distribution: NFTDistribution) -> Self {
         Self {
             contract_id,
             token_ids: vec![],
+            distribution,
+            pinned_tokens: vec![],
         }
     }
 
-    /// Query whether or not there is at least 1 NFT to transfer
+    /// Query whether or not there is at least 1 NFT (pooled or pinned) held for the drop
     pub fn is_empty(&self) -> bool {
-        self.token_ids.is_empty()
+        self.token_ids.is_empty() && self.pinned_tokens.is_empty()
     }
 
     /// Get the asset ID for this asset. In the case of Non Fungible Tokens, the ID is the contract
@@ -29,9 +66,51 @@ impl InternalNFTData {
         self.contract_id.to_string()
     }
 
-    /// Query whether or not there is at least 1 NFT to transfer
-    pub fn get_next_token_id(&self) -> Option<TokenId> {
-        self.token_ids.last().cloned()
+    /// Query which token ID will be transferred in the next claim for a given key and use.
+    /// Tokens pinned to the key take precedence over the shared pool.
+    pub fn get_next_token_id(&self, key_id: u64, use_number: UseNumber) -> Option<TokenId> {
+        if let Some(pin_idx) = self.get_pinned_token_idx(key_id, use_number) {
+            return Some(self.pinned_tokens[pin_idx].token_id.clone());
+        }
+
+        self.get_next_token_idx()
+            .map(|idx| self.token_ids[idx].clone())
+    }
+
+    /// Index of the token pinned to a given key and use. Pins for that exact use take precedence over pins for any use of the key.
+    pub fn get_pinned_token_idx(&self, key_id: u64, use_number: UseNumber) -> Option<usize> {
+        self.pinned_tokens
+            .iter()
+            .position(|p| p.key_id == key_id && p.use_number == Some(use_number))
+            .or_else(|| {
+                self.pinned_tokens
+                    .iter()
+                    .position(|p| p.key_id == key_id && p.use_number.is_none())
+            })
+    }
+
+    /// Index of the token ID that will be transferred in the next claim based on the distribution mode.
+    /// For random distributions, this is deterministic within the same receipt (as long as the pool doesn't change)
+    /// So `get_next_token_id` and `claim_nft_asset` always agree on the token.
+    pub fn get_next_token_idx(&self) -> Option<usize> {
+        if self.token_ids.is_empty() {
+            return None;
+        }
+
+        let num_tokens = self.token_ids.len();
+        match self.distribution {
+            NFTDistribution::lifo => Some(num_tokens - 1),
+            NFTDistribution::fifo => Some(0),
+            NFTDistribution::random => {
+                // Mix in the pool size so that multiple claims in the same block don't all land on the same index
+                let mut seed = env::random_seed();
+                seed.extend_from_slice(&(num_tokens as u64).to_le_bytes());
+                let hash = env::sha256(&seed);
+                let mut random_bytes = [0u8; 8];
+                random_bytes.copy_from_slice(&hash[..8]);
+                Some((u64::from_le_bytes(random_bytes) % num_tokens as u64) as usize)
+            }
+        }
     }
 
     /// Query how much gas is required for a single claim
@@ -9,46 +9,128 @@ impl InternalNFTData {
     pub fn add_to_token_ids(&mut self, token_id: &TokenId) {
         self.token_ids.push(token_id.clone());
     }
+
+    /// Reserve a token ID for a specific key (and optionally a specific use of that key).
+    /// The token is taken out of the shared pool or, if it was already pinned, re-assigned.
+    pub fn pin_token_id(&mut self, pin: PinnedNFT) {
+        if let Some(idx) = self.token_ids.iter().position(|t| *t == pin.token_id) {
+            self.token_ids.remove(idx);
+        } else {
+            let idx = self
+                .pinned_tokens
+                .iter()
+                .position(|p| p.token_id == pin.token_id)
+                .expect("Token ID not found in drop");
+            self.pinned_tokens.remove(idx);
+        }
+
+        require!(
+            !self
+                .pinned_tokens
+                .iter()
+                .any(|p| p.key_id == pin.key_id && p.use_number == pin.use_number),
+            format!(
+                "A token is already pinned to key {} for use {:?}",
+                pin.key_id, pin.use_number
+            )
+        );
+        self.pinned_tokens.push(pin);
+    }
+
+    /// Release a pinned token ID back into the shared pool
+    pub fn unpin_token_id(&mut self, token_id: &TokenId) {
+        let idx = self
+            .pinned_tokens
+            .iter()
+            .position(|p| p.token_id == *token_id)
+            .expect("Token ID is not pinned");
+        self.pinned_tokens.remove(idx);
+        self.add_to_token_ids(token_id);
+    }
 }
 
 #[near_bindgen]
 impl Keypom {
     /// Standard function for accepting NFTs to then be claimable as part of linkdrops.
+    ///
+    /// The `msg` can either be the drop ID or a JSON `ExtDepositMsg`. If a key ID is passed in, the token
+    /// Will be pinned to that key (and optionally use) rather than added to the shared pool.
+    ///
+    /// The token is returned if the drop has no matching NFT asset or, unless `refund_overflow` is set to false,
+    /// If the drop already holds enough NFTs to cover every remaining claim.
     pub fn nft_on_transfer(
         &mut self,
         token_id: String,
         sender_id: AccountId,
-        msg: DropId,
+        msg: String,
     ) -> PromiseOrValue<bool> {
-        self.assert_no_global_freeze();
+        self.assert_not_paused(PausableAction::deposits);
 
         let initial_storage = env::storage_usage();
-        let drop_id = msg;
+        let ExtDepositMsg {
+            drop_id,
+            key_id: pin_key_id,
+            use_number: pin_use_number,
+            refund_overflow,
+        } = ExtDepositMsg::parse(msg);
         let asset_id = env::predecessor_account_id();
-        let mut drop: InternalDrop = self.drop_by_id.get(&drop_id).expect("Drop not found");
+        let Some(mut drop) = self.drop_by_id.get(&drop_id) else {
+            near_sdk::log!(
+                "Drop {} not found. Refunding token ID {}",
+                drop_id,
+                token_id
+            );
+            return PromiseOrValue::Value(true);
+        };
 
         // For NFTs, we should assert here so there's not a malicious attack where someone sends really long NFTs
         // and then the funder has to pay for the storage.
-        require!(
-            drop.funder_id == sender_id,
-            "Only the funder can add NFTs to the drop"
-        );
+        drop.assert_can_deposit(&sender_id);
 
-        let mut asset: InternalAsset = drop
+        let mut asset = drop
             .asset_by_id
             .get(&asset_id.to_string())
-            .expect("Asset not found");
+            .unwrap_or(InternalAsset::none);
         // Ensure asset is an NFT and then call the internal function
-        if let InternalAsset::nft(nft_data) = &mut asset {
-            nft_data.add_to_token_ids(&token_id);
+        let InternalAsset::nft(nft_data) = &mut asset else {
             near_sdk::log!(
-                "Added Token ID: {} to drop ID {}. There are now {} NFTs available for claim",
-                token_id,
+                "No NFT asset for {} in drop {}. Refunding token ID {}",
+                asset_id,
                 drop_id,
-                nft_data.token_ids.len() as u32
+                token_id
             );
+            return PromiseOrValue::Value(true);
         };
 
+        // Pinned tokens are taken out of the shared pool but still count towards what the drop holds
+        let tokens_held = (nft_data.token_ids.len() + nft_data.pinned_tokens.len()) as u128;
+        if refund_overflow.unwrap_or(true)
+            && tokens_held >= drop.get_tokens_needed_for_asset(&asset_id.to_string())
+        {
+            near_sdk::log!(
+                "Drop {} already has enough NFTs. Refunding token ID {}",
+                drop_id,
+                token_id
+            );
+            return PromiseOrValue::Value(true);
+        }
+
+        nft_data.add_to_token_ids(&token_id);
+        if let Some(key_id) = pin_key_id {
+            assert_valid_nft_pin(&drop, key_id, pin_use_number);
+            nft_data.pin_token_id(PinnedNFT {
+                token_id: token_id.clone(),
+                key_id,
+                use_number: pin_use_number,
+            });
+        }
+        near_sdk::log!(
+            "Added Token ID: {} to drop ID {}. There are now {} NFTs available for claim",
+            token_id,
+            drop_id,
+            nft_data.token_ids.len() as u32
+        );
+
         drop.asset_by_id.insert(&asset_id.to_string(), &asset);
 
         self.drop_by_id.insert(&drop_id, &drop);
@@ -63,3 +145,94 @@ impl Keypom {
     }
 }
 
+#[near_bindgen]
+impl Keypom {
+    /// Pin token IDs that have already been deposited to specific keys (and optionally specific uses of those keys).
+    /// Tokens that are already pinned will be re-assigned. Only the funder can pin tokens.
+    pub fn pin_nft_tokens(
+        &mut self,
+        drop_id: DropId,
+        nft_contract_id: AccountId,
+        pins: Vec<PinnedNFT>,
+    ) {
+        self.internal_modify_nft_pins(drop_id, nft_contract_id, |drop, nft_data| {
+            for pin in pins {
+                assert_valid_nft_pin(drop, pin.key_id, pin.use_number);
+                near_sdk::log!("Pinning token ID {} to key {}", pin.token_id, pin.key_id);
+                nft_data.pin_token_id(pin);
+            }
+        });
+    }
+
+    /// Release pinned token IDs back into the drop's shared pool. Only the funder can unpin tokens.
+    pub fn unpin_nft_tokens(
+        &mut self,
+        drop_id: DropId,
+        nft_contract_id: AccountId,
+        token_ids: Vec<TokenId>,
+    ) {
+        self.internal_modify_nft_pins(drop_id, nft_contract_id, |_, nft_data| {
+            for token_id in token_ids {
+                nft_data.unpin_token_id(&token_id);
+            }
+        });
+    }
+}
+
+impl Keypom {
+    /// Apply a modification to the pinned tokens of an NFT asset and charge (or refund) the funder for the net storage
+    fn internal_modify_nft_pins(
+        &mut self,
+        drop_id: DropId,
+        nft_contract_id: AccountId,
+        modify: impl FnOnce(&InternalDrop, &mut InternalNFTData),
+    ) {
+        self.assert_not_paused(PausableAction::deposits);
+
+        let initial_storage = env::storage_usage();
+        let mut drop: InternalDrop = self.drop_by_id.get(&drop_id).expect("Drop not found");
+        require!(
+            drop.funder_id == env::predecessor_account_id(),
+            "Only the funder can pin NFTs"
+        );
+
+        let asset_id = nft_contract_id.to_string();
+        let mut asset: InternalAsset = drop.asset_by_id.get(&asset_id).expect("Asset not found");
+        if let InternalAsset::nft(nft_data) = &mut asset {
+            modify(&drop, nft_data);
+        } else {
+            env::panic_str("Asset is not an NFT");
+        }
+
+        drop.asset_by_id.insert(&asset_id, &asset);
+        self.drop_by_id.insert(&drop_id, &drop);
+
+        let final_storage = env::storage_usage();
+        if final_storage > initial_storage {
+            let total_cost =
+                (final_storage - initial_storage) as u128 * env::storage_byte_cost().as_yoctonear();
+            self.internal_modify_user_balance(&drop.funder_id, total_cost, true);
+        } else {
+            let total_refund =
+                (initial_storage - final_storage) as u128 * env::storage_byte_cost().as_yoctonear();
+            self.internal_modify_user_balance(&drop.funder_id, total_refund, false);
+        }
+    }
+}
+
+/// Ensure that a token is being pinned to a key and use that can exist in the drop
+fn assert_valid_nft_pin(drop: &InternalDrop, key_id: u64, use_number: Option<UseNumber>) {
+    require!(
+        key_id < drop.next_key_id,
+        format!("Key {} does not exist in the drop", key_id)
+    );
+    if let Some(use_number) = use_number {
+        require!(
+            use_number > 0 && use_number <= drop.max_key_uses,
+            format!(
+                "Use {} does not exist. The drop has {} uses",
+                use_number, drop.max_key_uses
+            )
+        );
+    }
+}
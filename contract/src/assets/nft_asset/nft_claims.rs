@@ -13,17 +13,25 @@ pub const MIN_GAS_FOR_NFT_TRANSFER: Gas = Gas::from_tgas(4);
 pub const MIN_GAS_FOR_NFT_CALLBACK_LOGIC: Gas = Gas::from_tgas(2);
 
 impl InternalNFTData {
-    /// Attempt to transfer FTs to a given address (will cover registration automatically).
-    /// If the transfer fails, the FTs will be returned to the available balance
-    /// Should *only* be invoked if the available balance is greater than or equal to the transfer amount.
-    pub fn claim_nft_asset(&mut self, receiver_id: &AccountId) -> Option<Promise> {
-        if self.is_empty() {
+    /// Attempt to transfer the NFT pinned to the key (or the next NFT in the pool) to a given address.
+    /// If the transfer fails, the token ID will be returned in `on_assets_claimed`
+    pub fn claim_nft_asset(
+        &mut self,
+        receiver_id: &AccountId,
+        key_id: u64,
+        use_number: UseNumber,
+    ) -> Option<Promise> {
+        let token_to_transfer = if let Some(pin_idx) = self.get_pinned_token_idx(key_id, use_number)
+        {
+            // Tokens pinned to this key always take precedence over the shared pool
+            self.pinned_tokens.remove(pin_idx).token_id
+        } else if let Some(token_idx) = self.get_next_token_idx() {
+            // Remove the next NFT (based on the distribution mode) from the available NFTs
+            self.token_ids.remove(token_idx)
+        } else {
             near_sdk::log!("No NFTs available to transfer. Skipping asset claim.");
             return None;
-        }
-
-        // Pop the last NFT from the available NFTs
-        let token_to_transfer = self.token_ids.pop().unwrap();
+        };
 
         // Create a new promise to transfer the NFT to the new account ID
         let transfer_promise = Promise::new(self.contract_id.clone())
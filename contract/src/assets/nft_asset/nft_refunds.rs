@@ -17,7 +17,7 @@ impl Keypom {
         token_ids: Option<Vec<TokenId>>,
         limit: Option<u8>,
     ) -> Promise {
-        self.assert_no_global_freeze();
+        self.assert_not_paused(PausableAction::withdrawals);
 
         // get the drop object
         let mut drop = self.drop_by_id.get(&drop_id).expect("No drop found");
@@ -151,19 +151,25 @@ impl InternalNFTData {
         let mut batch_promise = Promise::new(self.contract_id.clone());
 
         // Loop through all the token IDs, and add to the batch promise while removing them from the vector
+        // Pinned tokens can be refunded as well, in which case their pin is removed
         for token_id in token_ids.clone() {
-            let index = self
-                .token_ids
-                .iter()
-                .position(|x| *x == token_id)
-                .expect("token ID not found");
-            near_sdk::log!(
-                "Refunding token ID {}. Idx: {} vec: {:?}",
-                token_id,
-                index,
-                self.token_ids
-            );
-            self.token_ids.remove(index);
+            if let Some(index) = self.token_ids.iter().position(|x| *x == token_id) {
+                near_sdk::log!(
+                    "Refunding token ID {}. Idx: {} vec: {:?}",
+                    token_id,
+                    index,
+                    self.token_ids
+                );
+                self.token_ids.remove(index);
+            } else {
+                let index = self
+                    .pinned_tokens
+                    .iter()
+                    .position(|p| p.token_id == token_id)
+                    .expect("token ID not found");
+                near_sdk::log!("Refunding pinned token ID {}", token_id);
+                self.pinned_tokens.remove(index);
+            }
 
             batch_promise = batch_promise
             .function_call_weight(
@@ -0,0 +1,66 @@
+use crate::*;
+
+#[derive(BorshSerialize, BorshDeserialize, PanicOnDefault, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct InternalWNEARData {
+    /// Account ID of the wrapped NEAR contract (i.e `wrap.near`)
+    pub contract_id: AccountId,
+    /// How much it costs to register a new user on the wrapped NEAR contract
+    pub registration_cost: Balance,
+}
+
+// Implement a custom serialization that converts `registration_cost` to a `U128` for the frontend
+impl Serialize for InternalWNEARData {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("InternalWNEARData", 2)?;
+        state.serialize_field("contract_id", &self.contract_id)?;
+        state.serialize_field("registration_cost", &U128(self.registration_cost))?;
+        state.end()
+    }
+}
+
+impl InternalWNEARData {
+    /// Initialize a new set of wrapped NEAR data. The $NEAR is held by the contract until it's claimed
+    pub fn new(contract_id: AccountId, registration_cost: Balance) -> Self {
+        Self {
+            contract_id,
+            registration_cost,
+        }
+    }
+
+    /// Get the asset ID for this asset. This is the prefix followed by the wrapped NEAR contract
+    pub fn get_asset_id(&self) -> AssetId {
+        get_wnear_asset_id(&self.contract_id)
+    }
+
+    /// How much $NEAR is spent for a single claim. This is the amount being wrapped, the registration cost
+    /// And the 1 yoctoNEAR attached to `ft_transfer`
+    pub fn get_yocto_cost(&self, amount_to_wrap: Balance) -> Balance {
+        amount_to_wrap + self.registration_cost + 1
+    }
+
+    /// Query how much gas is required for a single claim
+    pub fn get_required_asset_gas(&self) -> Gas {
+        Gas::from_gas(
+            MIN_GAS_FOR_STORAGE_BALANCE_OF.as_gas()
+                + GAS_FOR_WNEAR_REGISTRATION_CHECK.as_gas()
+                + GAS_FOR_RESOLVE_UNUSED_REGISTRATION.as_gas()
+                + MIN_GAS_FOR_NEAR_DEPOSIT.as_gas()
+                + MIN_GAS_FOR_WNEAR_STORAGE_DEPOSIT.as_gas()
+                + MIN_GAS_FOR_WNEAR_TRANSFER.as_gas(),
+        )
+    }
+
+    /// Query how much gas is required for a single claim
+    pub fn get_total_required_gas(&self) -> Gas {
+        Gas::from_gas(GAS_FOR_WNEAR_CLAIM_LOGIC.as_gas() + self.get_required_asset_gas().as_gas())
+    }
+}
+
+/// Asset ID for a given wrapped NEAR contract
+pub fn get_wnear_asset_id(contract_id: &AccountId) -> AssetId {
+    format!("{}{}", WNEAR_ASSET_PREFIX, contract_id)
+}
@@ -0,0 +1,6 @@
+pub mod internal_wnear_core;
+pub mod wnear_claims;
+pub mod wnear_registration;
+
+pub use internal_wnear_core::*;
+pub use wnear_claims::*;
@@ -0,0 +1,129 @@
+use near_sdk::{serde_json::json, Gas, GasWeight};
+
+use crate::*;
+
+/// Gas needed to execute any logic in the wrapped NEAR claim function
+/// 5 TGas
+pub const GAS_FOR_WNEAR_CLAIM_LOGIC: Gas = Gas::from_tgas(5);
+/// Minimum Gas required to wrap $NEAR on the wrapped NEAR contract
+/// 5 TGas
+pub const MIN_GAS_FOR_NEAR_DEPOSIT: Gas = Gas::from_tgas(5);
+/// Minimum Gas required to register a user on the wrapped NEAR contract
+/// 5 TGas
+pub const MIN_GAS_FOR_WNEAR_STORAGE_DEPOSIT: Gas = Gas::from_tgas(5);
+/// Minimum Gas required to transfer wrapped NEAR
+/// 5 TGas
+pub const MIN_GAS_FOR_WNEAR_TRANSFER: Gas = Gas::from_tgas(5);
+/// Gas needed to execute the logic in `on_wnear_registration_checked` (including firing the batch and resolve)
+/// 2 TGas + 2 * CCC gas (since there are 2 CCCs)
+/// 12 TGas
+pub const GAS_FOR_WNEAR_REGISTRATION_CHECK: Gas =
+    Gas::from_gas(Gas::from_tgas(2).as_gas() + 2 * MIN_BASE_GAS_FOR_RECEIPT_SPIN_UP.as_gas());
+
+impl InternalWNEARData {
+    /// Check whether the receiver is registered on the wrapped NEAR contract and then wrap the $NEAR held by the contract
+    /// And transfer it to them in `on_wnear_registration_checked`. The funder is only charged for registration if it was needed.
+    pub fn claim_wnear_asset(
+        &self,
+        receiver_id: &AccountId,
+        amount_to_wrap: &Balance,
+        funder_id: &AccountId,
+    ) -> Promise {
+        Promise::new(self.contract_id.clone())
+            .function_call_weight(
+                "storage_balance_of".to_string(),
+                json!({ "account_id": receiver_id }).to_string().into(),
+                NearToken::from_yoctonear(0),
+                MIN_GAS_FOR_STORAGE_BALANCE_OF,
+                GasWeight(0),
+            )
+            .then(
+                Keypom::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_WNEAR_REGISTRATION_CHECK)
+                    .with_unused_gas_weight(1)
+                    .on_wnear_registration_checked(
+                        self.contract_id.clone(),
+                        receiver_id.clone(),
+                        U128(*amount_to_wrap),
+                        U128(self.registration_cost),
+                        funder_id.clone(),
+                    ),
+            )
+    }
+}
+
+/// Create a batch promise that wraps the $NEAR, (optionally) pays for the receiver's storage and then transfers the wNEAR.
+/// All the actions are batched so that if any of them fail, the attached $NEAR is returned to the contract
+/// And can be refunded to the funder in `on_assets_claimed`.
+fn wnear_transfer_with_registration(
+    contract_id: &AccountId,
+    receiver_id: &AccountId,
+    amount_to_wrap: Balance,
+    registration_cost: Option<Balance>,
+) -> Promise {
+    // The Keypom contract is registered on the wNEAR contract when the drop is created so the full amount is minted
+    let mut batch_transfer = Promise::new(contract_id.clone()).function_call_weight(
+        "near_deposit".to_string(),
+        json!({}).to_string().into(),
+        NearToken::from_yoctonear(amount_to_wrap),
+        MIN_GAS_FOR_NEAR_DEPOSIT,
+        GasWeight(0),
+    );
+
+    if let Some(registration_cost) = registration_cost {
+        batch_transfer = batch_transfer.function_call_weight(
+            "storage_deposit".to_string(),
+            json!({ "account_id": receiver_id, "registration_only": true })
+                .to_string()
+                .into(),
+            NearToken::from_yoctonear(registration_cost),
+            MIN_GAS_FOR_WNEAR_STORAGE_DEPOSIT,
+            GasWeight(0),
+        );
+    }
+
+    batch_transfer.function_call_weight(
+        "ft_transfer".to_string(),
+        json!({ "receiver_id": receiver_id, "amount": amount_to_wrap.to_string(), "memo": "Keypom wNEAR" }).to_string().into(),
+        NearToken::from_yoctonear(1),
+        MIN_GAS_FOR_WNEAR_TRANSFER,
+        GasWeight(0),
+    )
+}
+
+#[near_bindgen]
+impl Keypom {
+    /// Callback after `storage_balance_of` for wrapped NEAR claims. Registers the receiver only if they aren't
+    /// Already registered and then wraps and transfers the $NEAR.
+    #[private]
+    pub fn on_wnear_registration_checked(
+        &mut self,
+        wnear_contract_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        registration_cost: U128,
+        funder_id: AccountId,
+    ) -> Promise {
+        if !was_storage_registered() {
+            return wnear_transfer_with_registration(
+                &wnear_contract_id,
+                &receiver_id,
+                amount.0,
+                Some(registration_cost.0),
+            );
+        }
+
+        near_sdk::log!(
+            "{} is already registered on {}",
+            receiver_id,
+            wnear_contract_id
+        );
+        // The registration cost is only credited back once the transfer goes through. If it fails,
+        // The standard refund in `on_assets_claimed` will take care of the registration cost.
+        wnear_transfer_with_registration(&wnear_contract_id, &receiver_id, amount.0, None).then(
+            Keypom::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_UNUSED_REGISTRATION)
+                .on_claimed_without_registration(funder_id, registration_cost),
+        )
+    }
+}
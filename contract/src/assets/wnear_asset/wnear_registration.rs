@@ -0,0 +1,138 @@
+use near_sdk::{serde_json::json, Gas, GasWeight, PromiseResult};
+
+use crate::*;
+
+/// Gas needed to execute the logic in `on_keypom_wnear_registration_checked` (including firing the deposit and resolve)
+/// 2 TGas + 2 * CCC gas (since there are 2 CCCs)
+/// 12 TGas
+pub const GAS_FOR_KEYPOM_WNEAR_REGISTRATION_CHECK: Gas =
+    Gas::from_gas(Gas::from_tgas(2).as_gas() + 2 * MIN_BASE_GAS_FOR_RECEIPT_SPIN_UP.as_gas());
+/// Gas needed to resolve the Keypom contract's registration on a wrapped NEAR contract
+/// 3 TGas
+pub const GAS_FOR_RESOLVE_KEYPOM_WNEAR_REGISTRATION: Gas = Gas::from_tgas(3);
+
+impl Keypom {
+    /// The Keypom contract needs to be registered on a wrapped NEAR contract before it can wrap $NEAR for claims. Otherwise, `near_deposit`
+    /// Would register it automatically and mint less than the amount attached. The first drop with a wNEAR asset for a given contract
+    /// Pays for the registration. Returns the contracts that should be registered with and the cost the funder should be charged.
+    pub(crate) fn internal_reserve_wnear_registrations(
+        &mut self,
+        asset_by_id: &UnorderedMap<AssetId, InternalAsset>,
+    ) -> (Vec<(AccountId, Balance)>, Balance) {
+        let mut registrations = Vec::new();
+        let mut registration_cost = 0;
+        for asset in asset_by_id.values() {
+            if let InternalAsset::wnear(wnear_data) = asset {
+                // Inserting right away makes sure other drops created before the registration resolves don't pay for it again
+                if self
+                    .registered_wnear_contracts
+                    .insert(&wnear_data.contract_id)
+                {
+                    registration_cost += wnear_data.registration_cost;
+                    registrations.push((wnear_data.contract_id, wnear_data.registration_cost));
+                }
+            }
+        }
+
+        (registrations, registration_cost)
+    }
+
+    /// Check whether the Keypom contract is already registered on the wrapped NEAR contract and register it in the callback if it isn't
+    pub(crate) fn internal_register_with_wnear_contract(
+        &self,
+        wnear_contract_id: AccountId,
+        registration_cost: Balance,
+        funder_id: AccountId,
+    ) -> Promise {
+        Promise::new(wnear_contract_id.clone())
+            .function_call_weight(
+                "storage_balance_of".to_string(),
+                json!({ "account_id": env::current_account_id() })
+                    .to_string()
+                    .into(),
+                NearToken::from_yoctonear(0),
+                MIN_GAS_FOR_STORAGE_BALANCE_OF,
+                GasWeight(0),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_KEYPOM_WNEAR_REGISTRATION_CHECK)
+                    .with_unused_gas_weight(1)
+                    .on_keypom_wnear_registration_checked(
+                        wnear_contract_id,
+                        U128(registration_cost),
+                        funder_id,
+                    ),
+            )
+    }
+}
+
+#[near_bindgen]
+impl Keypom {
+    /// Callback after checking whether the Keypom contract is registered on a wrapped NEAR contract. If it already is,
+    /// The registration cost is credited back to the funder. Otherwise, the contract registers itself.
+    #[private]
+    pub fn on_keypom_wnear_registration_checked(
+        &mut self,
+        wnear_contract_id: AccountId,
+        registration_cost: U128,
+        funder_id: AccountId,
+    ) -> PromiseOrValue<bool> {
+        if was_storage_registered() {
+            near_sdk::log!(
+                "Already registered on {}. Crediting {} back to {}",
+                wnear_contract_id,
+                registration_cost.0,
+                funder_id
+            );
+            self.internal_modify_user_balance(&funder_id, registration_cost.0, false);
+            return PromiseOrValue::Value(true);
+        }
+
+        PromiseOrValue::Promise(
+            Promise::new(wnear_contract_id.clone())
+                .function_call_weight(
+                    "storage_deposit".to_string(),
+                    json!({ "account_id": env::current_account_id(), "registration_only": true })
+                        .to_string()
+                        .into(),
+                    NearToken::from_yoctonear(registration_cost.0),
+                    MIN_GAS_FOR_WNEAR_STORAGE_DEPOSIT,
+                    GasWeight(0),
+                )
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(GAS_FOR_RESOLVE_KEYPOM_WNEAR_REGISTRATION)
+                        .on_keypom_wnear_registered(
+                            wnear_contract_id,
+                            registration_cost,
+                            funder_id,
+                        ),
+                ),
+        )
+    }
+
+    /// Callback after the Keypom contract registers itself on a wrapped NEAR contract. If the registration failed,
+    /// The deposit is credited back to the funder and the next drop with a wNEAR asset for that contract will try again.
+    #[private]
+    pub fn on_keypom_wnear_registered(
+        &mut self,
+        wnear_contract_id: AccountId,
+        registration_cost: U128,
+        funder_id: AccountId,
+    ) -> bool {
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            return true;
+        }
+
+        near_sdk::log!(
+            "Registration on {} failed. Crediting {} back to {}",
+            wnear_contract_id,
+            registration_cost.0,
+            funder_id
+        );
+        self.registered_wnear_contracts.remove(&wnear_contract_id);
+        self.internal_modify_user_balance(&funder_id, registration_cost.0, false);
+        false
+    }
+}
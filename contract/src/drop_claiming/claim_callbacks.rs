@@ -16,9 +16,9 @@ impl Keypom {
         let successful_creation = was_account_created();
 
         // If the account was successfully created, we should claim the assets
-        // Otherwise, we should loop through all the assets in the current use and refund the tokens
+        // Otherwise, the use is either held for a retry or the tokens for the assets in the current use are refunded
         if successful_creation {
-            return self.internal_claim_assets(
+            return self.internal_release_assets(
                 token_id,
                 receiver_id,
                 old_public_key,
@@ -27,66 +27,57 @@ impl Keypom {
             );
         }
 
-        let initial_storage = env::storage_usage();
-        let (drop_id, _) = parse_token_id(&token_id).unwrap();
-        let mut drop: InternalDrop = self.drop_by_id.get(&drop_id).expect("Drop not found");
-        let key_info = drop
-            .key_info_by_token_id
-            .get(&token_id)
-            .expect("Key not found");
-        // The uses were decremented before the account creation, so we need to increment them back to get what use should be refunded
-        let cur_key_use = get_key_cur_use(&drop, &key_info) - 1;
-        let InternalAssetDataForUses {
-            uses: _,
-            config: _,
-            assets_metadata,
-            required_asset_gas: _,
-        } = get_asset_data_for_specific_use(&drop.asset_data_for_uses, &cur_key_use);
-
-        for metadata in assets_metadata {
-            let amount_to_increment = drop
-                .asset_by_id
-                .get(&metadata.asset_id)
-                .expect("Asset not found")
-                .get_yocto_refund_amount(&metadata.tokens_per_use.map(|t| t.into()));
-            self.internal_modify_user_balance(&drop.funder_id, amount_to_increment, false);
-        }
+        self.internal_handle_unreleased_use(&token_id, &receiver_id, true)
+    }
 
-        // Loop through all the assets in the drop and ensure they're empty
-        let mut is_drop_empty = true;
-        for asset in drop.asset_by_id.values() {
-            if !asset.is_empty() {
-                is_drop_empty = false;
-                break;
+    /// Callback after the external membership check for a use with claimer rules. If the receiver is a member, the assets are claimed.
+    /// Otherwise, the use is restored on the key so that it can be claimed by an eligible account instead.
+    #[private]
+    pub fn on_membership_checked(
+        &mut self,
+        token_id: TokenId,
+        receiver_id: AccountId,
+        old_public_key: PublicKey,
+        fc_args: Option<UserProvidedFCArgs>,
+        new_public_key: Option<PublicKey>,
+    ) -> PromiseOrValue<bool> {
+        let is_member = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                near_sdk::serde_json::from_slice::<bool>(&value).unwrap_or(false)
             }
+            _ => false,
+        };
+
+        if is_member {
+            return self.internal_claim_assets(
+                token_id,
+                receiver_id,
+                old_public_key,
+                fc_args,
+                new_public_key,
+            );
         }
 
-        // If the key no longer has uses, it should be removed from the drop.
-        // In addition, if the drop has no keys and no more refundable assets, it should be removed from storage
-        self.handle_key_cleanup(
-            &mut drop,
-            &key_info,
-            &token_id,
-            &drop_id,
-            is_drop_empty,
-            initial_storage,
+        near_sdk::log!(
+            "Account {} failed the membership check. Assets were not released.",
+            receiver_id
         );
-
-        PromiseOrValue::Value(false)
+        self.internal_handle_unreleased_use(&token_id, &receiver_id, false)
     }
 
     #[private]
     pub fn on_assets_claimed(
         &mut self,
         token_id: TokenId,
+        receiver_id: AccountId,
         token_ids_transferred: Vec<Option<TokenId>>,
+        nft_pins_transferred: Vec<Option<PinnedNFT>>,
+        claimed_asset_indices: Vec<u32>,
     ) -> PromiseOrValue<bool> {
-        let num_promises = env::promise_results_count();
-
         let initial_storage = env::storage_usage();
-        let (drop_id, _) = parse_token_id(&token_id).unwrap();
+        let (drop_id, key_id) = parse_token_id(&token_id).unwrap();
         let mut drop: InternalDrop = self.drop_by_id.get(&drop_id).expect("Drop not found");
-        let key_info = drop
+        let mut key_info = drop
             .key_info_by_token_id
             .get(&token_id)
             .expect("Key not found");
@@ -94,17 +85,23 @@ impl Keypom {
         let cur_key_use = get_key_cur_use(&drop, &key_info) - 1;
         let InternalAssetDataForUses {
             uses: _,
-            config: _,
+            config: use_config,
             assets_metadata,
             required_asset_gas: _,
         } = get_asset_data_for_specific_use(&drop.asset_data_for_uses, &cur_key_use);
 
-        // Iterate through all the promises and get the results
+        // Iterate through all the promises and get the results. This is driven by the assets that fired a promise rather than
+        // `env::promise_results_count` since this can be called directly (with no promises) from within another callback
         let mut was_successful = true;
         let mut drop_assets_empty = true;
-        for i in 0..num_promises {
-            let promise_result = env::promise_result(i);
-            let metadata = &assets_metadata[i as usize];
+        let mut failed_asset_indices = Vec::new();
+        let mut amount_to_refund = 0;
+        let mut fc_retry_cost = 0;
+        for (i, asset_idx) in claimed_asset_indices.iter().enumerate() {
+            let promise_result = env::promise_result(i as u64);
+            // Map the promise back to the asset that fired it
+            let asset_idx = *asset_idx as usize;
+            let metadata = &assets_metadata[asset_idx];
 
             match promise_result {
                 PromiseResult::Successful(_) => {
@@ -123,10 +120,17 @@ impl Keypom {
                 }
                 PromiseResult::Failed => {
                     was_successful = false;
+                    failed_asset_indices.push(asset_idx as u32);
                     near_sdk::log!("Asset claim failed");
-                    // If we're dealing with an FC asset, no need to perform any refunds
+                    // If we're dealing with an FC asset, no need to perform any refunds. If the use is rolled back, the FC
+                    // Asset is fired again in full and its deposits were either spent or credited back when its chain halted
                     if is_fc_asset_id(&metadata.asset_id) {
                         near_sdk::log!("FC asset claimed");
+                        fc_retry_cost += drop
+                            .asset_by_id
+                            .get(&metadata.asset_id)
+                            .expect("Asset not found")
+                            .get_yocto_refund_amount(&None);
                         continue;
                     }
 
@@ -134,15 +138,18 @@ impl Keypom {
                         .asset_by_id
                         .get(&metadata.asset_id)
                         .expect("Asset not found");
-                    let mut tokens_per_use = metadata.tokens_per_use.map(|x| x.0.to_string());
+                    let mut tokens_per_use = key_info
+                        .get_tokens_per_use(cur_key_use, asset_idx, metadata)
+                        .map(|x| x.0.to_string());
+                    let mut nft_pin = None;
 
-                    // If it's a NFT, we need to get the token ID
+                    // If it's a NFT, we need to get the token ID and whether it was pinned to the key
                     if let InternalAsset::nft(_) = &mut asset {
-                        tokens_per_use = token_ids_transferred[i as usize].clone();
+                        tokens_per_use = token_ids_transferred[asset_idx].clone();
+                        nft_pin = nft_pins_transferred[asset_idx].clone();
                     };
 
-                    let amount_to_increment = asset.on_failed_claim(&tokens_per_use);
-                    self.internal_modify_user_balance(&drop.funder_id, amount_to_increment, false);
+                    amount_to_refund += asset.on_failed_claim(&tokens_per_use, nft_pin);
                     // Re-insert into storage
                     drop.asset_by_id.insert(&metadata.asset_id, &asset);
                     if !asset.is_empty() {
@@ -152,8 +159,57 @@ impl Keypom {
             }
         }
 
+        // For atomic uses, the key's use is restored so the failed assets can be claimed again.
+        // The $NEAR for the failed assets then stays reserved for the next attempt instead of being refunded.
+        let mut rolled_back = false;
+        if !was_successful && use_config.and_then(|c| c.atomic).unwrap_or(false) {
+            let claim_context = ClaimContext {
+                drop_id: drop_id.clone(),
+                key_id,
+                use_number: cur_key_use,
+                funder_id: drop.funder_id.clone(),
+            };
+            // The account (if any) was created by now so the use no longer needs to be held for `create_account_and_claim`
+            rolled_back = self.internal_rollback_key_use(
+                &mut drop,
+                &mut key_info,
+                &token_id,
+                &claim_context,
+                UseRollback {
+                    asset_indices: failed_asset_indices.clone(),
+                    extra_cost: fc_retry_cost,
+                    reserved_use: None,
+                },
+            );
+        }
+
+        if rolled_back {
+            // The receiver can claim the use again so it shouldn't count towards their claim limit yet
+            self.internal_release_account_claim(&drop, &receiver_id);
+        } else {
+            // The use is finished one way or another so there's nothing left to retry
+            key_info.retryable_use = None;
+            key_info.reserved_use = None;
+            self.internal_modify_user_balance(&drop.funder_id, amount_to_refund, false);
+        }
+
+        if !was_successful {
+            log_events(vec![EventLog {
+                standard: KEYPOM_STANDARD_NAME.to_string(),
+                version: KEYPOM_STANDARD_VERSION.to_string(),
+                event: EventLogVariant::ClaimFailed(ClaimFailedLog {
+                    drop_id: drop_id.to_string(),
+                    public_key: (&key_info.pub_key).into(),
+                    use_number: cur_key_use,
+                    failed_asset_indices: failed_asset_indices.clone(),
+                    rolled_back,
+                }),
+            }]);
+        }
+
         // Potential error -> because we only re-insert the drop at the end of the loop, what happens
         // If the promise result is not ready? Do we lose all the modifications that we made to the drop?
+        drop.key_info_by_token_id.insert(&token_id, &key_info);
         self.drop_by_id.insert(&drop_id, &drop);
 
         // If the key no longer has uses, it should be removed from the drop.
@@ -167,6 +223,25 @@ impl Keypom {
             initial_storage,
         );
 
+        // A rolled back use hasn't been claimed yet so it's only recorded once it finishes
+        let record_claims = drop
+            .config
+            .as_ref()
+            .and_then(|c| c.record_claims)
+            .unwrap_or(false);
+        if record_claims && !rolled_back {
+            self.internal_record_claim_receipt(
+                &drop,
+                &drop_id,
+                ClaimReceipt {
+                    account_id: receiver_id,
+                    use_number: cur_key_use,
+                    claimed_at: env::block_timestamp(),
+                    failed_asset_indices,
+                },
+            );
+        }
+
         PromiseOrValue::Value(was_successful)
     }
 }
@@ -6,12 +6,12 @@ impl Keypom {
     pub fn claim(
         &mut self,
         account_id: AccountId,
-        signature: Base64VecU8,
+        signature: KeySignature,
         linkdrop_pk: PublicKey,
         fc_args: Option<UserProvidedFCArgs>,
         password: Option<String>,
     ) -> PromiseOrValue<bool> {
-        self.assert_no_global_freeze();
+        self.assert_not_paused(PausableAction::claims);
         // All args, unfilled options will be filtered out
         let mut args_json = json!({
             "account_id": account_id,
@@ -27,7 +27,7 @@ impl Keypom {
         let args_string = args_json.to_string();
     
         require!(
-            self.verify_signature(signature, linkdrop_pk.clone(), args_string),
+            self.verify_signature(signature, linkdrop_pk.clone(), "claim", args_string),
             "Invalid signature for public key"
         );
 
@@ -37,10 +37,17 @@ impl Keypom {
             required_asset_gas,
             root_account_id: _,
             account_creation_keypom_args: _,
-            drop_id: _,
+            drop_id,
             key_id: _,
             funder_id: _,
-        } = self.before_claim_logic(&mut event_logs, None, password, linkdrop_pk.clone());
+        } = self.before_claim_logic(
+            &mut event_logs,
+            &account_id,
+            None,
+            password,
+            linkdrop_pk.clone(),
+        );
+        self.internal_record_account_claim(&drop_id, &account_id);
         let prepaid_gas = env::prepaid_gas();
         let total_required_gas = BASE_GAS_FOR_CLAIM.as_gas() + required_asset_gas.as_gas();
         // Use to check prepaid == required. Changed to >= for the sake of simplicity for now
@@ -55,7 +62,7 @@ impl Keypom {
         );
 
         log_events(event_logs);
-        self.internal_claim_assets(token_id, account_id, linkdrop_pk, fc_args, None)
+        self.internal_release_assets(token_id, account_id, linkdrop_pk, fc_args, None)
     }
 
     #[private]
@@ -63,12 +70,12 @@ impl Keypom {
         &mut self,
         new_account_id: AccountId,
         new_public_key: PublicKey,
-        signature: Base64VecU8,
+        signature: KeySignature,
         linkdrop_pk: PublicKey,
         fc_args: Option<UserProvidedFCArgs>,
         password: Option<String>,
     ) -> Promise {
-        self.assert_no_global_freeze();
+        self.assert_not_paused(PausableAction::claims);
 
         // All args, unfilled options will be filtered out
         let mut args_json = json!({
@@ -86,7 +93,7 @@ impl Keypom {
         let args_string = args_json.to_string();
     
         require!(
-            self.verify_signature(signature, linkdrop_pk.clone(), args_string),
+            self.verify_signature(signature, linkdrop_pk.clone(), "create_account_and_claim", args_string),
             "Invalid signature for public key"
         );
 
@@ -101,10 +108,12 @@ impl Keypom {
             funder_id,
         } = self.before_claim_logic(
             &mut event_logs,
+            &new_account_id,
             Some(&new_public_key),
             password,
             linkdrop_pk.clone(),
         );
+        self.internal_record_account_claim(&drop_id, &new_account_id);
 
         let prepaid_gas = env::prepaid_gas();
         let total_required_gas =
@@ -166,4 +175,58 @@ impl Keypom {
                     ),
             )
     }
+
+    /// Release a use that was reserved after a failed account creation once its retry window has expired.
+    /// The use is forfeited and its $NEAR is refunded to the funder. If the key has no uses left, it's deleted.
+    /// Can be called by anyone since the outcome is the same as the funder would expect.
+    pub fn release_expired_reservation(&mut self, linkdrop_pk: PublicKey) {
+        let initial_storage = env::storage_usage();
+        let token_id = self
+            .token_id_by_pk
+            .get(&linkdrop_pk)
+            .expect("No drop ID found for PK");
+        let (drop_id, _) = parse_token_id(&token_id).unwrap();
+        let mut drop: InternalDrop = self.drop_by_id.get(&drop_id).expect("Drop not found");
+        let mut key_info = drop
+            .key_info_by_token_id
+            .get(&token_id)
+            .expect("Key not found");
+
+        let expires_at = key_info
+            .reserved_use
+            .as_ref()
+            .map(|r| r.expires_at)
+            .expect("Key has no reserved use");
+        require!(
+            env::block_timestamp() >= expires_at,
+            "Reserved use has not expired yet"
+        );
+
+        let mut event_logs = Vec::new();
+        self.internal_forfeit_reserved_use(&mut drop, &mut key_info);
+        if key_info.remaining_uses == 0 {
+            self.internal_remove_key_mappings(&mut event_logs, &key_info, &token_id, &drop_id);
+        }
+        log_events(event_logs);
+        drop.key_info_by_token_id.insert(&token_id, &key_info);
+        self.drop_by_id.insert(&drop_id, &drop);
+
+        // Loop through all the assets in the drop and ensure they're empty
+        let mut is_drop_empty = true;
+        for asset in drop.asset_by_id.values() {
+            if !asset.is_empty() {
+                is_drop_empty = false;
+                break;
+            }
+        }
+
+        self.handle_key_cleanup(
+            &mut drop,
+            &key_info,
+            &token_id,
+            &drop_id,
+            is_drop_empty,
+            initial_storage,
+        );
+    }
 }
@@ -9,6 +9,7 @@ impl Keypom {
     pub(crate) fn before_claim_logic(
         &mut self,
         event_logs: &mut Vec<EventLog>,
+        receiver_id: &AccountId,
         new_public_key: Option<&PublicKey>,
         password: Option<String>,
         linkdrop_pk: PublicKey,
@@ -26,17 +27,33 @@ impl Keypom {
 
         let (drop_id, key_id) = parse_token_id(&token_id).unwrap();
         let mut drop: InternalDrop = self.drop_by_id.get(&drop_id).expect("Drop not found");
+        require!(!drop.paused, "Drop is paused");
         let mut key_info = drop
             .key_info_by_token_id
             .get(&token_id)
             .expect("Key not found");
+
+        // A use that is reserved after a failed account creation can only be retried with `create_account_and_claim` until it expires
+        if let Some(expires_at) = key_info.reserved_use.as_ref().map(|r| r.expires_at) {
+            if env::block_timestamp() >= expires_at {
+                require!(
+                    key_info.remaining_uses > 1,
+                    "Reserved use has expired. Call `release_expired_reservation` to clean up the key"
+                );
+                self.internal_forfeit_reserved_use(&mut drop, &mut key_info);
+            } else {
+                require!(
+                    new_public_key.is_some(),
+                    "Reserved use can only be retried with `create_account_and_claim`"
+                );
+            }
+        }
+
         let cur_key_use = get_key_cur_use(&drop, &key_info);
-        let InternalAssetDataForUses {
-            uses: _,
-            config: use_config,
-            assets_metadata: _,
-            required_asset_gas,
-        } = get_asset_data_for_specific_use(&drop.asset_data_for_uses, &cur_key_use);
+        let asset_data = get_asset_data_for_specific_use(&drop.asset_data_for_uses, &cur_key_use);
+        // Include any membership check that happens before the assets are released
+        let required_asset_gas = asset_data.get_total_asset_gas();
+        let use_config = asset_data.config;
 
         assert_pre_claim_conditions(
             &key_info,
@@ -44,41 +61,48 @@ impl Keypom {
             &password,
             &cur_key_use,
             &drop.max_key_uses,
+            receiver_id,
             new_public_key.is_some(),
         );
 
+        // Make sure neither the key nor the drop has exceeded its claims for the current window
+        if let Some(rate_limit) = self.get_rate_limit_for_drop(&drop) {
+            let window_start = get_rate_limit_window_start(rate_limit.window);
+            require!(
+                key_info
+                    .claim_window
+                    .try_record_claim(window_start, rate_limit.max_claims_per_key),
+                format!(
+                    "Key rate limit exceeded. Max {} claims per window",
+                    rate_limit.max_claims_per_key.unwrap_or_default()
+                )
+            );
+            require!(
+                drop.claim_window
+                    .try_record_claim(window_start, rate_limit.max_claims_per_drop),
+                format!(
+                    "Drop rate limit exceeded. Max {} claims per window",
+                    rate_limit.max_claims_per_drop.unwrap_or_default()
+                )
+            );
+        }
+
+        // The drop no longer needs the tokens for the use that's being consumed
+        let mut tokens_needed = HashMap::new();
+        get_tokens_needed_for_use(
+            &mut tokens_needed,
+            cur_key_use,
+            &drop.asset_by_id,
+            &drop.asset_data_for_uses,
+            &key_info,
+        );
+        drop.modify_tokens_needed(tokens_needed, true);
+
         key_info.remaining_uses -= 1;
         key_info.last_claimed = env::block_timestamp();
+        drop.has_claims = true;
         if key_info.remaining_uses == 0 {
-            // Delete everything except the token ID -> key info mapping since we need the key info in callbacks
-            if let Some(owner) = key_info.owner_id.as_ref() {
-                self.internal_remove_token_from_owner(owner, &token_id);
-            }
-
-            self.token_id_by_pk.remove(&linkdrop_pk);
-
-            event_logs.push(EventLog {
-                standard: NFT_STANDARD_NAME.to_string(),
-                version: NFT_METADATA_SPEC.to_string(),
-                event: EventLogVariant::NftBurn(vec![NftBurnLog {
-                    owner_id: key_info
-                        .owner_id
-                        .as_ref()
-                        .unwrap_or(&env::current_account_id())
-                        .to_string(),
-                    token_ids: vec![token_id.to_string()],
-                    authorized_id: None,
-                    memo: None,
-                }]),
-            });
-            event_logs.push(EventLog {
-                standard: KEYPOM_STANDARD_NAME.to_string(),
-                version: KEYPOM_STANDARD_VERSION.to_string(),
-                event: EventLogVariant::DeleteKey(vec![AddOrDeleteKeyLog {
-                    drop_id: drop_id.to_string(),
-                    public_key: (&linkdrop_pk).into(),
-                }]),
-            });
+            self.internal_remove_key_mappings(event_logs, &key_info, &token_id, &drop_id);
         }
 
         drop.key_info_by_token_id.insert(&token_id, &key_info);
@@ -132,25 +156,52 @@ impl Keypom {
             required_asset_gas: _,
         } = get_asset_data_for_specific_use(&drop.asset_data_for_uses, &cur_key_use);
 
-        //let promises;
         let mut promises = Vec::new();
+        // Index (in the assets for this use) of every asset that fired a promise. The promise results
+        // In `on_assets_claimed` are in the same order so they can be matched back to their asset.
+        let mut claimed_asset_indices = Vec::new();
         let mut token_ids_transferred = Vec::new();
+        let mut nft_pins_transferred = Vec::new();
         let mut fc_arg_idx = 0;
         let mut assets_to_log = Vec::new();
-        for metadata in assets_metadata {
+        let claim_context = ClaimContext {
+            drop_id: drop_id.clone(),
+            key_id,
+            use_number: cur_key_use,
+            funder_id: drop.funder_id.clone(),
+        };
+        for (asset_idx, metadata) in assets_metadata.iter().enumerate() {
             let mut asset = drop
                 .asset_by_id
                 .get(&metadata.asset_id)
                 .expect("Asset not found");
 
+            // If this use was rolled back after some assets were already claimed, only the assets that failed should be claimed again
+            if !key_info.is_asset_pending(cur_key_use, asset_idx) {
+                token_ids_transferred.push(None);
+                nft_pins_transferred.push(None);
+                if let InternalAsset::fc(_) = asset {
+                    fc_arg_idx += 1;
+                }
+                continue;
+            }
+
+            // Take any per-key overrides into account for how many tokens are transferred
+            let tokens_per_use = key_info.get_tokens_per_use(cur_key_use, asset_idx, metadata);
+
             // For claim events
-            assets_to_log.push(asset.to_external_events_asset(&metadata.tokens_per_use));
+            assets_to_log.push(asset.to_external_events_asset(&tokens_per_use));
 
-            // We need to keep track of all the NFT token IDs in order to potentially perform refunds
+            // We need to keep track of all the NFT token IDs (and whether they were pinned) in order to potentially perform refunds
             if let InternalAsset::nft(data) = &asset {
-                token_ids_transferred.push(data.get_next_token_id());
+                token_ids_transferred.push(data.get_next_token_id(key_id, cur_key_use));
+                nft_pins_transferred.push(
+                    data.get_pinned_token_idx(key_id, cur_key_use)
+                        .map(|idx| data.pinned_tokens[idx].clone()),
+                );
             } else {
                 token_ids_transferred.push(None);
+                nft_pins_transferred.push(None);
             }
 
             // Try to get the fc args for the asset. If the length of the fc_args outer vector is not the same as the number of FC assets
@@ -161,14 +212,15 @@ impl Keypom {
                 .unwrap_or(None);
 
             // Some cases may result in no promise index (i.e not enough balance)
-            promises.push(asset.claim_asset(
+            if let Some(promise) = asset.claim_asset(
                 &receiver_id,
-                &metadata.tokens_per_use.map(|x| x.into()),
+                &tokens_per_use.map(|x| x.into()),
                 fc_args_for_asset,
-                drop_id.clone(),
-                key_id.to_string(),
-                drop.funder_id.clone(),
-            ));
+                &claim_context,
+            ) {
+                promises.push(promise);
+                claimed_asset_indices.push(asset_idx as u32);
+            }
 
             // Increment the number of fc args we've seen
             if let InternalAsset::fc(_) = asset {
@@ -208,22 +260,590 @@ impl Keypom {
         };
         log_events(vec![event_log]);
 
-        if let Some(resolve) = promises
-            .into_iter()
-            .reduce(|a, b| a.and(b))
-            .expect("empty promises")
-        {
+        // Join all the asset promises so the callback receives one result per claimed asset
+        if let Some(resolve) = promises.into_iter().reduce(|a, b| a.and(b)) {
             PromiseOrValue::Promise(
                 resolve.then(
                     Self::ext(env::current_account_id())
                         //.with_static_gas(MIN_GAS_FOR_RESOLVE_ASSET_CLAIM)
                         .with_unused_gas_weight(1)
-                        .on_assets_claimed(token_id, token_ids_transferred),
+                        .on_assets_claimed(
+                            token_id,
+                            receiver_id,
+                            token_ids_transferred,
+                            nft_pins_transferred,
+                            claimed_asset_indices,
+                        ),
                 ),
             )
         } else {
-            self.on_assets_claimed(token_id, token_ids_transferred)
+            self.on_assets_claimed(
+                token_id,
+                receiver_id,
+                token_ids_transferred,
+                nft_pins_transferred,
+                claimed_asset_indices,
+            )
+        }
+    }
+
+    /// Once a key has no uses left, delete everything except the token ID -> key info mapping since we need the key info in callbacks
+    pub(crate) fn internal_remove_key_mappings(
+        &mut self,
+        event_logs: &mut Vec<EventLog>,
+        key_info: &InternalKeyInfo,
+        token_id: &TokenId,
+        drop_id: &DropId,
+    ) {
+        if let Some(owner) = key_info.owner_id.as_ref() {
+            self.internal_remove_token_from_owner(owner, token_id);
+        }
+
+        self.token_id_by_pk.remove(&key_info.pub_key);
+
+        event_logs.push(EventLog {
+            standard: NFT_STANDARD_NAME.to_string(),
+            version: NFT_METADATA_SPEC.to_string(),
+            event: EventLogVariant::NftBurn(vec![NftBurnLog {
+                owner_id: key_info
+                    .owner_id
+                    .as_ref()
+                    .unwrap_or(&env::current_account_id())
+                    .to_string(),
+                token_ids: vec![token_id.to_string()],
+                authorized_id: None,
+                memo: None,
+            }]),
+        });
+        event_logs.push(EventLog {
+            standard: KEYPOM_STANDARD_NAME.to_string(),
+            version: KEYPOM_STANDARD_VERSION.to_string(),
+            event: EventLogVariant::DeleteKey(vec![AddOrDeleteKeyLog {
+                drop_id: drop_id.to_string(),
+                public_key: (&key_info.pub_key).into(),
+            }]),
+        });
+    }
+
+    /// Give up a use that was reserved after a failed account creation. The $NEAR for any assets in the use that
+    /// Weren't already claimed is refunded to the funder, the drop no longer needs their tokens and the use is consumed.
+    pub(crate) fn internal_forfeit_reserved_use(
+        &mut self,
+        drop: &mut InternalDrop,
+        key_info: &mut InternalKeyInfo,
+    ) {
+        let Some(reserved_use) = key_info.reserved_use.take() else {
+            return;
+        };
+
+        let InternalAssetDataForUses {
+            uses: _,
+            config: _,
+            assets_metadata,
+            required_asset_gas: _,
+        } = get_asset_data_for_specific_use(&drop.asset_data_for_uses, &reserved_use.use_number);
+
+        let mut tokens_needed = HashMap::new();
+        get_tokens_needed_for_use(
+            &mut tokens_needed,
+            reserved_use.use_number,
+            &drop.asset_by_id,
+            &drop.asset_data_for_uses,
+            key_info,
+        );
+        drop.modify_tokens_needed(tokens_needed, true);
+
+        let mut amount_to_refund = 0;
+        for (asset_idx, metadata) in assets_metadata.iter().enumerate() {
+            if !key_info.is_asset_pending(reserved_use.use_number, asset_idx) {
+                continue;
+            }
+
+            let tokens_per_use =
+                key_info.get_tokens_per_use(reserved_use.use_number, asset_idx, metadata);
+            amount_to_refund += drop
+                .asset_by_id
+                .get(&metadata.asset_id)
+                .expect("Asset not found")
+                .get_yocto_refund_amount(&tokens_per_use.map(|t| t.into()));
+        }
+
+        near_sdk::log!(
+            "Reserved use {} expired. Refunding {} to the funder",
+            reserved_use.use_number,
+            amount_to_refund
+        );
+        self.internal_modify_user_balance(&drop.funder_id, amount_to_refund, false);
+        key_info.retryable_use = None;
+        key_info.remaining_uses -= 1;
+    }
+
+    /// Roll back a use of a key whose claim failed. The use is restored and if some assets were already claimed
+    /// (i.e an atomic use partially failed), the key is marked so that only the remaining assets are claimed on the next attempt.
+    /// The rollback can also hold the use as reserved after a failed account creation.
+    /// Returns false if the use couldn't be rolled back (i.e the funder can't cover the cost of the retry).
+    pub(crate) fn internal_rollback_key_use(
+        &mut self,
+        drop: &mut InternalDrop,
+        key_info: &mut InternalKeyInfo,
+        token_id: &TokenId,
+        claim_context: &ClaimContext,
+        rollback: UseRollback,
+    ) -> bool {
+        let ClaimContext {
+            drop_id,
+            use_number,
+            ..
+        } = claim_context;
+        let use_number = *use_number;
+        let UseRollback {
+            asset_indices,
+            extra_cost,
+            reserved_use,
+        } = rollback;
+
+        // If every asset is left to claim, the entire use can simply be claimed again
+        let num_assets = get_asset_data_for_specific_use(&drop.asset_data_for_uses, &use_number)
+            .assets_metadata
+            .len();
+        let retryable_use = if asset_indices.len() == num_assets {
+            None
+        } else {
+            Some(RetryableUse {
+                use_number,
+                asset_indices,
+            })
+        };
+
+        // The funder pays for any extra storage needed to keep track of the assets left to claim and the reservation
+        let old_len = key_info.retry_state_len();
+        let new_len = near_sdk::borsh::to_vec(&retryable_use).unwrap().len()
+            + near_sdk::borsh::to_vec(&reserved_use).unwrap().len();
+        let storage_cost =
+            new_len.saturating_sub(old_len) as u128 * env::storage_byte_cost().as_yoctonear();
+        let retry_cost = storage_cost + extra_cost;
+        let funder_balance = self
+            .funder_info_by_id
+            .get(&drop.funder_id)
+            .map(|f| f.balance)
+            .unwrap_or(0);
+        if funder_balance < retry_cost {
+            near_sdk::log!(
+                "Funder balance {} cannot cover {} for the retry. Not rolling back.",
+                funder_balance,
+                retry_cost
+            );
+            return false;
+        }
+
+        // If this was the key's last use, its public key mapping was removed and might have been taken since
+        if key_info.remaining_uses == 0 && self.token_id_by_pk.get(&key_info.pub_key).is_some() {
+            near_sdk::log!("Public key is already in use. Not rolling back.");
+            return false;
         }
+
+        self.internal_modify_user_balance(&drop.funder_id, retry_cost, true);
+        key_info.retryable_use = retryable_use;
+        key_info.reserved_use = reserved_use;
+        key_info.remaining_uses += 1;
+
+        // The failed assets were put back into the drop and are needed again for the retry
+        let mut tokens_needed = HashMap::new();
+        get_tokens_needed_for_use(
+            &mut tokens_needed,
+            use_number,
+            &drop.asset_by_id,
+            &drop.asset_data_for_uses,
+            key_info,
+        );
+        drop.modify_tokens_needed(tokens_needed, false);
+
+        // Add back everything that was removed when the key's last use was consumed
+        if key_info.remaining_uses == 1 {
+            self.token_id_by_pk.insert(&key_info.pub_key, token_id);
+            if let Some(owner) = key_info.owner_id.as_ref() {
+                self.internal_add_token_to_owner(owner, token_id);
+            }
+
+            let mut nft_mint_logs = vec![];
+            let mut add_key_logs = vec![];
+            add_new_key_logs(
+                &mut nft_mint_logs,
+                &mut add_key_logs,
+                &key_info.owner_id,
+                drop_id,
+                &key_info.pub_key,
+                token_id,
+            );
+            log_events(vec![
+                EventLog {
+                    standard: NFT_STANDARD_NAME.to_string(),
+                    version: NFT_METADATA_SPEC.to_string(),
+                    event: EventLogVariant::NftMint(nft_mint_logs),
+                },
+                EventLog {
+                    standard: KEYPOM_STANDARD_NAME.to_string(),
+                    version: KEYPOM_STANDARD_VERSION.to_string(),
+                    event: EventLogVariant::AddKey(add_key_logs),
+                },
+            ]);
+        }
+
+        true
+    }
+
+    /// Query how many times an account has claimed within the given scope
+    pub(crate) fn get_account_claims(
+        &self,
+        scope: &AccountClaimScope,
+        account_id: &AccountId,
+    ) -> u32 {
+        self.claims_per_account
+            .get(scope)
+            .and_then(|claims| claims.get(account_id))
+            .unwrap_or(0)
+    }
+
+    /// Make sure the receiving account hasn't reached the drop's limit on claims per account and count the claim towards it.
+    /// This happens right away (rather than once the assets are claimed) so that parallel claims can't get past the limit.
+    /// The storage is charged to the funder. If the assets end up not being released, the claim is released again.
+    pub(crate) fn internal_record_account_claim(
+        &mut self,
+        drop_id: &DropId,
+        account_id: &AccountId,
+    ) {
+        let drop: InternalDrop = self.drop_by_id.get(drop_id).expect("Drop not found");
+        let Some(limit) = drop
+            .config
+            .as_ref()
+            .and_then(|c| c.account_claim_limit.as_ref())
+        else {
+            return;
+        };
+
+        let initial_storage = env::storage_usage();
+        let scope = limit.get_scope(&drop);
+        let prev_claims = self.get_account_claims(&scope, account_id);
+        require!(
+            prev_claims < limit.max_claims_per_account,
+            format!(
+                "Account {} has reached the limit of {} claims",
+                account_id, limit.max_claims_per_account
+            )
+        );
+
+        let mut claims = self.claims_per_account.get(&scope).unwrap_or_else(|| {
+            LookupMap::new(StorageKeys::ClaimsPerAccountInner {
+                scope_hash: env::sha256_array(&near_sdk::borsh::to_vec(&scope).unwrap()),
+            })
+        });
+        claims.insert(account_id, &(prev_claims + 1));
+        self.claims_per_account.insert(&scope, &claims);
+
+        let storage_cost = (env::storage_usage() - initial_storage) as u128
+            * env::storage_byte_cost().as_yoctonear();
+        self.internal_modify_user_balance(&drop.funder_id, storage_cost, true);
+    }
+
+    /// Stop counting a claim towards the drop's limit on claims per account (i.e if the use was rolled back or the assets
+    /// Were never released). Any storage freed is credited back to the funder in `handle_key_cleanup`.
+    pub(crate) fn internal_release_account_claim(
+        &mut self,
+        drop: &InternalDrop,
+        account_id: &AccountId,
+    ) {
+        let Some(limit) = drop
+            .config
+            .as_ref()
+            .and_then(|c| c.account_claim_limit.as_ref())
+        else {
+            return;
+        };
+
+        let scope = limit.get_scope(drop);
+        if let Some(mut claims) = self.claims_per_account.get(&scope) {
+            match claims.get(account_id).unwrap_or(0) {
+                0 => {}
+                1 => {
+                    claims.remove(account_id);
+                }
+                prev_claims => {
+                    claims.insert(account_id, &(prev_claims - 1));
+                }
+            }
+        }
+    }
+
+    /// Store a receipt for a claim on a drop with `record_claims` enabled. The storage is paid from the funder's balance
+    /// And if it can't be covered, the receipt is skipped rather than failing the claim.
+    pub(crate) fn internal_record_claim_receipt(
+        &mut self,
+        drop: &InternalDrop,
+        drop_id: &DropId,
+        receipt: ClaimReceipt,
+    ) {
+        let initial_storage = env::storage_usage();
+        let funder_id = &drop.funder_id;
+        let account_id = receipt.account_id.clone();
+
+        let mut drop_receipts = self
+            .claim_receipts_by_drop
+            .get(&drop.nonce)
+            .unwrap_or_else(|| {
+                Vector::new(StorageKeys::ClaimReceiptsByDropInner {
+                    drop_nonce: drop.nonce,
+                })
+            });
+        let mut account_receipts = self
+            .claim_receipts_by_account
+            .get(&account_id)
+            .unwrap_or_else(|| {
+                Vector::new(StorageKeys::ClaimReceiptsByAccountInner {
+                    account_id_hash: hash_string(&account_id.to_string()),
+                })
+            });
+
+        account_receipts.push(&ClaimReceiptId {
+            drop_id: drop_id.clone(),
+            drop_nonce: drop.nonce,
+            receipt_idx: drop_receipts.len(),
+        });
+        drop_receipts.push(&receipt);
+        self.claim_receipts_by_drop
+            .insert(&drop.nonce, &drop_receipts);
+        self.claim_receipts_by_account
+            .insert(&account_id, &account_receipts);
+
+        let storage_cost = (env::storage_usage() - initial_storage) as u128
+            * env::storage_byte_cost().as_yoctonear();
+        let funder_balance = self
+            .funder_info_by_id
+            .get(funder_id)
+            .map(|f| f.balance)
+            .unwrap_or(0);
+        if funder_balance >= storage_cost {
+            self.internal_modify_user_balance(funder_id, storage_cost, true);
+            return;
+        }
+
+        // The funder can't pay for the receipt so undo everything that was just written
+        near_sdk::log!(
+            "Funder balance {} cannot cover {} for the claim receipt. Skipping.",
+            funder_balance,
+            storage_cost
+        );
+        drop_receipts.pop();
+        account_receipts.pop();
+        if drop_receipts.is_empty() {
+            self.claim_receipts_by_drop.remove(&drop.nonce);
+        } else {
+            self.claim_receipts_by_drop
+                .insert(&drop.nonce, &drop_receipts);
+        }
+        if account_receipts.is_empty() {
+            self.claim_receipts_by_account.remove(&account_id);
+        } else {
+            self.claim_receipts_by_account
+                .insert(&account_id, &account_receipts);
+        }
+    }
+
+    /// Release the assets for the key's current use to the receiver. If the use has a membership check, the external
+    /// Contract is asked first and the assets are only claimed in `on_membership_checked` if the receiver is a member.
+    pub(crate) fn internal_release_assets(
+        &mut self,
+        token_id: TokenId,
+        receiver_id: AccountId,
+        old_public_key: PublicKey,
+        fc_args: Option<UserProvidedFCArgs>,
+        new_public_key: Option<PublicKey>,
+    ) -> PromiseOrValue<bool> {
+        let (drop_id, _) = parse_token_id(&token_id).unwrap();
+        let drop: InternalDrop = self.drop_by_id.get(&drop_id).expect("Drop not found");
+        let key_info = drop
+            .key_info_by_token_id
+            .get(&token_id)
+            .expect("Key not found");
+
+        // The uses were decremented before the claim, so we need to increment them back to get the current use
+        let cur_key_use = get_key_cur_use(&drop, &key_info) - 1;
+        let InternalAssetDataForUses {
+            uses: _,
+            config: use_config,
+            assets_metadata: _,
+            required_asset_gas,
+        } = get_asset_data_for_specific_use(&drop.asset_data_for_uses, &cur_key_use);
+
+        let Some(membership_check) = use_config
+            .and_then(|c| c.claimer_rules)
+            .and_then(|r| r.membership_check)
+        else {
+            return self.internal_claim_assets(
+                token_id,
+                receiver_id,
+                old_public_key,
+                fc_args,
+                new_public_key,
+            );
+        };
+
+        let mut args = serde_json::Map::new();
+        args.insert(
+            membership_check
+                .account_id_field
+                .unwrap_or("account_id".to_string()),
+            json!(receiver_id),
+        );
+
+        PromiseOrValue::Promise(
+            Promise::new(membership_check.contract_id)
+                .function_call_weight(
+                    membership_check
+                        .method_name
+                        .unwrap_or("is_member".to_string()),
+                    serde_json::Value::Object(args).to_string().into(),
+                    NearToken::from_yoctonear(0),
+                    GAS_FOR_MEMBERSHIP_CHECK,
+                    GasWeight(0),
+                )
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(Gas::from_gas(
+                            BASE_GAS_FOR_RESOLVE_MEMBERSHIP_CHECK.as_gas()
+                                + required_asset_gas.as_gas(),
+                        ))
+                        .with_unused_gas_weight(1)
+                        .on_membership_checked(
+                            token_id,
+                            receiver_id,
+                            old_public_key,
+                            fc_args,
+                            new_public_key,
+                        ),
+                ),
+        )
+    }
+
+    /// Handle a use whose assets were never released because account creation failed or the receiver failed the membership check.
+    /// The use is restored on the key if possible (for failed account creations, only if it can be reserved for a retry).
+    /// Otherwise, the $NEAR for the assets in the use is refunded to the funder. Either way, the claim no longer counts towards the receiver's limit.
+    pub(crate) fn internal_handle_unreleased_use(
+        &mut self,
+        token_id: &TokenId,
+        receiver_id: &AccountId,
+        account_creation_failed: bool,
+    ) -> PromiseOrValue<bool> {
+        let initial_storage = env::storage_usage();
+        let (drop_id, key_id) = parse_token_id(token_id).unwrap();
+        let mut drop: InternalDrop = self.drop_by_id.get(&drop_id).expect("Drop not found");
+        let mut key_info = drop
+            .key_info_by_token_id
+            .get(token_id)
+            .expect("Key not found");
+        // The uses were decremented before the claim, so we need to increment them back to get what use should be refunded
+        let cur_key_use = get_key_cur_use(&drop, &key_info) - 1;
+        let InternalAssetDataForUses {
+            uses: _,
+            config: use_config,
+            assets_metadata,
+            required_asset_gas: _,
+        } = get_asset_data_for_specific_use(&drop.asset_data_for_uses, &cur_key_use);
+
+        // Assets that were already claimed in a rolled back use won't be sent again so they aren't refunded or retried
+        let asset_indices: Vec<u32> = (0..assets_metadata.len())
+            .filter(|asset_idx| key_info.is_asset_pending(cur_key_use, *asset_idx))
+            .map(|asset_idx| asset_idx as u32)
+            .collect();
+
+        let existing_reservation = key_info
+            .reserved_use
+            .clone()
+            .filter(|r| r.use_number == cur_key_use);
+        // If account creation failed, hold the use so the holder can retry with a different account name.
+        // Retrying doesn't extend the original window.
+        let reserved_use = if account_creation_failed {
+            existing_reservation.or(use_config
+                .and_then(|c| c.account_creation_retry_window)
+                .map(|window| ReservedUse {
+                    use_number: cur_key_use,
+                    expires_at: env::block_timestamp() + window,
+                }))
+        } else {
+            existing_reservation
+        }
+        .filter(|r| env::block_timestamp() < r.expires_at);
+
+        // A failed membership check always restores the use so it can go to an eligible account instead.
+        // Nothing was sent out either way so the FC deposits are still available for the retry
+        let rolled_back = if !account_creation_failed || reserved_use.is_some() {
+            let claim_context = ClaimContext {
+                drop_id: drop_id.clone(),
+                key_id,
+                use_number: cur_key_use,
+                funder_id: drop.funder_id.clone(),
+            };
+            self.internal_rollback_key_use(
+                &mut drop,
+                &mut key_info,
+                token_id,
+                &claim_context,
+                UseRollback {
+                    asset_indices: asset_indices.clone(),
+                    extra_cost: 0,
+                    reserved_use,
+                },
+            )
+        } else {
+            false
+        };
+
+        if rolled_back {
+            near_sdk::log!(
+                "Assets were not released. Use {} was restored on the key. Reserved until: {:?}",
+                cur_key_use,
+                key_info.reserved_use.as_ref().map(|r| r.expires_at)
+            );
+        } else {
+            for asset_idx in asset_indices {
+                let metadata = &assets_metadata[asset_idx as usize];
+                let tokens_per_use =
+                    key_info.get_tokens_per_use(cur_key_use, asset_idx as usize, metadata);
+                let amount_to_increment = drop
+                    .asset_by_id
+                    .get(&metadata.asset_id)
+                    .expect("Asset not found")
+                    .get_yocto_refund_amount(&tokens_per_use.map(|t| t.into()));
+                self.internal_modify_user_balance(&drop.funder_id, amount_to_increment, false);
+            }
+
+            key_info.retryable_use = None;
+            key_info.reserved_use = None;
+        }
+        drop.key_info_by_token_id.insert(token_id, &key_info);
+        // No assets were sent to the receiver so the claim no longer counts towards their limit
+        self.internal_release_account_claim(&drop, receiver_id);
+
+        // Loop through all the assets in the drop and ensure they're empty
+        let mut is_drop_empty = true;
+        for asset in drop.asset_by_id.values() {
+            if !asset.is_empty() {
+                is_drop_empty = false;
+                break;
+            }
+        }
+
+        // If the key no longer has uses, it should be removed from the drop.
+        // In addition, if the drop has no keys and no more refundable assets, it should be removed from storage
+        self.handle_key_cleanup(
+            &mut drop,
+            &key_info,
+            token_id,
+            &drop_id,
+            is_drop_empty,
+            initial_storage,
+        );
+
+        PromiseOrValue::Value(false)
     }
 
     /// Check if key is empty and perform cleanup if it is
@@ -301,6 +921,7 @@ pub(crate) fn assert_pre_claim_conditions(
     user_password: &Option<String>,
     cur_key_use: &UseNumber,
     max_uses_per_key: &UseNumber,
+    receiver_id: &AccountId,
     creating_account: bool,
 ) {
     // Ensure that claim and create_account_and_claim are only called based on the key / drop's config
@@ -315,6 +936,11 @@ pub(crate) fn assert_pre_claim_conditions(
         }
     }
 
+    // Ensure the receiving account is allowed to get the assets. Any membership check happens once the claim goes through
+    if let Some(rules) = use_config.as_ref().and_then(|c| c.claimer_rules.as_ref()) {
+        rules.assert_claimer_allowed(receiver_id);
+    }
+
     // Ensure any timestamps in the configs have been fulfilled
     assert_claim_timestamps(use_config, key_info, max_uses_per_key);
 
@@ -10,7 +10,7 @@ impl Keypom {
         // Should any excess attached deposit be deposited to the user's balance?
         keep_excess_deposit: Option<bool>,
     ) -> bool {
-        self.assert_no_global_freeze();
+        self.assert_not_paused(PausableAction::drop_creation);
 
         // Before anything, measure storage usage so we can net the cost and charge the funder
         let initial_storage = env::storage_usage();
@@ -44,30 +44,11 @@ impl Keypom {
             );
         }
 
-        // Parse the external assets and store them in the contract
-        let max_key_uses = drop.max_key_uses;
-
-        let mut total_cost_per_key = 0;
-        // Get the total cost and allowance required for a key that has all its uses remaining
-        // We'll then multiply this by the number of keys we want to add and charge the user
-        get_total_costs_for_key(
-            &mut total_cost_per_key,
-            max_key_uses,
-            &drop.asset_by_id,
-            &drop.asset_data_for_uses,
-        );
-
         // Keep track of all the events
         let mut event_logs = Vec::new();
-        // Add the keys to the contract
-        self.internal_add_keys_to_account(
-            &mut drop.next_key_id,
-            &mut drop.key_info_by_token_id,
-            &mut event_logs,
-            &drop_id,
-            max_key_uses,
-            &key_data,
-        );
+        // Add the keys to the contract and get the total cost of the assets for all of them
+        let total_asset_cost =
+            self.internal_add_keys_to_account(&mut drop, &drop_id, &key_data, &mut event_logs);
 
         // Write the updated drop data to storage
         self.drop_by_id.insert(&drop_id, &drop);
@@ -77,7 +58,7 @@ impl Keypom {
         let refund_amount = self.determine_costs(
             key_data.len(),
             false, // No drop was created
-            total_cost_per_key,
+            total_asset_cost,
             net_storage,
             env::attached_deposit().as_yoctonear(),
         );
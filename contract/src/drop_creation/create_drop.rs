@@ -108,7 +108,7 @@ impl Keypom {
         // Should any excess attached deposit be deposited to the user's balance?
         keep_excess_deposit: Option<bool>,
     ) -> bool {
-        self.assert_no_global_freeze();
+        self.assert_not_paused(PausableAction::drop_creation);
         // Get the amount of $NEAR that should be refunded out of the user's attached deposit
         let refund_amount = self.internal_create_drop(
             drop_id,
@@ -143,19 +143,22 @@ impl Keypom {
         drop_config: Option<DropConfig>,
         attached_deposit: Balance,
     ) -> Balance {
-        self.assert_no_global_freeze();
+        self.assert_not_paused(PausableAction::drop_creation);
         require!(!drop_id.contains(':'), "Drop ID cannot contain a colon (:)");
         require!(
-            self.drop_by_id.get(&drop_id).is_none(),
+            self.drop_by_id.get(&drop_id).is_none() && !self.is_legacy_drop(&drop_id),
             format!("Drop with ID {} already exists", drop_id)
         );
 
+        // Make sure every use in the drop is valid and can be claimed before writing anything to storage
+        assert_valid_asset_data(&asset_data);
+
         // Before anything, measure storage usage so we can net the cost and charge the funder
         let initial_storage = env::storage_usage();
         near_sdk::log!("initial bytes {}", initial_storage);
 
         // Instantiate the drop data structures
-        let mut key_info_by_token_id: UnorderedMap<TokenId, InternalKeyInfo> =
+        let key_info_by_token_id: UnorderedMap<TokenId, InternalKeyInfo> =
             UnorderedMap::new(StorageKeys::KeyInfoByPk {
                 drop_id_hash: hash_string(&drop_id.to_string()),
             });
@@ -171,11 +174,23 @@ impl Keypom {
             "Cannot add more than 100 keys at a time"
         );
 
+        if let Some(rate_limit) = drop_config.as_ref().and_then(|c| c.rate_limit.as_ref()) {
+            assert_valid_rate_limit(rate_limit);
+        }
+
         let mut max_key_uses = 0;
         // Parse the external asset data and convert it into the internal representation
         for ext_asset_data in asset_data {
             // Convert the external asset data into the internal asset data
-            asset_data_for_uses.push(InternalAssetDataForUses::from(&ext_asset_data));
+            let mut internal_asset_data = InternalAssetDataForUses::from(&ext_asset_data);
+            if let Some(claimer_rules) = internal_asset_data
+                .config
+                .as_mut()
+                .and_then(|c| c.claimer_rules.as_mut())
+            {
+                self.internal_store_claimer_lists(claimer_rules);
+            }
+            asset_data_for_uses.push(internal_asset_data);
 
             // Take the assets and populate the asset_by_id mapping
             store_assets_by_id(&ext_asset_data.assets, &mut asset_by_id);
@@ -183,41 +198,35 @@ impl Keypom {
             max_key_uses += ext_asset_data.uses;
         }
 
-        let mut total_cost_per_key = 0;
-        // Get the total cost and allowance required for a key that has all its uses remaining
-        // We'll then multiply this by the number of keys we want to add and charge the user
-        get_total_costs_for_key(
-            &mut total_cost_per_key,
-            max_key_uses,
-            &asset_by_id,
-            &asset_data_for_uses,
-        );
-
-        // Keep track of all the key IDs
-        let mut next_key_id = 0;
-        // Keep track of all the events
-        let mut event_logs = Vec::new();
-        // Add the keys to the contract
-        self.internal_add_keys_to_account(
-            &mut next_key_id,
-            &mut key_info_by_token_id,
-            &mut event_logs,
-            &drop_id,
-            max_key_uses,
-            &key_data,
-        );
-
-        // Write the drop data to storage
         let funder_id = env::predecessor_account_id();
-        let drop = InternalDrop {
+        let mut drop = InternalDrop {
             max_key_uses,
             asset_data_for_uses,
             asset_by_id,
             key_info_by_token_id,
-            next_key_id,
+            tokens_needed_by_asset: LookupMap::new(StorageKeys::TokensNeededByAsset {
+                drop_id_hash: hash_string(&drop_id.to_string()),
+            }),
+            next_key_id: 0,
+            nonce: self.internal_take_drop_nonce(),
             config: drop_config,
             funder_id: funder_id.clone(),
+            claim_window: Default::default(),
+            has_claims: false,
+            paused: false,
         };
+
+        // Keep track of all the events
+        let mut event_logs = Vec::new();
+        // Add the keys to the contract and get the total cost of the assets for all of them
+        let total_asset_cost =
+            self.internal_add_keys_to_account(&mut drop, &drop_id, &key_data, &mut event_logs);
+
+        // The first drop with a wNEAR asset for a given contract pays for registering the Keypom contract on it
+        let (wnear_registrations, wnear_registration_cost) =
+            self.internal_reserve_wnear_registrations(&drop.asset_by_id);
+
+        // Write the drop data to storage
         require!(
             self.drop_by_id.insert(&drop_id, &drop).is_none(),
             format!("Drop with ID {} already exists", drop_id)
@@ -230,7 +239,7 @@ impl Keypom {
         let refund_amount = self.determine_costs(
             key_data.len(),
             true, // We did create a drop here
-            total_cost_per_key,
+            total_asset_cost + wnear_registration_cost,
             net_storage,
             attached_deposit,
         );
@@ -249,6 +258,14 @@ impl Keypom {
         // Fire cross-contract call to all the accounts in the drop config's allowlist if it exists
         // (and a message was passed in)
 
+        for (wnear_contract_id, registration_cost) in wnear_registrations {
+            self.internal_register_with_wnear_contract(
+                wnear_contract_id,
+                registration_cost,
+                funder_id.clone(),
+            );
+        }
+
         // Now that everything is done (no more potential for panics), we can log the events
         log_events(event_logs);
         refund_amount
@@ -1,22 +1,56 @@
 use crate::*;
+use std::collections::HashSet;
 
 #[near_bindgen]
 impl Keypom {
     /// Loops through public keys and adds them to the current contract account
     /// Also adds the keys to the drop_id_for_pk map and ensures that no keys are already on the contract
+    ///
+    /// Returns the total cost of the assets for all the keys that were added (including any per-key overrides)
     pub(crate) fn internal_add_keys_to_account(
         &mut self,
-        next_key_id: &mut u64,
-        key_info_by_token_id: &mut UnorderedMap<TokenId, InternalKeyInfo>,
-        event_logs: &mut Vec<EventLog>,
+        drop: &mut InternalDrop,
         drop_id: &DropId,
-        max_uses_per_key: UseNumber,
         key_data: &[ExtKeyData],
-    ) {
+        event_logs: &mut Vec<EventLog>,
+    ) -> Balance {
+        let InternalDrop {
+            next_key_id,
+            key_info_by_token_id,
+            asset_by_id,
+            asset_data_for_uses,
+            ..
+        } = drop;
+
         // Logs for add key and NFT mint events
         let mut add_key_logs = Vec::new();
         let mut nft_mint_logs = Vec::new();
 
+        let max_uses_per_key: UseNumber = asset_data_for_uses.iter().map(|a| a.uses).sum();
+        // Get the total cost required for a key that has all its uses remaining
+        // Keys with overrides will have their cost adjusted individually
+        let mut cost_per_key = 0;
+        get_total_costs_for_key(
+            &mut cost_per_key,
+            max_uses_per_key,
+            asset_by_id,
+            asset_data_for_uses,
+            &[],
+        );
+        let mut total_asset_cost = 0;
+
+        // Likewise, keep track of the tokens every new key needs so they can be added to the drop's running totals at once
+        let mut tokens_needed_per_key = HashMap::new();
+        get_tokens_needed_for_key(
+            &mut tokens_needed_per_key,
+            max_uses_per_key,
+            asset_by_id,
+            asset_data_for_uses,
+            &[],
+        );
+        let mut tokens_needed_for_overrides = HashMap::new();
+        let mut keys_without_overrides = 0;
+
         // Loop through the public keys and add them to the contract.
         // None of these promises will fire if there's a panic so it's
         // Fine to add them in the loop
@@ -26,8 +60,34 @@ impl Keypom {
                 password_by_use,
                 metadata,
                 key_owner,
+                tokens_per_use_overrides,
             } = data;
 
+            let tokens_per_use_overrides = get_internal_tokens_per_use_overrides(
+                tokens_per_use_overrides,
+                asset_data_for_uses,
+            );
+            let mut cost_for_key = cost_per_key;
+            apply_tokens_per_use_overrides(
+                &mut cost_for_key,
+                max_uses_per_key,
+                asset_by_id,
+                asset_data_for_uses,
+                &tokens_per_use_overrides,
+            );
+            total_asset_cost += cost_for_key;
+            if tokens_per_use_overrides.is_empty() {
+                keys_without_overrides += 1;
+            } else {
+                get_tokens_needed_for_key(
+                    &mut tokens_needed_for_overrides,
+                    max_uses_per_key,
+                    asset_by_id,
+                    asset_data_for_uses,
+                    &tokens_per_use_overrides,
+                );
+            }
+
             let token_id = format!("{}:{}", drop_id, next_key_id);
             require!(
                 self.token_id_by_pk.insert(public_key, &token_id).is_none(),
@@ -61,6 +121,10 @@ impl Keypom {
                     approved_account_ids: Default::default(),
                     metadata: metadata.clone(),
                     pw_by_use,
+                    claim_window: Default::default(),
+                    tokens_per_use_overrides,
+                    retryable_use: None,
+                    reserved_use: None,
                 },
             );
 
@@ -77,6 +141,12 @@ impl Keypom {
             *next_key_id += 1;
         }
 
+        for (asset_id, tokens) in tokens_needed_per_key {
+            *tokens_needed_for_overrides.entry(asset_id).or_default() +=
+                tokens * keys_without_overrides;
+        }
+        drop.modify_tokens_needed(tokens_needed_for_overrides, false);
+
         // Construct the events themselves
         if !nft_mint_logs.is_empty() {
             event_logs.push(EventLog {
@@ -92,6 +162,8 @@ impl Keypom {
                 event: EventLogVariant::AddKey(add_key_logs),
             });
         }
+
+        total_asset_cost
     }
 
     /// Tally up all the costs for adding keys / creating a drop and refund any excess deposit
@@ -99,14 +171,13 @@ impl Keypom {
         &mut self,
         num_keys: usize,
         did_create_drop: bool,
-        asset_cost_per_key: Balance,
+        total_asset_cost: Balance,
         net_storage: u64,
         attached_deposit: Balance,
     ) -> Balance {
         let num_keys = num_keys as u128;
 
         let storage_cost = net_storage as Balance * env::storage_byte_cost().as_yoctonear();
-        let total_asset_cost = asset_cost_per_key * num_keys;
         let fees_for_user = self
             .fees_per_user
             .get(&env::predecessor_account_id())
@@ -126,6 +197,33 @@ impl Keypom {
         self.charge_with_deposit_or_balance(total_cost, attached_deposit)
     }
 
+    /// Give out the nonce for a newly stored drop and increment it for the next one
+    pub(crate) fn internal_take_drop_nonce(&mut self) -> u64 {
+        let nonce = self.next_drop_nonce;
+        self.next_drop_nonce += 1;
+        nonce
+    }
+
+    /// Move the allowlist and denylist in a use's claimer rules into their own storage and keep track of where they are
+    pub(crate) fn internal_store_claimer_lists(&mut self, rules: &mut ClaimerRules) {
+        if let Some(allowlist) = rules.allowlist.take() {
+            rules.allowlist_id = Some(self.internal_store_claimer_list(allowlist));
+        }
+        if let Some(denylist) = rules.denylist.take() {
+            rules.denylist_id = Some(self.internal_store_claimer_list(denylist));
+        }
+    }
+
+    /// Store a set of accounts for a use's claimer rules and return the ID that it can be found under
+    fn internal_store_claimer_list(&mut self, account_ids: HashSet<AccountId>) -> u64 {
+        let list_id = self.next_claimer_list_id;
+        self.next_claimer_list_id += 1;
+
+        let mut list = LookupSet::new(StorageKeys::ClaimerList { list_id });
+        list.extend(account_ids);
+        list_id
+    }
+
     /// Internal method to add a drop ID the list of drops a funder has. If they don't have any, instantiate
     /// A new unordered set and add the drop ID to it. Otherwise, just add the drop ID to the existing set
     pub(crate) fn internal_add_drop_to_funder(&mut self, funder_id: &AccountId, drop_id: &DropId) {
@@ -198,21 +296,33 @@ pub fn store_assets_by_id(
     }
 }
 
-/// Ensure that the time configurations passed in is valid
-pub(crate) fn assert_valid_time_config(config: &TimeConfig) {
+/// Ensure that the time configurations passed in is valid. When updating an existing config, `current_config`
+/// Should be passed in so that a start or end timestamp that is already in the past can be left unchanged.
+pub(crate) fn assert_valid_time_config(config: &TimeConfig, current_config: Option<&TimeConfig>) {
     // Assert that if the claim_interval is some, the start_timestamp is also some
     assert!(
         !(config.interval.is_some() && config.start.is_none()),
         "If you want to set a claim interval, you must also set a start timestamp"
     );
+    assert!(
+        config.interval.unwrap_or(1) > 0,
+        "The claim interval must be greater than 0"
+    );
 
     // Assert that both the start_timestamp and end timestamps are greater than the current block
+    // (unless they haven't changed from the current config)
+    let current_start = current_config.and_then(|c| c.start);
+    let current_end = current_config.and_then(|c| c.end);
     assert!(
-        config.start.unwrap_or(env::block_timestamp()) >= env::block_timestamp(),
+        config.start.is_none()
+            || config.start == current_start
+            || config.start.unwrap() >= env::block_timestamp(),
         "The start timestamp must be greater than the current block timestamp"
     );
     assert!(
-        config.end.unwrap_or(env::block_timestamp()) >= env::block_timestamp(),
+        config.end.is_none()
+            || config.end == current_end
+            || config.end.unwrap() >= env::block_timestamp(),
         "The end timestamp must be greater than the current block timestamp"
     );
 
@@ -224,3 +334,213 @@ pub(crate) fn assert_valid_time_config(config: &TimeConfig) {
         );
     }
 }
+
+/// Ensure the rate limit config has a non-zero window if one is specified
+pub(crate) fn assert_valid_rate_limit(config: &RateLimitConfig) {
+    assert!(
+        config.window.unwrap_or(1) > 0,
+        "The rate limit window must be greater than 0"
+    );
+}
+
+/// Ensure every suffix in the claimer rules is a sub-account suffix with a leading period
+pub(crate) fn assert_valid_claimer_rules(rules: &ClaimerRules) {
+    for suffix in rules.allowed_suffixes.iter().flatten() {
+        require!(
+            suffix.len() > 1 && suffix.starts_with('.'),
+            format!(
+                "Allowed suffix {} must start with a period (i.e .sweat)",
+                suffix
+            )
+        );
+    }
+}
+
+/// Validate all the asset data for a drop before anything is written to storage. This ensures that every use
+/// Can actually be claimed (valid time configs, valid assets and a gas budget that fits within a single transaction)
+pub(crate) fn assert_valid_asset_data(asset_data: &[ExtAssetDataForUses]) {
+    require!(
+        !asset_data.is_empty(),
+        "Must provide asset data for at least 1 use"
+    );
+
+    for (idx, ext_asset_data) in asset_data.iter().enumerate() {
+        require!(
+            ext_asset_data.uses > 0,
+            format!("Asset data at index {} must apply to at least 1 use", idx)
+        );
+
+        if let Some(time_config) = ext_asset_data.config.as_ref().and_then(|c| c.time.as_ref()) {
+            assert_valid_time_config(time_config, None);
+        }
+
+        if let Some(claimer_rules) = ext_asset_data
+            .config
+            .as_ref()
+            .and_then(|c| c.claimer_rules.as_ref())
+        {
+            assert_valid_claimer_rules(claimer_rules);
+        }
+
+        for ext_asset in ext_asset_data.assets.iter().flatten() {
+            assert_valid_ext_asset(ext_asset, idx);
+        }
+
+        // Make sure the keys can actually be claimed within the gas limit
+        InternalAssetDataForUses::from(ext_asset_data).assert_within_gas_limit();
+    }
+}
+
+/// Convert the per-use token overrides passed in for a key into their internal representation.
+/// Panics if the use doesn't exist, the asset isn't part of the use or the asset doesn't transfer an amount.
+pub(crate) fn get_internal_tokens_per_use_overrides(
+    ext_overrides: &Option<HashMap<UseNumber, HashMap<AssetId, U128>>>,
+    asset_data_for_uses: &[InternalAssetDataForUses],
+) -> Vec<TokensPerUseOverride> {
+    let mut overrides = vec![];
+    let Some(ext_overrides) = ext_overrides else {
+        return overrides;
+    };
+
+    for (use_number, amounts) in ext_overrides {
+        let assets_metadata =
+            get_asset_data_for_specific_use(asset_data_for_uses, use_number).assets_metadata;
+        require!(
+            *use_number > 0 && !assets_metadata.is_empty(),
+            format!("Use {} does not exist in the drop", use_number)
+        );
+
+        for (asset_id, tokens_per_use) in amounts {
+            require!(
+                tokens_per_use.0 > 0,
+                format!(
+                    "Override for {} in use {} must be more than 0 tokens",
+                    asset_id, use_number
+                )
+            );
+
+            let mut found = false;
+            // The same asset could be present multiple times in a use so every entry should be overridden
+            for (asset_idx, metadata) in assets_metadata.iter().enumerate() {
+                if metadata.asset_id == *asset_id {
+                    require!(
+                        metadata.tokens_per_use.is_some(),
+                        format!("Asset {} does not transfer an amount of tokens", asset_id)
+                    );
+                    overrides.push(TokensPerUseOverride {
+                        use_number: *use_number,
+                        asset_idx: asset_idx as u16,
+                        tokens_per_use: *tokens_per_use,
+                    });
+                    found = true;
+                }
+            }
+            require!(
+                found,
+                format!("Asset {} is not part of use {}", asset_id, use_number)
+            );
+        }
+    }
+
+    overrides
+}
+
+/// Ensure that an individual asset has sensible values for the amount it transfers and any methods it calls
+fn assert_valid_ext_asset(ext_asset: &ExtAsset, idx: usize) {
+    match ext_asset {
+        ExtAsset::FTAsset(ft_data) => require!(
+            ft_data.ft_amount.0 > 0,
+            format!(
+                "FT asset {} at index {} must transfer more than 0 tokens",
+                ft_data.ft_contract_id, idx
+            )
+        ),
+        ExtAsset::NearAsset(near_data) => require!(
+            near_data.yoctonear.0 > 0,
+            format!(
+                "NEAR asset at index {} must transfer more than 0 yoctoNEAR",
+                idx
+            )
+        ),
+        ExtAsset::WNEARAsset(wnear_data) => require!(
+            wnear_data.wrapped_yoctonear.0 > 0,
+            format!(
+                "wNEAR asset at index {} must wrap more than 0 yoctoNEAR",
+                idx
+            )
+        ),
+        ExtAsset::NFTAsset(_) => {}
+        ExtAsset::MTAsset(mt_data) => require!(
+            mt_data.mt_amount.0 > 0,
+            format!(
+                "MT asset {} at index {} must transfer more than 0 tokens",
+                mt_data.mt_token_id, idx
+            )
+        ),
+        ExtAsset::FCAsset(methods) => {
+            require!(
+                !methods.is_empty(),
+                format!("FC asset at index {} must have at least 1 method", idx)
+            );
+            methods
+                .iter()
+                .for_each(|method| assert_valid_method_data(method, idx));
+        }
+    }
+}
+
+/// Ensure that a method in an FC asset can be called
+fn assert_valid_method_data(method: &MethodData, idx: usize) {
+    require!(
+        !method.method_name.is_empty() && !method.method_name.contains(','),
+        format!(
+            "FC asset at index {} has an invalid method name: {:?}",
+            idx, method.method_name
+        )
+    );
+    require!(
+        !DEFAULT_PROHIBITED_FC_METHODS.contains(&method.method_name.as_str()),
+        format!(
+            "Method {} at index {} is prohibited from being called in an FC drop",
+            method.method_name, idx
+        )
+    );
+
+    // The receiver is replaced with the claiming account if receiver_to_claimer is set
+    if !method.receiver_to_claimer.unwrap_or(false) {
+        require!(
+            method.receiver_id.parse::<AccountId>().is_ok(),
+            format!(
+                "FC asset at index {} has an invalid receiver ID: {}",
+                idx, method.receiver_id
+            )
+        );
+        require!(
+            method.receiver_id != env::current_account_id().as_str(),
+            format!(
+                "FC asset at index {} cannot call the current Keypom contract",
+                idx
+            )
+        );
+    }
+
+    require!(
+        method.attached_gas.as_gas() > 0,
+        format!(
+            "Method {} at index {} must attach more than 0 gas",
+            method.method_name, idx
+        )
+    );
+
+    // Args must be JSON so that keypom and user arguments can be inserted at claim time
+    if !method.args.is_empty() {
+        let parsed_args = serde_json::from_str::<serde_json::Value>(&method.args);
+        require!(
+            parsed_args.map(|v| v.is_object()).unwrap_or(false),
+            format!(
+                "Method {} at index {} must have args that are a JSON object",
+                method.method_name, idx
+            )
+        );
+    }
+}
@@ -15,7 +15,7 @@ impl Keypom {
         limit: Option<u8>,
         keep_empty_drop: Option<bool>,
     ) -> bool {
-        self.assert_no_global_freeze();
+        self.assert_not_paused(PausableAction::withdrawals);
 
         // Measure initial storage before doing any operations
         let initial_storage = env::storage_usage();
@@ -49,6 +49,7 @@ impl Keypom {
 
         // Keep track of the total cost for the key & the required allowance to be refunded
         let mut total_cost_for_keys: Balance = 0;
+        let mut tokens_needed_for_keys = HashMap::new();
         let mut delete_key_logs = Vec::new();
         let mut nft_burn_logs = Vec::new();
 
@@ -73,7 +74,35 @@ impl Keypom {
                 key_info.remaining_uses,
                 &drop.asset_by_id,
                 &drop.asset_data_for_uses,
+                &key_info.tokens_per_use_overrides,
             );
+            // Assets that were already claimed in a rolled back use shouldn't be refunded again
+            let cur_key_use = get_key_cur_use(&drop, &key_info);
+            total_cost_for_keys -= key_info.get_retryable_use_claimed_cost(
+                cur_key_use,
+                &drop.asset_by_id,
+                &drop.asset_data_for_uses,
+            );
+            // A use that was rolled back only needs the tokens for the assets that are left to claim
+            let retry_use_number = key_info
+                .get_pending_retryable_use(cur_key_use)
+                .map(|r| r.use_number);
+            get_tokens_needed_for_key(
+                &mut tokens_needed_for_keys,
+                key_info.remaining_uses - retry_use_number.is_some() as UseNumber,
+                &drop.asset_by_id,
+                &drop.asset_data_for_uses,
+                &key_info.tokens_per_use_overrides,
+            );
+            if let Some(use_number) = retry_use_number {
+                get_tokens_needed_for_use(
+                    &mut tokens_needed_for_keys,
+                    use_number,
+                    &drop.asset_by_id,
+                    &drop.asset_data_for_uses,
+                    &key_info,
+                );
+            }
 
             add_delete_key_logs(
                 &mut nft_burn_logs,
@@ -85,6 +114,8 @@ impl Keypom {
             );
         }
 
+        drop.modify_tokens_needed(tokens_needed_for_keys, true);
+
         // Keep track of all the events starting with the NFT burn and key deletion logs
         let mut event_logs = vec![
             EventLog {
@@ -135,6 +166,9 @@ pub(crate) fn internal_clear_drop_storage(
     event_logs: &mut Vec<EventLog>,
     drop_id: &DropId,
 ) {
+    for asset_id in drop.asset_by_id.keys() {
+        drop.tokens_needed_by_asset.remove(&asset_id);
+    }
     drop.asset_by_id.clear();
     drop.key_info_by_token_id.clear();
 
@@ -0,0 +1,182 @@
+use crate::*;
+
+#[near_bindgen]
+impl Keypom {
+    /// Temporarily stop all claims and key transfers for a drop. Only callable by the drop funder
+    pub fn pause_drop(&mut self, drop_id: DropId) {
+        self.internal_set_drop_paused(drop_id, true);
+    }
+
+    /// Resume claims and key transfers for a drop that was previously paused. Only callable by the drop funder
+    pub fn unpause_drop(&mut self, drop_id: DropId) {
+        self.internal_set_drop_paused(drop_id, false);
+    }
+
+    /// Allows the funder to update the configurations for a drop after it has been created. Only the fields
+    /// That are specified will be changed. Any fields left out will keep their current value and any fields
+    /// Set to null will be cleared.
+    ///
+    /// `use_configs` maps a use number to the new config for that use. Since uses share configs with the
+    /// Other uses in the same set of asset data, updating a use will update the config for that entire set.
+    ///
+    /// Any net storage is charged to (or refunded to) the funder using the attached deposit or their balance.
+    #[payable]
+    pub fn update_drop_config(
+        &mut self,
+        drop_id: DropId,
+        drop_config: Option<DropConfigUpdate>,
+        use_configs: Option<HashMap<UseNumber, UseConfigUpdate>>,
+
+        // Should any excess attached deposit be deposited to the user's balance?
+        keep_excess_deposit: Option<bool>,
+    ) -> bool {
+        self.assert_not_paused(PausableAction::drop_creation);
+
+        // Before anything, measure storage usage so we can net the cost and charge the funder
+        let initial_storage = env::storage_usage();
+
+        let mut drop = self.drop_by_id.get(&drop_id).expect("No drop found");
+        let funder_id = drop.funder_id.clone();
+        require!(
+            funder_id == env::predecessor_account_id(),
+            "Only drop funder can update the drop config"
+        );
+
+        if let Some(update) = drop_config {
+            if let Some(Some(rate_limit)) = update.rate_limit.as_ref() {
+                assert_valid_rate_limit(rate_limit);
+            }
+            // Changing the limit after claims were counted would count them against the wrong scope or cap
+            require!(
+                update.account_claim_limit.is_none() || !drop.has_claims,
+                "Cannot change the account claim limit once the drop has been claimed"
+            );
+
+            drop.config = Some(merge_drop_config(drop.config.take(), update));
+        }
+
+        for (use_number, mut update) in use_configs.unwrap_or_default() {
+            require!(
+                use_number > 0 && use_number <= drop.max_key_uses,
+                format!("Use {} does not exist. The drop has {} uses", use_number, drop.max_key_uses)
+            );
+
+            let asset_data = get_asset_data_for_use_mut(&mut drop.asset_data_for_uses, use_number);
+            let current_config = asset_data.config.take();
+            if let Some(Some(time_config)) = update.time.as_ref() {
+                assert_valid_time_config(
+                    time_config,
+                    current_config.as_ref().and_then(|c| c.time.as_ref()),
+                );
+            }
+
+            if let Some(Some(claimer_rules)) = update.claimer_rules.as_mut() {
+                assert_valid_claimer_rules(claimer_rules);
+                self.internal_store_claimer_lists(claimer_rules);
+            }
+
+            asset_data.config = Some(merge_use_config(current_config, update));
+
+            // Changing the permissions could change which base gas applies
+            asset_data.assert_within_gas_limit();
+        }
+
+        self.drop_by_id.insert(&drop_id, &drop);
+
+        // Net the storage. If more storage was used, charge the funder. Otherwise refund the freed storage
+        let final_storage = env::storage_usage();
+        let attached_deposit = env::attached_deposit().as_yoctonear();
+        let refund_amount = if final_storage > initial_storage {
+            let storage_cost = (final_storage - initial_storage) as u128
+                * env::storage_byte_cost().as_yoctonear();
+            near_sdk::log!("Charging funder for storage: {}", storage_cost);
+            self.charge_with_deposit_or_balance(storage_cost, attached_deposit)
+        } else {
+            attached_deposit
+                + (initial_storage - final_storage) as u128 * env::storage_byte_cost().as_yoctonear()
+        };
+
+        if refund_amount > 0 {
+            // If the user wants to keep the excess deposit, just modify the user balance
+            if keep_excess_deposit.unwrap_or(false) {
+                self.internal_modify_user_balance(&funder_id, refund_amount, false);
+                return true;
+            }
+
+            near_sdk::log!("Refunding {} excess deposit", refund_amount);
+            Promise::new(funder_id).transfer(NearToken::from_yoctonear(refund_amount));
+        }
+
+        true
+    }
+
+    pub(crate) fn internal_set_drop_paused(&mut self, drop_id: DropId, paused: bool) {
+        let mut drop = self.drop_by_id.get(&drop_id).expect("No drop found");
+        let funder_id = drop.funder_id.clone();
+        require!(
+            funder_id == env::predecessor_account_id(),
+            "Only drop funder can pause or unpause the drop"
+        );
+        require!(
+            drop.paused != paused,
+            if paused { "Drop is already paused" } else { "Drop is not paused" }
+        );
+
+        drop.paused = paused;
+        self.drop_by_id.insert(&drop_id, &drop);
+
+        let log = PauseOrUnpauseDropLog {
+            funder_id: funder_id.to_string(),
+            drop_id: drop_id.to_string(),
+        };
+        log_events(vec![EventLog {
+            standard: KEYPOM_STANDARD_NAME.to_string(),
+            version: KEYPOM_STANDARD_VERSION.to_string(),
+            event: if paused {
+                EventLogVariant::DropPaused(log)
+            } else {
+                EventLogVariant::DropUnpaused(log)
+            },
+        }]);
+    }
+}
+
+/// Overwrite any fields in the current drop config that are specified in the update
+fn merge_drop_config(current: Option<DropConfig>, update: DropConfigUpdate) -> DropConfig {
+    let current = current.unwrap_or_default();
+
+    DropConfig {
+        metadata: update.metadata.unwrap_or(current.metadata),
+        nft_keys_config: update.nft_keys_config.unwrap_or(current.nft_keys_config),
+        add_key_allowlist: update.add_key_allowlist.unwrap_or(current.add_key_allowlist),
+        transfer_key_allowlist: update
+            .transfer_key_allowlist
+            .unwrap_or(current.transfer_key_allowlist),
+        delete_empty_drop: update.delete_empty_drop.unwrap_or(current.delete_empty_drop),
+        rate_limit: update.rate_limit.unwrap_or(current.rate_limit),
+        deposit_allowlist: update.deposit_allowlist.unwrap_or(current.deposit_allowlist),
+        record_claims: update.record_claims.unwrap_or(current.record_claims),
+        account_claim_limit: update
+            .account_claim_limit
+            .unwrap_or(current.account_claim_limit),
+    }
+}
+
+/// Overwrite any fields in the current use config that are specified in the update
+fn merge_use_config(current: Option<UseConfig>, update: UseConfigUpdate) -> UseConfig {
+    let current = current.unwrap_or_default();
+
+    UseConfig {
+        time: update.time.unwrap_or(current.time),
+        permissions: update.permissions.unwrap_or(current.permissions),
+        account_creation_keypom_args: update
+            .account_creation_keypom_args
+            .unwrap_or(current.account_creation_keypom_args),
+        root_account_id: update.root_account_id.unwrap_or(current.root_account_id),
+        atomic: update.atomic.unwrap_or(current.atomic),
+        account_creation_retry_window: update
+            .account_creation_retry_window
+            .unwrap_or(current.account_creation_retry_window),
+        claimer_rules: update.claimer_rules.unwrap_or(current.claimer_rules),
+    }
+}
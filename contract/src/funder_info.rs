@@ -7,7 +7,7 @@ impl Keypom {
     /// Allows users to add to their balance. This is to prepay and cover drop costs
     #[payable]
     pub fn set_funder_metadata(&mut self, metadata: Option<String>) -> bool {
-        self.assert_no_global_freeze();
+        self.assert_not_paused(PausableAction::deposits);
         let refund_amount =
             self.internal_modify_user_metadata(metadata, env::attached_deposit().as_yoctonear(), None);
 
@@ -23,7 +23,7 @@ impl Keypom {
     /// Allows users to add to their balance. This is to prepay and cover drop costs
     #[payable]
     pub fn add_to_balance(&mut self) -> bool {
-        self.assert_no_global_freeze();
+        self.assert_not_paused(PausableAction::deposits);
 
         // Get the attached_deposit value which is how much the user wants to add to their storage
         let attached_deposit = env::attached_deposit().as_yoctonear();
@@ -35,7 +35,7 @@ impl Keypom {
 
     /// Allows users to withdraw their balance
     pub fn withdraw_from_balance(&mut self, amount_to_withdraw: Option<U128>) -> bool {
-        self.assert_no_global_freeze();
+        self.assert_not_paused(PausableAction::withdrawals);
 
         // The account to withdraw storage to is always the predecessor
         let owner_id = env::predecessor_account_id();
@@ -56,6 +56,14 @@ pub(crate) fn get_key_cur_use(drop: &InternalDrop, key_info: &InternalKeyInfo) -
     drop.max_key_uses - key_info.remaining_uses + 1
 }
 
+/// Get the start of the current rate limit window. If no window length is specified, each block is its own window
+pub(crate) fn get_rate_limit_window_start(window: Option<u64>) -> u64 {
+    match window {
+        Some(window) => env::block_timestamp() - env::block_timestamp() % window,
+        None => env::block_height(),
+    }
+}
+
 /// Helper function that returns the total cost for a given key as well as its allowance
 /// This key can be partially used or not
 pub(crate) fn get_total_costs_for_key(
@@ -63,6 +71,7 @@ pub(crate) fn get_total_costs_for_key(
     remaining_uses: UseNumber,
     asset_by_id: &UnorderedMap<AssetId, InternalAsset>,
     asset_data_for_uses: &[InternalAssetDataForUses],
+    tokens_per_use_overrides: &[TokensPerUseOverride],
 ) {
     // Get the remaining asset data
     let remaining_asset_data = get_remaining_asset_data(asset_data_for_uses, remaining_uses);
@@ -71,27 +80,11 @@ pub(crate) fn get_total_costs_for_key(
     for asset_data in remaining_asset_data {
         let InternalAssetDataForUses {
             uses,
-            config: use_config,
+            config: _,
             assets_metadata,
-            required_asset_gas,
+            required_asset_gas: _,
         } = asset_data;
 
-        // If the config's permission field is set to Claim, the base should be set accordingly. In all other cases, it should be the base for CAAC
-        let base_gas_for_use =
-            if let Some(perms) = use_config.as_ref().and_then(|c| c.permissions.as_ref()) {
-                match perms {
-                    ClaimPermissions::claim => BASE_GAS_FOR_CLAIM,
-                    _ => BASE_GAS_FOR_CREATE_ACC_AND_CLAIM,
-                }
-            } else {
-                BASE_GAS_FOR_CREATE_ACC_AND_CLAIM
-            };
-
-        // Check and make sure that the time config is valid
-        if let Some(time_config) = use_config.as_ref().and_then(|c| c.time.as_ref()) {
-            assert_valid_time_config(time_config)
-        }
-
         // Loop through each asset metadata and tally the costs
         for metadata in assets_metadata {
             // Get the asset object (we only clear the assets by ID when the drop is empty and deleted)
@@ -104,15 +97,120 @@ pub(crate) fn get_total_costs_for_key(
                 internal_asset.get_yocto_refund_amount(&metadata.tokens_per_use.map(|x| x.into()));
             *total_cost_for_keys += cost_for_use * uses as u128;
         }
+    }
 
-        let total_claim_gas = required_asset_gas.as_gas() + base_gas_for_use.as_gas();
-        require!(
-            total_claim_gas <= MAX_GAS_ATTACHABLE.as_gas(),
-            format!(
-                "Total gas {} exceeds the maximum 300 TGas limit",
-                total_claim_gas
-            )
-        );
+    // Account for any amounts that were overridden for this specific key
+    apply_tokens_per_use_overrides(
+        total_cost_for_keys,
+        remaining_uses,
+        asset_by_id,
+        asset_data_for_uses,
+        tokens_per_use_overrides,
+    );
+}
+
+/// Adjust the cost of a key to account for any per-use token overrides in its remaining uses.
+/// The cost passed in should already include the default `tokens_per_use` for every remaining use.
+pub(crate) fn apply_tokens_per_use_overrides(
+    total_cost_for_keys: &mut Balance,
+    remaining_uses: UseNumber,
+    asset_by_id: &UnorderedMap<AssetId, InternalAsset>,
+    asset_data_for_uses: &[InternalAssetDataForUses],
+    tokens_per_use_overrides: &[TokensPerUseOverride],
+) {
+    let max_key_uses: UseNumber = asset_data_for_uses.iter().map(|a| a.uses).sum();
+    let first_remaining_use = max_key_uses - remaining_uses + 1;
+
+    for o in tokens_per_use_overrides
+        .iter()
+        .filter(|o| o.use_number >= first_remaining_use)
+    {
+        let metadata = &get_asset_data_for_specific_use(asset_data_for_uses, &o.use_number)
+            .assets_metadata[o.asset_idx as usize];
+        let internal_asset = asset_by_id
+            .get(&metadata.asset_id)
+            .expect("Asset not found");
+
+        // Swap the default cost for the use with the overridden one
+        let default_cost =
+            internal_asset.get_yocto_refund_amount(&metadata.tokens_per_use.map(|x| x.into()));
+        let override_cost = internal_asset.get_yocto_refund_amount(&Some(o.tokens_per_use.0));
+        *total_cost_for_keys = *total_cost_for_keys + override_cost - default_cost;
+    }
+}
+
+/// Helper function that tallies up how many tokens of every deposited asset (FTs, NFTs and MTs) the remaining uses of a key need.
+/// Like `get_total_costs_for_key`, this key can be partially used or not
+pub(crate) fn get_tokens_needed_for_key(
+    tokens_needed: &mut HashMap<AssetId, Balance>,
+    remaining_uses: UseNumber,
+    asset_by_id: &UnorderedMap<AssetId, InternalAsset>,
+    asset_data_for_uses: &[InternalAssetDataForUses],
+    tokens_per_use_overrides: &[TokensPerUseOverride],
+) {
+    for asset_data in get_remaining_asset_data(asset_data_for_uses, remaining_uses) {
+        for metadata in asset_data.assets_metadata.iter() {
+            let internal_asset = asset_by_id
+                .get(&metadata.asset_id)
+                .expect("Asset not found");
+
+            if let Some(tokens) = internal_asset
+                .get_deposited_tokens_per_use(&metadata.tokens_per_use.map(|x| x.into()))
+            {
+                *tokens_needed.entry(metadata.asset_id.clone()).or_default() +=
+                    tokens * asset_data.uses as u128;
+            }
+        }
+    }
+
+    // Swap the default amount for any uses that were overridden for this specific key
+    let max_key_uses: UseNumber = asset_data_for_uses.iter().map(|a| a.uses).sum();
+    let first_remaining_use = max_key_uses - remaining_uses + 1;
+    for o in tokens_per_use_overrides
+        .iter()
+        .filter(|o| o.use_number >= first_remaining_use)
+    {
+        let metadata = &get_asset_data_for_specific_use(asset_data_for_uses, &o.use_number)
+            .assets_metadata[o.asset_idx as usize];
+        let internal_asset = asset_by_id
+            .get(&metadata.asset_id)
+            .expect("Asset not found");
+
+        if let (Some(default_tokens), Some(override_tokens)) = (
+            internal_asset.get_deposited_tokens_per_use(&metadata.tokens_per_use.map(|x| x.into())),
+            internal_asset.get_deposited_tokens_per_use(&Some(o.tokens_per_use.0)),
+        ) {
+            let needed = tokens_needed.entry(metadata.asset_id.clone()).or_default();
+            *needed = *needed + override_tokens - default_tokens;
+        }
+    }
+}
+
+/// Helper function that tallies up how many tokens of every deposited asset (FTs, NFTs and MTs) a single use of a key needs
+pub(crate) fn get_tokens_needed_for_use(
+    tokens_needed: &mut HashMap<AssetId, Balance>,
+    use_number: UseNumber,
+    asset_by_id: &UnorderedMap<AssetId, InternalAsset>,
+    asset_data_for_uses: &[InternalAssetDataForUses],
+    key_info: &InternalKeyInfo,
+) {
+    let asset_data = get_asset_data_for_specific_use(asset_data_for_uses, &use_number);
+    for (asset_idx, metadata) in asset_data.assets_metadata.iter().enumerate() {
+        // Assets that were already claimed in a rolled back use are no longer needed
+        if !key_info.is_asset_pending(use_number, asset_idx) {
+            continue;
+        }
+
+        let internal_asset = asset_by_id
+            .get(&metadata.asset_id)
+            .expect("Asset not found");
+
+        let tokens_per_use = key_info.get_tokens_per_use(use_number, asset_idx, metadata);
+        if let Some(tokens) =
+            internal_asset.get_deposited_tokens_per_use(&tokens_per_use.map(|x| x.into()))
+        {
+            *tokens_needed.entry(metadata.asset_id.clone()).or_default() += tokens;
+        }
     }
 }
 
@@ -170,6 +268,22 @@ pub(crate) fn get_asset_data_for_specific_use(
     }
 }
 
+/// Get a mutable reference to the asset data that the given use number falls under
+pub(crate) fn get_asset_data_for_use_mut(
+    asset_data_for_uses: &mut [InternalAssetDataForUses],
+    use_number: UseNumber,
+) -> &mut InternalAssetDataForUses {
+    let mut uses_traversed = 0;
+    for asset_data in asset_data_for_uses.iter_mut() {
+        uses_traversed += asset_data.uses;
+        if use_number <= uses_traversed {
+            return asset_data;
+        }
+    }
+
+    env::panic_str("Asset data not found for use")
+}
+
 /// Take a token ID and return the drop ID and key nonce based on the `:` delimiter.
 pub(crate) fn parse_token_id(token_id: &TokenId) -> Result<(DropId, u64), String> {
     let delimiter = ":";
@@ -207,6 +321,8 @@ pub(crate) fn add_keypom_args(
         drop_id_field: None,
         key_id_field: None,
         funder_id_field: None,
+        previous_result_field: None,
+        previous_result_path: None,
     });
 
     insert_keypom_arg(
@@ -221,6 +337,11 @@ pub(crate) fn add_keypom_args(
         &keypom_args.funder_id_field,
         funder_id.to_string(),
     )?;
+    insert_keypom_arg(
+        output_args,
+        &keypom_args.previous_result_field,
+        PREVIOUS_RESULT_MARKER.to_string(),
+    )?;
 
     if output_args.contains("\"keypom_args\"") {
         return Err(
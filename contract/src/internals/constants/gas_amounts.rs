@@ -34,6 +34,16 @@ pub const BASE_GAS_FOR_CLAIM: Gas = Gas::from_gas(
     BASE_GAS_FOR_RESOLVE_ACCOUNT_CREATION.as_gas() + 2 * MIN_GAS_FOR_RECEIPT_COMPUTATION.as_gas(),
 );
 
+// ------------------------ Claimer Rules ------------------------ //
+/// Actual amount of GAS to attach to the external membership check.
+/// This value is equal to 10 TGas
+pub const GAS_FOR_MEMBERSHIP_CHECK: Gas = Gas::from_tgas(10);
+/// The base amount of gas required for the callback after the membership check.
+/// This does not include the actual asset promises
+pub const BASE_GAS_FOR_RESOLVE_MEMBERSHIP_CHECK: Gas = Gas::from_gas(
+    MIN_GAS_FOR_RECEIPT_COMPUTATION.as_gas() + MIN_BASE_GAS_FOR_RECEIPT_SPIN_UP.as_gas(),
+);
+
 // ------------------------ Shared Constants ------------------------ //
 /// The minimum amount of gas required for the callback after a all the assets have been claimed.
 pub const MIN_GAS_FOR_RESOLVE_ASSET_CLAIM: Gas = Gas::from_gas(
@@ -1,6 +1,15 @@
 // ------------------------ Access Key Method Names ------------------------ //
 pub const GLOBAL_KEY_METHOD_NAMES: &str =
-    "claim,create_account_and_claim,nft_transfer,nft_approve,nft_revoke,verify_signature";
+    "claim,create_account_and_claim,nft_transfer,nft_approve,nft_transfer_payout";
+/// Methods that a claim-only signing key can call
+pub const CLAIM_KEY_METHOD_NAMES: &str = "claim,create_account_and_claim";
+/// Methods that a transfer-only signing key can call
+pub const TRANSFER_KEY_METHOD_NAMES: &str = "nft_transfer,nft_approve,nft_transfer_payout";
+
+// ------------------------ Signed Payloads ------------------------ //
+/// Prefix for every `SignedKeyPayload` before it is hashed and signed. This sits in the off-chain message range
+/// (>= 2^31) outlined in NEP-461 but is distinct from NEP-413 so key signatures can't be confused with wallet messages.
+pub const KEYPOM_SIGNATURE_TAG: u32 = (1 << 31) + 2_023;
 
 // ------------------------ NFT Standard Stuff ------------------------ //
 /// This spec can be treated like a version of the standard.
@@ -23,3 +32,7 @@ pub const NEAR_ASSET_ID: &str = "near";
 pub const NONE_ASSET_ID: &str = "none-asset";
 /// Prefix for all FC asset IDs
 pub const FC_ASSET_PREFIX: &str = "fc&";
+/// Prefix for all MT asset IDs (followed by `contract:token_id`)
+pub const MT_ASSET_PREFIX: &str = "mt&";
+/// Prefix for all wrapped NEAR asset IDs (followed by the wrapped NEAR contract)
+pub const WNEAR_ASSET_PREFIX: &str = "wnear&";
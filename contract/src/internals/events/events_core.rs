@@ -11,6 +11,8 @@ pub enum ExtAssetForEvents {
     FTAsset(ExtFTData),
     NearAsset(ExtNEARData),
     NFTAsset(ExtNFTData),
+    MTAsset(ExtMTData),
+    WNEARAsset(ExtWNEARData),
     FCAsset(Vec<ExtFCDataForEvents>),
 }
 
@@ -40,9 +42,15 @@ pub enum EventLogVariant {
     DropCreation(CreateOrDeleteDropLog),
     DropDeletion(CreateOrDeleteDropLog),
 
+    /// Whenever a funder pauses or unpauses a drop
+    DropPaused(PauseOrUnpauseDropLog),
+    DropUnpaused(PauseOrUnpauseDropLog),
+
     /// Whenever keys are claimed
     Claim(ClaimLog),
     CreateAccountAndClaim(CreateAccountAndClaimLog),
+    /// Whenever some assets in a claim fail to be transferred
+    ClaimFailed(ClaimFailedLog),
 
     /// Whenever keys are added
     AddKey(Vec<AddOrDeleteKeyLog>),
@@ -55,6 +63,15 @@ pub enum EventLogVariant {
     /// Whenever keys are transferred
     KeyTransfer(TransferKeyLog),
     NftTransfer(Vec<NftTransferLog>),
+
+    /// Whenever signing admins are added or removed
+    AddSigningAdmin(Vec<SigningAdminLog>),
+    RemoveSigningAdmin(Vec<SigningAdminLog>),
+
+    /// Whenever the contract ownership transfer is proposed, accepted or cancelled
+    OwnershipTransferProposed(OwnershipTransferLog),
+    OwnershipTransferAccepted(OwnershipTransferLog),
+    OwnershipTransferCancelled(OwnershipTransferLog),
 }
 
 /// Interface to capture data about an event
@@ -13,6 +13,18 @@ pub struct CreateOrDeleteDropLog {
 }
 
 
+/// An event log to capture whenever a drop is paused or unpaused by its funder
+///
+/// Arguments
+/// * `funder_id`: "account.near"
+/// * `drop_id`: "my-drop123"
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseOrUnpauseDropLog {
+    pub funder_id: String,
+    pub drop_id: String,
+}
+
 /// An event log to capture whenever a key is added to or deleted from a drop
 ///
 /// Arguments
@@ -85,4 +97,44 @@ pub struct CreateAccountAndClaimLog {
     pub drop_id: String,
 
     pub assets: Vec<Option<ExtAssetForEvents>>,
-}
\ No newline at end of file
+}
+
+/// An event log to capture whenever some of the assets in a claim fail to be transferred
+///
+/// Arguments
+/// * `drop_id`: "my-drop123"
+/// * `public_key`: "ed25519:8WWpRpZswPHoJn5THFdBaFdbVeSjtSrQmEcoUeaNdkKy"
+/// * `use_number`: which use of the key was being claimed
+/// * `failed_asset_indices`: index of every asset in the use that failed
+/// * `rolled_back`: whether the use was restored so that the failed assets can be claimed again
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ClaimFailedLog {
+    pub drop_id: String,
+    pub public_key: String,
+    pub use_number: UseNumber,
+    pub failed_asset_indices: Vec<u32>,
+    pub rolled_back: bool,
+}
+
+/// An event log to capture whenever an account is added to or removed from the signing admins
+///
+/// Arguments
+/// * `account_id`: "admin.near"
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SigningAdminLog {
+    pub account_id: String,
+}
+
+/// An event log to capture each step of a contract ownership transfer
+///
+/// Arguments
+/// * `owner_id`: The current (or in the case of an accepted transfer, previous) owner of the contract
+/// * `pending_owner_id`: The account that was proposed as the new owner
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OwnershipTransferLog {
+    pub owner_id: String,
+    pub pending_owner_id: String,
+}
@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LookupMap, LookupSet, UnorderedMap, UnorderedSet};
-use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::collections::{LookupMap, LookupSet, UnorderedMap, UnorderedSet, Vector};
+use near_sdk::json_types::{Base64VecU8, U128, U64};
 use near_sdk::serde::ser::SerializeStruct;
 use near_sdk::serde::{Deserialize, Serialize, Serializer};
 use near_sdk::serde_json::json;
@@ -15,9 +15,11 @@ mod assets;
 mod drop_claiming;
 mod drop_creation;
 mod drop_deletion;
+mod drop_management;
 mod funder_info;
 mod helpers;
 mod internals;
+mod migrate;
 mod models;
 mod nft_keys;
 mod owner;
@@ -29,6 +31,7 @@ use drop_creation::*;
 use drop_deletion::*;
 use helpers::*;
 use internals::*;
+use migrate::*;
 use models::*;
 use nft_keys::*;
 
@@ -41,32 +44,61 @@ pub struct Keypom {
     // ------------------------ Owner Only Things ------------------------ //
     /// Owner of the contract that can set configurations such as global freezes etc.
     pub contract_owner_id: AccountId,
-    /// Whether or not the contract is frozen and no new drops can be created / keys added.
-    pub global_freeze: bool,
+    /// Account that has been proposed as the next owner and must accept before ownership is transferred
+    pub pending_owner_id: Option<AccountId>,
+    /// Which parts of the contract (creation, claims, key transfers, deposits, withdrawals) are currently paused.
+    pub pause_state: PauseState,
     /// Outlines the fees that are charged for every drop and key
     pub fee_structure: KeypomFees,
     /// Total amount of fees available for withdrawal collected overtime.
     pub fees_collected: u128,
     /// Overload the fees for specific users by providing custom fees
     pub fees_per_user: LookupMap<AccountId, KeypomFees>,
-    /// Key used to sign transactions for the contract
-    pub signing_pks: LookupSet<PublicKey>,
-    pub signing_admins: LookupSet<AccountId>,
+    /// Keys used to sign transactions for the contract mapped to their scope, expiry and allowance
+    pub signing_pks: UnorderedMap<PublicKey, SigningKeyInfo>,
+    /// Accounts that can add and remove signing keys
+    pub signing_admins: UnorderedSet<AccountId>,
+    /// Default rate limit applied to drops that don't specify their own
+    pub global_rate_limit: Option<RateLimitConfig>,
 
     // ------------------------ Drops ------------------------ //
     /// Map a drop ID to its internal drop data
     pub drop_by_id: LookupMap<DropId, InternalDrop>,
+    /// Drops stored in the layout from before the last upgrade. These can't be used until `migrate_drops` moves them into `drop_by_id`
+    pub legacy_drop_by_id: Option<LookupMap<DropId, OldInternalDrop>>,
+    /// Nonce given to the next drop that's created or migrated. Drop IDs can be reused once a drop is deleted so this tells them apart
+    pub next_drop_nonce: u64,
     /// Keep track of the drop ids that each funder has created. This is used for view methods.
     pub drop_ids_by_funder: LookupMap<AccountId, UnorderedSet<DropId>>,
     /// Get the token ID for any given public key
     pub token_id_by_pk: UnorderedMap<PublicKey, TokenId>,
     /// Which account should all newly created accounts be sub-accounts of? (i.e `testnet` or `near`)
     pub root_account: AccountId,
+    /// Network that the contract is deployed on (i.e `mainnet` or `testnet`). Key signatures are bound to it
+    pub chain_id: String,
 
     // ------------------------ NFT Keys ------------------------ //
     /// Keeps track of all the token IDs for a given account
     pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
 
+    // ------------------------ Wrapped NEAR ------------------------ //
+    /// Wrapped NEAR contracts that the Keypom contract has registered itself on (or is in the process of registering on)
+    pub registered_wnear_contracts: UnorderedSet<AccountId>,
+
+    // ------------------------ Claim Receipts ------------------------ //
+    /// Receipts for every claim on drops that have `record_claims` enabled, keyed by the drop's nonce. These outlive the drop itself
+    pub claim_receipts_by_drop: LookupMap<u64, Vector<ClaimReceipt>>,
+    /// Where to find the receipt for every claim an account has received
+    pub claim_receipts_by_account: LookupMap<AccountId, Vector<ClaimReceiptId>>,
+
+    // ------------------------ Account Claim Limits ------------------------ //
+    /// How many times each account has claimed within a drop or group of drops that limits claims per account
+    pub claims_per_account: LookupMap<AccountClaimScope, LookupMap<AccountId, u32>>,
+
+    // ------------------------ Claimer Rules ------------------------ //
+    /// ID given to the next allowlist or denylist that's stored for a use's claimer rules
+    pub next_claimer_list_id: u64,
+
     // ------------------------ Utility ------------------------ //
     /// Keep track of the balances for each user. This is to prepay for drop creations
     pub funder_info_by_id: LookupMap<AccountId, FunderInfo>,
@@ -80,32 +112,34 @@ impl Keypom {
         owner_id: AccountId,
         signing_pks: Vec<PublicKey>,
         signing_admins: Vec<AccountId>,
+        chain_id: String,
     ) -> Self {
-        for signing_pubkey in &signing_pks {
-            Promise::new(env::current_account_id()).add_access_key_allowance(
-                signing_pubkey.clone(),
-                Allowance::Unlimited,
-                env::current_account_id(),
-                GLOBAL_KEY_METHOD_NAMES.to_string(),
-            );
-
+        // Initial signing keys can call every method with an unlimited allowance and never expire
+        let signing_key_info = SigningKeyInfo::new(None);
+        let mut signing_pks_map = UnorderedMap::new(StorageKeys::SigningPks);
+        let mut promise = Promise::new(env::current_account_id());
+        for signing_pubkey in signing_pks {
             env::log_str(format!("Signing PK: {:?}", signing_pubkey).as_str());
-        }
 
-        let mut signing_pks_set = LookupSet::new(StorageKeys::SigningPks);
-        signing_pks.iter().for_each(|pk| { signing_pks_set.insert(pk); });
+            signing_pks_map.insert(&signing_pubkey, &signing_key_info);
+            promise = signing_key_info.add_access_key(promise, signing_pubkey);
+        }
 
-        let mut signing_admins_set = LookupSet::new(StorageKeys::SigningAdmins);
+        let mut signing_admins_set = UnorderedSet::new(StorageKeys::SigningAdmins);
         signing_admins.iter().for_each(|admin| { signing_admins_set.insert(admin); });
 
         Self {
             contract_owner_id: owner_id,
-            global_freeze: false,
+            pending_owner_id: None,
+            pause_state: PauseState::default(),
             drop_by_id: LookupMap::new(StorageKeys::DropById),
+            legacy_drop_by_id: None,
+            next_drop_nonce: 0,
             token_id_by_pk: UnorderedMap::new(StorageKeys::TokenIdByPk),
             tokens_per_owner: LookupMap::new(StorageKeys::TokensPerOwner),
             funder_info_by_id: LookupMap::new(StorageKeys::FunderInfoById),
             root_account,
+            chain_id,
             fees_per_user: LookupMap::new(StorageKeys::FeesPerUser),
             drop_ids_by_funder: LookupMap::new(StorageKeys::DropIdsByFunder),
             fees_collected: 0,
@@ -113,42 +147,167 @@ impl Keypom {
                 per_drop: 0,
                 per_key: 0,
             },
-            signing_pks: signing_pks_set,
+            signing_pks: signing_pks_map,
             signing_admins: signing_admins_set,
+            global_rate_limit: None,
+            registered_wnear_contracts: UnorderedSet::new(StorageKeys::RegisteredWNEARContracts),
+            claim_receipts_by_drop: LookupMap::new(StorageKeys::ClaimReceiptsByDrop),
+            claim_receipts_by_account: LookupMap::new(StorageKeys::ClaimReceiptsByAccount),
+            claims_per_account: LookupMap::new(StorageKeys::ClaimsPerAccount),
+            next_claimer_list_id: 0,
         }
     }
 
-    /// Helper function to make sure there isn't a global freeze on the contract
-    pub(crate) fn assert_no_global_freeze(&self) {
+    /// Helper function to make sure the given set of entry points isn't paused. The contract owner is exempt.
+    pub(crate) fn assert_not_paused(&self, action: PausableAction) {
         if env::predecessor_account_id() != self.contract_owner_id {
             require!(
-                !self.global_freeze,
-                "Contract is frozen and no new drops or keys can be created"
+                !self.pause_state.is_paused(action),
+                format!("Contract is paused for {:?}", action)
             );
         }
     }
 
+    /// Get the rate limit that applies to claims for a given drop. The drop's own config takes precedence over the global one
+    pub(crate) fn get_rate_limit_for_drop(&self, drop: &InternalDrop) -> Option<RateLimitConfig> {
+        drop.config
+            .as_ref()
+            .and_then(|c| c.rate_limit.clone())
+            .or(self.global_rate_limit.clone())
+    }
+
     #[private]
     pub fn add_signing_admin(&mut self, account_id: AccountId) {
         require!(env::predecessor_account_id() == env::current_account_id(), "Only the contract can add admins");
-        self.signing_admins.insert(&account_id);
+        self.internal_add_signing_admins(vec![account_id]);
+    }
+
+    /// Add a set of accounts that can manage the contract's signing keys. Only callable by the contract owner
+    pub fn add_signing_admins(&mut self, account_ids: Vec<AccountId>) {
+        self.assert_owner();
+        self.internal_add_signing_admins(account_ids);
+    }
+
+    /// Remove a set of accounts from the signing admins. Only callable by the contract owner
+    pub fn remove_signing_admins(&mut self, account_ids: Vec<AccountId>) {
+        self.assert_owner();
+
+        let mut admin_logs = Vec::new();
+        for account_id in account_ids {
+            require!(
+                self.signing_admins.remove(&account_id),
+                format!("{} is not a signing admin", account_id)
+            );
+            admin_logs.push(SigningAdminLog {
+                account_id: account_id.to_string(),
+            });
+        }
+
+        log_events(vec![EventLog {
+            standard: KEYPOM_STANDARD_NAME.to_string(),
+            version: KEYPOM_STANDARD_VERSION.to_string(),
+            event: EventLogVariant::RemoveSigningAdmin(admin_logs),
+        }]);
+    }
+
+    pub(crate) fn internal_add_signing_admins(&mut self, account_ids: Vec<AccountId>) {
+        let mut admin_logs = Vec::new();
+        for account_id in account_ids {
+            require!(
+                self.signing_admins.insert(&account_id),
+                format!("{} is already a signing admin", account_id)
+            );
+            admin_logs.push(SigningAdminLog {
+                account_id: account_id.to_string(),
+            });
+        }
+
+        log_events(vec![EventLog {
+            standard: KEYPOM_STANDARD_NAME.to_string(),
+            version: KEYPOM_STANDARD_VERSION.to_string(),
+            event: EventLogVariant::AddSigningAdmin(admin_logs),
+        }]);
     }
 
-    pub fn add_signing_pks(&mut self, public_keys: Vec<PublicKey>) {
+    /// Register a set of keys that can sign transactions for the contract and add them as access keys.
+    /// All keys share the same scope, expiry and allowance which default to all methods, never and unlimited respectively.
+    pub fn add_signing_pks(&mut self, public_keys: Vec<PublicKey>, config: Option<SigningKeyConfig>) {
         require!(self.signing_admins.contains(&env::predecessor_account_id()), "Only admins can add signing pks");
-        public_keys.iter().for_each(|pk| {self.signing_pks.insert(pk);});
+
+        let signing_key_info = SigningKeyInfo::new(config);
+        let mut promise = Promise::new(env::current_account_id());
+        for pk in public_keys {
+            require!(
+                self.signing_pks.insert(&pk, &signing_key_info).is_none(),
+                format!("Signing key {:?} already exists", pk)
+            );
+            promise = signing_key_info.add_access_key(promise, pk);
+        }
     }
 
+    /// Remove a set of signing keys from the contract and delete their access keys
     pub fn remove_signing_pks(&mut self, public_keys: Vec<PublicKey>) {
         require!(self.signing_admins.contains(&env::predecessor_account_id()), "Only admins can remove signing pks");
-        public_keys.iter().for_each(|pk| {self.signing_pks.remove(pk);});
+
+        let mut promise = Promise::new(env::current_account_id());
+        for pk in public_keys {
+            require!(
+                self.signing_pks.remove(&pk).is_some(),
+                format!("Signing key {:?} not found", pk)
+            );
+            promise = promise.delete_key(pk);
+        }
+    }
+
+    /// Remove every signing key that has passed its expiry and delete its access key so it can't keep spending its allowance.
+    /// Callable by the contract owner or any signing admin. Returns the keys that were removed
+    pub fn prune_expired_signing_keys(&mut self) -> Vec<PublicKey> {
+        let predecessor = env::predecessor_account_id();
+        require!(
+            predecessor == self.contract_owner_id || self.signing_admins.contains(&predecessor),
+            "Only the owner or admins can prune signing pks"
+        );
+
+        let expired_pks: Vec<PublicKey> = self
+            .signing_pks
+            .iter()
+            .filter(|(_, info)| info.is_expired())
+            .map(|(pk, _)| pk)
+            .collect();
+
+        let mut promise = Promise::new(env::current_account_id());
+        for pk in expired_pks.iter() {
+            self.signing_pks.remove(pk);
+            promise = promise.delete_key(pk.clone());
+        }
+
+        expired_pks
     }
 
-    pub(crate) fn verify_signature(&mut self, signature: Base64VecU8, pk: PublicKey, arguments: String) -> bool {
+    /// Verify that `signature` is a valid signature by `pk` over the `SignedKeyPayload` for this call.
+    /// The payload binds the chain, contract, method name, arguments, the key's current nonce and an expiry
+    /// so that a signature can't be replayed against another network, deployment, method or after it has expired.
+    pub(crate) fn verify_signature(
+        &mut self,
+        signature: KeySignature,
+        pk: PublicKey,
+        method_name: &str,
+        arguments: String,
+    ) -> bool {
         near_sdk::log!("argument string in verify signature: {}", arguments);
         
         // Assert valid key signed the transaction
-        self.assert_contract_key();
+        self.assert_contract_key(method_name);
+
+        let valid_until = signature.valid_until.0;
+        let current_timestamp = env::block_timestamp();
+        require!(
+            current_timestamp <= valid_until,
+            format!(
+                "Signature expired at {}. Current timestamp {}",
+                valid_until, current_timestamp
+            )
+        );
 
         near_sdk::log!(
             "Verifying PK: {}: {:?}",
@@ -156,7 +315,7 @@ impl Keypom {
             pk
         );
 
-        // Build expected message, global signing message + linkdrop pk signing nonce
+        // Build expected payload, bound to this contract and method + linkdrop pk signing nonce
         let token_id = self
             .token_id_by_pk
             .get(&pk)
@@ -169,12 +328,19 @@ impl Keypom {
             .get(&token_id)
             .expect("Key not found");
 
-        let expected_message = format!("{}{}", arguments, key_info.message_nonce);
+        let expected_payload = SignedKeyPayload {
+            chain_id: self.chain_id.clone(),
+            contract_id: env::current_account_id(),
+            method_name: method_name.to_string(),
+            args: arguments,
+            nonce: key_info.message_nonce,
+            valid_until,
+        };
 
-        // Verify the signature is the valid message and signed by the linkdrop PK
+        // Verify the signature is over the payload hash and signed by the linkdrop PK
         let pk_bytes = pk_to_32_byte_array(&pk).unwrap();
-        let sig_bytes = vec_to_64_byte_array(signature.into()).unwrap();
-        let is_valid = env::ed25519_verify(&sig_bytes, expected_message.as_bytes(), pk_bytes);
+        let sig_bytes = vec_to_64_byte_array(signature.signature.into()).unwrap();
+        let is_valid = env::ed25519_verify(&sig_bytes, &expected_payload.to_signable_hash(), pk_bytes);
 
         // Only increment the nonce if the signature is valid.
         // Otherwise, someone could pass in a different public key and increment their nonce
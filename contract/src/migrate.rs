@@ -0,0 +1,410 @@
+use crate::*;
+use near_sdk::collections::LookupSet;
+use std::collections::HashSet;
+
+/// Contract state as it was stored before the last upgrade
+#[derive(BorshDeserialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldKeypom {
+    pub contract_owner_id: AccountId,
+    pub global_freeze: bool,
+    pub fee_structure: KeypomFees,
+    pub fees_collected: u128,
+    pub fees_per_user: LookupMap<AccountId, KeypomFees>,
+    pub signing_pks: LookupSet<PublicKey>,
+    pub signing_admins: LookupSet<AccountId>,
+    pub drop_by_id: LookupMap<DropId, OldInternalDrop>,
+    pub drop_ids_by_funder: LookupMap<AccountId, UnorderedSet<DropId>>,
+    pub token_id_by_pk: UnorderedMap<PublicKey, TokenId>,
+    pub root_account: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub funder_info_by_id: LookupMap<AccountId, FunderInfo>,
+}
+
+/// Drop data as it was stored before the last upgrade
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldInternalDrop {
+    pub funder_id: AccountId,
+    pub max_key_uses: UseNumber,
+    pub asset_by_id: UnorderedMap<AssetId, OldInternalAsset>,
+    pub asset_data_for_uses: Vec<OldInternalAssetDataForUses>,
+    pub key_info_by_token_id: UnorderedMap<TokenId, OldInternalKeyInfo>,
+    pub next_key_id: u64,
+    pub config: Option<OldDropConfig>,
+}
+
+/// Key data as it was stored before the last upgrade
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldInternalKeyInfo {
+    pub message_nonce: u32,
+    pub pub_key: PublicKey,
+    pub remaining_uses: UseNumber,
+    pub owner_id: Option<AccountId>,
+    pub last_claimed: u64,
+    pub metadata: Option<String>,
+    pub pw_by_use: Option<HashMap<UseNumber, Vec<u8>>>,
+    pub approved_account_ids: HashMap<AccountId, u64>,
+    pub next_approval_id: u64,
+}
+
+/// Asset data for a set of uses as it was stored before the last upgrade
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldInternalAssetDataForUses {
+    pub uses: UseNumber,
+    pub config: Option<OldUseConfig>,
+    pub assets_metadata: Vec<AssetMetadata>,
+    pub required_asset_gas: Gas,
+}
+
+/// Use config as it was stored before the last upgrade
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldUseConfig {
+    pub time: Option<TimeConfig>,
+    pub permissions: Option<ClaimPermissions>,
+    pub account_creation_keypom_args: Option<OldKeypomInjectedArgs>,
+    pub root_account_id: Option<AccountId>,
+}
+
+/// Drop config as it was stored before the last upgrade
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldDropConfig {
+    pub metadata: Option<DropMetadata>,
+    pub nft_keys_config: Option<NFTKeyConfigurations>,
+    pub add_key_allowlist: Option<HashSet<AccountId>>,
+    pub transfer_key_allowlist: Option<HashSet<AccountId>>,
+    pub delete_empty_drop: Option<bool>,
+}
+
+/// Asset data as it was stored before the last upgrade
+#[allow(non_camel_case_types)]
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum OldInternalAsset {
+    ft(OldInternalFTData),
+    nft(OldInternalNFTData),
+    fc(OldFCData),
+    near,
+    none,
+}
+
+/// FT data as it was stored before the last upgrade
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldInternalFTData {
+    pub contract_id: AccountId,
+    pub balance_avail: Balance,
+    pub registration_cost: Balance,
+}
+
+/// FC data as it was stored before the last upgrade
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldFCData {
+    pub methods: Vec<OldMethodData>,
+}
+
+/// FC method data as it was stored before the last upgrade
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldMethodData {
+    pub receiver_id: String,
+    pub method_name: String,
+    pub args: String,
+    pub attached_deposit: U128,
+    pub attached_gas: Gas,
+    pub keypom_args: Option<OldKeypomInjectedArgs>,
+    pub receiver_to_claimer: Option<bool>,
+    pub user_args_rule: Option<UserArgsRule>,
+}
+
+/// Keypom injected args as they were stored before the last upgrade
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldKeypomInjectedArgs {
+    pub account_id_field: Option<String>,
+    pub drop_id_field: Option<String>,
+    pub key_id_field: Option<String>,
+    pub funder_id_field: Option<String>,
+}
+
+/// NFT data as it was stored before the last upgrade
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldInternalNFTData {
+    pub contract_id: AccountId,
+    pub token_ids: Vec<TokenId>,
+}
+
+impl From<OldInternalAsset> for InternalAsset {
+    fn from(old: OldInternalAsset) -> Self {
+        match old {
+            // Claimers were always registered before the transfer and the gas for existing uses was calculated that way
+            OldInternalAsset::ft(ft_data) => InternalAsset::ft(InternalFTData {
+                contract_id: ft_data.contract_id,
+                balance_avail: ft_data.balance_avail,
+                registration_cost: ft_data.registration_cost,
+                registration_policy: FTRegistrationPolicy::always,
+            }),
+            // NFTs were always handed out in LIFO order
+            OldInternalAsset::nft(nft_data) => InternalAsset::nft(InternalNFTData {
+                contract_id: nft_data.contract_id,
+                token_ids: nft_data.token_ids,
+                distribution: NFTDistribution::lifo,
+                pinned_tokens: vec![],
+            }),
+            // Methods were always fired 1 after the next regardless of their results
+            OldInternalAsset::fc(fc_data) => InternalAsset::fc(FCData {
+                methods: fc_data
+                    .methods
+                    .into_iter()
+                    .map(|method| MethodData {
+                        receiver_id: method.receiver_id,
+                        method_name: method.method_name,
+                        args: method.args,
+                        attached_deposit: method.attached_deposit,
+                        attached_gas: method.attached_gas,
+                        keypom_args: method.keypom_args.map(|args| args.into()),
+                        receiver_to_claimer: method.receiver_to_claimer,
+                        user_args_rule: method.user_args_rule,
+                        halt_on_failure: None,
+                    })
+                    .collect(),
+            }),
+            OldInternalAsset::near => InternalAsset::near,
+            OldInternalAsset::none => InternalAsset::none,
+        }
+    }
+}
+
+impl From<OldKeypomInjectedArgs> for KeypomInjectedArgs {
+    fn from(old: OldKeypomInjectedArgs) -> Self {
+        Self {
+            account_id_field: old.account_id_field,
+            drop_id_field: old.drop_id_field,
+            key_id_field: old.key_id_field,
+            funder_id_field: old.funder_id_field,
+            previous_result_field: None,
+            previous_result_path: None,
+        }
+    }
+}
+
+impl From<OldInternalAssetDataForUses> for InternalAssetDataForUses {
+    fn from(old: OldInternalAssetDataForUses) -> Self {
+        Self {
+            uses: old.uses,
+            config: old.config.map(|config| UseConfig {
+                time: config.time,
+                permissions: config.permissions,
+                account_creation_keypom_args: config
+                    .account_creation_keypom_args
+                    .map(|args| args.into()),
+                root_account_id: config.root_account_id,
+                atomic: None,
+                account_creation_retry_window: None,
+                claimer_rules: None,
+            }),
+            assets_metadata: old.assets_metadata,
+            required_asset_gas: old.required_asset_gas,
+        }
+    }
+}
+
+impl From<OldInternalKeyInfo> for InternalKeyInfo {
+    fn from(old: OldInternalKeyInfo) -> Self {
+        Self {
+            message_nonce: old.message_nonce,
+            pub_key: old.pub_key,
+            remaining_uses: old.remaining_uses,
+            owner_id: old.owner_id,
+            last_claimed: old.last_claimed,
+            metadata: old.metadata,
+            pw_by_use: old.pw_by_use,
+            approved_account_ids: old.approved_account_ids,
+            next_approval_id: old.next_approval_id,
+            claim_window: Default::default(),
+            tokens_per_use_overrides: vec![],
+            retryable_use: None,
+            reserved_use: None,
+        }
+    }
+}
+
+impl From<OldDropConfig> for DropConfig {
+    fn from(old: OldDropConfig) -> Self {
+        Self {
+            metadata: old.metadata,
+            nft_keys_config: old.nft_keys_config,
+            add_key_allowlist: old.add_key_allowlist,
+            transfer_key_allowlist: old.transfer_key_allowlist,
+            delete_empty_drop: old.delete_empty_drop,
+            rate_limit: None,
+            deposit_allowlist: None,
+            record_claims: None,
+            account_claim_limit: None,
+        }
+    }
+}
+
+impl OldInternalDrop {
+    /// Rewrite the drop and all of its keys and assets in the current layout. They keep the same storage prefixes
+    fn into_current(self, drop_id: &DropId, nonce: u64) -> InternalDrop {
+        let mut old_asset_by_id = self.asset_by_id;
+        let old_assets: Vec<(AssetId, OldInternalAsset)> = old_asset_by_id.iter().collect();
+        old_asset_by_id.clear();
+
+        let mut asset_by_id: UnorderedMap<AssetId, InternalAsset> =
+            UnorderedMap::new(StorageKeys::AssetById {
+                drop_id_hash: hash_string(&drop_id.to_string()),
+            });
+        for (asset_id, asset) in old_assets {
+            asset_by_id.insert(&asset_id, &asset.into());
+        }
+
+        let asset_data_for_uses: Vec<InternalAssetDataForUses> = self
+            .asset_data_for_uses
+            .into_iter()
+            .map(|asset_data| asset_data.into())
+            .collect();
+
+        let mut old_key_info_by_token_id = self.key_info_by_token_id;
+        let old_keys: Vec<(TokenId, OldInternalKeyInfo)> = old_key_info_by_token_id.iter().collect();
+        old_key_info_by_token_id.clear();
+
+        let mut key_info_by_token_id: UnorderedMap<TokenId, InternalKeyInfo> =
+            UnorderedMap::new(StorageKeys::KeyInfoByPk {
+                drop_id_hash: hash_string(&drop_id.to_string()),
+            });
+        // Old drops didn't track whether they were claimed. Any key that was used or is missing (claimed or deleted) counts
+        let has_claims = (old_keys.len() as u64) < self.next_key_id
+            || old_keys.iter().any(|(_, key)| key.last_claimed > 0);
+        // Old drops didn't keep a running count of the tokens their keys need so it's tallied up from every key here
+        let mut tokens_needed = HashMap::new();
+        for (token_id, key_info) in old_keys {
+            get_tokens_needed_for_key(
+                &mut tokens_needed,
+                key_info.remaining_uses,
+                &asset_by_id,
+                &asset_data_for_uses,
+                &[],
+            );
+            key_info_by_token_id.insert(&token_id, &key_info.into());
+        }
+        let mut tokens_needed_by_asset = LookupMap::new(StorageKeys::TokensNeededByAsset {
+            drop_id_hash: hash_string(&drop_id.to_string()),
+        });
+        for (asset_id, tokens) in tokens_needed {
+            tokens_needed_by_asset.insert(&asset_id, &tokens);
+        }
+
+        InternalDrop {
+            funder_id: self.funder_id,
+            max_key_uses: self.max_key_uses,
+            asset_by_id,
+            asset_data_for_uses,
+            key_info_by_token_id,
+            tokens_needed_by_asset,
+            next_key_id: self.next_key_id,
+            nonce,
+            config: self.config.map(|config| config.into()),
+            claim_window: Default::default(),
+            has_claims,
+            paused: false,
+        }
+    }
+}
+
+#[near_bindgen]
+impl Keypom {
+    /// Upgrade the contract state from the previous version. `chain_id` is the network that key
+    /// signatures will be bound to (i.e `mainnet` or `testnet`). Since the old signing keys and admins can't be
+    /// iterated over, every one of them must be passed in as `signing_pks` and `signing_admins`. Existing signing keys
+    /// keep calling every method with an unlimited allowance.
+    ///
+    /// Existing drops are left in their old layout and can't be used until the owner moves them over with `migrate_drops`.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate(
+        chain_id: String,
+        signing_pks: Vec<PublicKey>,
+        signing_admins: Vec<AccountId>,
+    ) -> Self {
+        let mut old_state: OldKeypom = env::state_read().expect("No contract state to migrate");
+
+        let signing_key_info = SigningKeyInfo::new(None);
+        let mut signing_pks_map = UnorderedMap::new(StorageKeys::SigningPks);
+        for pk in signing_pks {
+            require!(
+                old_state.signing_pks.remove(&pk),
+                format!("Signing key {:?} not found", pk)
+            );
+            signing_pks_map.insert(&pk, &signing_key_info);
+        }
+
+        let mut signing_admins_set = UnorderedSet::new(StorageKeys::SigningAdmins);
+        for admin in signing_admins {
+            require!(
+                old_state.signing_admins.remove(&admin),
+                format!("{} is not a signing admin", admin)
+            );
+            signing_admins_set.insert(&admin);
+        }
+
+        Self {
+            contract_owner_id: old_state.contract_owner_id,
+            pending_owner_id: None,
+            pause_state: PauseState::all(old_state.global_freeze),
+            fee_structure: old_state.fee_structure,
+            fees_collected: old_state.fees_collected,
+            fees_per_user: old_state.fees_per_user,
+            signing_pks: signing_pks_map,
+            signing_admins: signing_admins_set,
+            global_rate_limit: None,
+            registered_wnear_contracts: UnorderedSet::new(StorageKeys::RegisteredWNEARContracts),
+            claim_receipts_by_drop: LookupMap::new(StorageKeys::ClaimReceiptsByDrop),
+            claim_receipts_by_account: LookupMap::new(StorageKeys::ClaimReceiptsByAccount),
+            claims_per_account: LookupMap::new(StorageKeys::ClaimsPerAccount),
+            next_claimer_list_id: 0,
+            drop_by_id: LookupMap::new(StorageKeys::MigratedDropById),
+            legacy_drop_by_id: Some(old_state.drop_by_id),
+            next_drop_nonce: 0,
+            drop_ids_by_funder: old_state.drop_ids_by_funder,
+            token_id_by_pk: old_state.token_id_by_pk,
+            root_account: old_state.root_account,
+            chain_id,
+            tokens_per_owner: old_state.tokens_per_owner,
+            funder_info_by_id: old_state.funder_info_by_id,
+        }
+    }
+
+    /// Move a set of drops (and all of their keys) from the layout before the last upgrade into the current one.
+    /// Drops with many keys may need to be migrated on their own to stay within the gas limit. Only callable by the contract owner
+    pub fn migrate_drops(&mut self, drop_ids: Vec<DropId>) {
+        self.assert_owner();
+        require!(self.legacy_drop_by_id.is_some(), "No drops left to migrate");
+
+        for drop_id in drop_ids {
+            let old_drop = self
+                .legacy_drop_by_id
+                .as_mut()
+                .and_then(|drops| drops.remove(&drop_id))
+                .expect("Drop not found or already migrated");
+            let nonce = self.internal_take_drop_nonce();
+            let drop = old_drop.into_current(&drop_id, nonce);
+            self.drop_by_id.insert(&drop_id, &drop);
+        }
+    }
+
+    /// Whether a drop is still stored in the layout from before the last upgrade
+    pub(crate) fn is_legacy_drop(&self, drop_id: &DropId) -> bool {
+        self.legacy_drop_by_id
+            .as_ref()
+            .map(|drops| drops.contains_key(drop_id))
+            .unwrap_or(false)
+    }
+}
@@ -12,7 +12,7 @@ pub enum ClaimPermissions {
 }
 
 /// Keep track of different configuration options for all the uses of a key in a given drop
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, Default)]
 #[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub struct UseConfig {
@@ -30,6 +30,60 @@ pub struct UseConfig {
     /// For example, Fayyr could specify a root of `fayyr.near` By which all sub-accounts will then
     /// be `ACCOUNT.fayyr.near`
     pub root_account_id: Option<AccountId>,
+
+    /// If set to true, the use is rolled back whenever any of its assets fail to be claimed. The key's use is restored
+    /// And only the assets that failed are claimed the next time the key is used. Defaults to false.
+    pub atomic: Option<bool>,
+
+    /// How long (in nanoseconds) the use stays reserved for the key if `create_account` fails, so that the holder can retry
+    /// `create_account_and_claim` with a different account name. If None, the use is lost and its assets are refunded to the funder.
+    pub account_creation_retry_window: Option<u64>,
+
+    /// Restrict which accounts can receive the assets for this use
+    pub claimer_rules: Option<ClaimerRules>,
+}
+
+/// Restrictions on which accounts can receive the assets for a use. Every rule that is set must pass.
+/// The allowlist and denylist are moved into their own storage once the config is set so they can hold any number
+/// Of accounts without being loaded for every claim. Lists that are later replaced through `update_drop_config` aren't freed.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct ClaimerRules {
+    /// Only these accounts can receive the assets
+    #[borsh(skip)]
+    #[serde(default, skip_serializing)]
+    pub allowlist: Option<HashSet<AccountId>>,
+    /// These accounts can never receive the assets
+    #[borsh(skip)]
+    #[serde(default, skip_serializing)]
+    pub denylist: Option<HashSet<AccountId>>,
+    /// The receiving account must be a sub-account ending with one of these suffixes. Every suffix must include
+    /// The leading period (i.e `.sweat`)
+    pub allowed_suffixes: Option<Vec<String>>,
+    /// External contract that is asked whether the receiving account is a member before the assets are released
+    pub membership_check: Option<MembershipCheck>,
+
+    /// Where the allowlist is stored. Set by the contract
+    #[serde(skip_deserializing)]
+    pub allowlist_id: Option<u64>,
+    /// Where the denylist is stored. Set by the contract
+    #[serde(skip_deserializing)]
+    pub denylist_id: Option<u64>,
+}
+
+/// View method on an external contract that must return `true` for the receiving account to get the assets.
+/// If the call fails or returns anything else, the use is restored on the key.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct MembershipCheck {
+    /// Contract to call
+    pub contract_id: AccountId,
+    /// Method to call on the contract. Defaults to `is_member`
+    pub method_name: Option<String>,
+    /// Which argument the receiving account ID should be passed in. Defaults to `account_id`
+    pub account_id_field: Option<String>,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
@@ -57,7 +111,7 @@ pub struct TimeConfig {
 }
 
 /// Optional configurations for the drop such as metadata, deleting empty drops etc.
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Default)]
 #[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub struct DropConfig {
@@ -80,4 +134,213 @@ pub struct DropConfig {
     /// Should the drop be automatically deleted when all the keys are used? This is defaulted to true and
     /// Must be overwritten
     pub delete_empty_drop: Option<bool>,
+
+    /// Limit how often keys in this drop can be claimed. If None, the contract's global rate limit is used (if any)
+    pub rate_limit: Option<RateLimitConfig>,
+
+    /// Which accounts, other than the funder, can deposit fungible tokens and NFTs into the drop via `ft_transfer_call`
+    /// And `nft_transfer_call`. If None, only the funder can deposit.
+    pub deposit_allowlist: Option<HashSet<AccountId>>,
+
+    /// Should a receipt be stored on-chain for every claim? The storage for each receipt is paid from the funder's balance
+    /// And if the balance can't cover it, the receipt is skipped. Defaults to false.
+    pub record_claims: Option<bool>,
+
+    /// Limit how many times a single account can receive claims from this drop (or any drop in the same group).
+    /// The storage used to count claims is charged to the funder.
+    pub account_claim_limit: Option<AccountClaimLimit>,
+}
+
+/// Changes to a drop's config passed into `update_drop_config`. Fields that are left out keep their current value
+/// While fields that are explicitly set to null are cleared.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DropConfigUpdate {
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub metadata: Option<Option<DropMetadata>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub nft_keys_config: Option<Option<NFTKeyConfigurations>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub add_key_allowlist: Option<Option<HashSet<AccountId>>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub transfer_key_allowlist: Option<Option<HashSet<AccountId>>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub delete_empty_drop: Option<Option<bool>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub rate_limit: Option<Option<RateLimitConfig>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub deposit_allowlist: Option<Option<HashSet<AccountId>>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub record_claims: Option<Option<bool>>,
+    /// Can only be changed before any key in the drop has been claimed
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub account_claim_limit: Option<Option<AccountClaimLimit>>,
+}
+
+/// Changes to a use's config passed into `update_drop_config`. Fields that are left out keep their current value
+/// While fields that are explicitly set to null are cleared. The time config is always replaced as a whole.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UseConfigUpdate {
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub time: Option<Option<TimeConfig>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub permissions: Option<Option<ClaimPermissions>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub account_creation_keypom_args: Option<Option<KeypomInjectedArgs>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub root_account_id: Option<Option<AccountId>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub atomic: Option<Option<bool>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub account_creation_retry_window: Option<Option<u64>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_config_update"
+    )]
+    pub claimer_rules: Option<Option<ClaimerRules>>,
+}
+
+/// A field that is present in a config update is always `Some`, even if its value is null. Missing fields
+/// Fall back to `None` through `#[serde(default)]`.
+fn deserialize_config_update<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: near_sdk::serde::Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// The different sets of entry points that can be paused independently by the contract owner
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug)]
+pub enum PausableAction {
+    /// Creating drops and adding keys
+    drop_creation,
+    /// `claim` and `create_account_and_claim`
+    claims,
+    /// Transferring and approving NFT keys
+    key_transfers,
+    /// Depositing FTs / NFTs into drops and adding to funder balances
+    deposits,
+    /// Withdrawing assets from drops, deleting keys and withdrawing funder balances
+    withdrawals,
+}
+
+/// Which methods a signing key is allowed to call on the contract
+#[allow(non_camel_case_types)]
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum SigningKeyScope {
+    /// Only `claim` and `create_account_and_claim`
+    claim,
+    /// Only NFT key transfers and approvals
+    transfer,
+    /// Every method a signing key can call
+    all,
+}
+
+/// Configurations for signing keys being added to the contract
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SigningKeyConfig {
+    /// Which methods the key can call. Defaults to all methods
+    pub scope: Option<SigningKeyScope>,
+    /// Block timestamp after which the key can no longer be used. If None, the key never expires.
+    /// Measured in number of non-leap-nanoseconds since January 1, 1970 0:00:00 UTC.
+    pub expires_at: Option<u64>,
+    /// How much $NEAR (in yocto) the access key can spend on gas. If None, the allowance is unlimited
+    pub allowance: Option<U128>,
+}
+
+/// Caps on how many claims can go through within a window of time. Since all claims are submitted by
+/// The relayer, this guards against a compromised or misbehaving relayer burning through every key at once.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct RateLimitConfig {
+    /// Length of each window. If None, every block is its own window.
+    /// Measured in number of non-leap-nanoseconds since January 1, 1970 0:00:00 UTC.
+    pub window: Option<u64>,
+
+    /// Maximum number of times a single key can be claimed within a window. If None, there is no per-key cap
+    pub max_claims_per_key: Option<u32>,
+
+    /// Maximum number of claims across all keys in a drop within a window. If None, there is no per-drop cap
+    pub max_claims_per_drop: Option<u32>,
+}
+
+/// Caps on how many times a single receiving account can claim. This guards public giveaways
+/// Against one person holding many keys and claiming all of them to the same account.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountClaimLimit {
+    /// Maximum number of times a single account can claim
+    pub max_claims_per_account: u32,
+
+    /// If set, claims are counted across every drop from the same funder with the same group instead of just this drop
+    pub group: Option<String>,
 }
@@ -1,5 +1,36 @@
 use crate::*;
 
+impl ExtDepositMsg {
+    /// Parse the `msg` passed to `ft_on_transfer` or `nft_on_transfer`. If it isn't a valid JSON
+    /// Message, the entire string is treated as the drop ID.
+    pub fn parse(msg: String) -> Self {
+        let deposit_msg =
+            near_sdk::serde_json::from_str::<ExtDepositMsg>(&msg).unwrap_or(ExtDepositMsg {
+                drop_id: msg,
+                key_id: None,
+                use_number: None,
+                refund_overflow: None,
+            });
+
+        require!(
+            deposit_msg.use_number.is_none() || deposit_msg.key_id.is_some(),
+            "A key ID must be specified when targeting a use"
+        );
+        deposit_msg
+    }
+
+    /// Parse the `msg` passed to `ft_on_transfer` or `mt_on_transfer`. Their balances are shared by every key in the drop
+    /// So a deposit that targets a specific key or use is rejected and the tokens are returned by the token contract.
+    pub fn parse_fungible(msg: String) -> Self {
+        let deposit_msg = Self::parse(msg);
+        require!(
+            deposit_msg.key_id.is_none() && deposit_msg.use_number.is_none(),
+            "Fungible deposits are shared by every key in the drop and can't target a specific key or use"
+        );
+        deposit_msg
+    }
+}
+
 impl ExtAsset {
     /// Convert an `ExtAsset` into an `InternalAsset`
     pub fn to_internal_asset(&self) -> InternalAsset {
@@ -7,10 +38,20 @@ impl ExtAsset {
             ExtAsset::FTAsset(ft_data) => InternalAsset::ft(InternalFTData::new(
                 ft_data.ft_contract_id.clone(),
                 ft_data.registration_cost.into(),
+                ft_data.ft_registration_policy.unwrap_or_default(),
+            )),
+            ExtAsset::NFTAsset(nft_data) => InternalAsset::nft(InternalNFTData::new(
+                nft_data.nft_contract_id.clone(),
+                nft_data.nft_distribution.unwrap_or_default(),
+            )),
+            ExtAsset::MTAsset(mt_data) => InternalAsset::mt(InternalMTData::new(
+                mt_data.mt_contract_id.clone(),
+                mt_data.mt_token_id.clone(),
+            )),
+            ExtAsset::WNEARAsset(wnear_data) => InternalAsset::wnear(InternalWNEARData::new(
+                wnear_data.wnear_contract_id.clone(),
+                wnear_data.registration_cost.into(),
             )),
-            ExtAsset::NFTAsset(nft_data) => {
-                InternalAsset::nft(InternalNFTData::new(nft_data.nft_contract_id.clone()))
-            }
             ExtAsset::FCAsset(method_data) => InternalAsset::fc(FCData::new(method_data.clone())),
             ExtAsset::NearAsset(_) => InternalAsset::near,
         }
@@ -22,6 +63,8 @@ impl ExtAsset {
             ExtAsset::FTAsset(ft_data) => ft_data.ft_amount,
             ExtAsset::NFTAsset(_) => U128(0),
             ExtAsset::NearAsset(near_data) => near_data.yoctonear,
+            ExtAsset::MTAsset(mt_data) => mt_data.mt_amount,
+            ExtAsset::WNEARAsset(wnear_data) => wnear_data.wrapped_yoctonear,
             ExtAsset::FCAsset(_) => U128(0),
         }
     }
@@ -32,6 +75,10 @@ impl ExtAsset {
             ExtAsset::FTAsset(ft_data) => ft_data.ft_contract_id.to_string(),
             ExtAsset::NFTAsset(nft_data) => nft_data.nft_contract_id.to_string(),
             ExtAsset::NearAsset(_) => NEAR_ASSET_ID.to_string(),
+            ExtAsset::MTAsset(mt_data) => {
+                get_mt_asset_id(&mt_data.mt_contract_id, &mt_data.mt_token_id)
+            }
+            ExtAsset::WNEARAsset(wnear_data) => get_wnear_asset_id(&wnear_data.wnear_contract_id),
             ExtAsset::FCAsset(_) => {
                 near_sdk::log!("FC Asset does not have an asset ID. THIS SHOULD NEVER HAPPEN");
                 FALLBACK_FC_ASSET_ID.to_string()
@@ -38,6 +38,8 @@ pub enum ExtAsset {
     FTAsset(ExtFTData),
     NearAsset(ExtNEARData),
     NFTAsset(ExtNFTData),
+    MTAsset(ExtMTData),
+    WNEARAsset(ExtWNEARData),
     FCAsset(Vec<MethodData>),
 }
 
@@ -61,6 +63,8 @@ pub struct ExtFTData {
     pub registration_cost: U128,
     /// How many fungible tokens (in their smallest indivisible unit) should be transferred as part of the asset claim
     pub ft_amount: U128,
+    /// Whether claimers should be registered on the FT contract and who pays for it. Defaults to registering only when needed
+    pub ft_registration_policy: Option<FTRegistrationPolicy>,
 }
 
 /// Data going into or out of the Keypom contract representing the presence of non-fungible tokens as an asset for a drop
@@ -71,6 +75,35 @@ pub struct ExtFTData {
 pub struct ExtNFTData {
     /// The contract that the non fungible tokens originate from
     pub nft_contract_id: AccountId,
+    /// In what order the NFTs should be handed out to claimers. Defaults to the most recently deposited NFT first
+    pub nft_distribution: Option<NFTDistribution>,
+}
+
+/// Data going into or out of the Keypom contract representing the presence of NEP-245 multi tokens as an asset for a drop
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExtMTData {
+    /// The contract that the multi tokens originate from
+    pub mt_contract_id: AccountId,
+    /// Which token on the multi token contract should be transferred
+    pub mt_token_id: String,
+    /// How many tokens should be transferred as part of the asset claim
+    pub mt_amount: U128,
+}
+
+/// Data going into or out of the Keypom contract representing $NEAR that is automatically wrapped and sent to the claimer as wNEAR
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExtWNEARData {
+    /// The wrapped NEAR contract (i.e `wrap.near`)
+    pub wnear_contract_id: AccountId,
+    /// How much $NEAR should be wrapped and transferred as part of the asset claim
+    pub wrapped_yoctonear: U128,
+    /// How much $NEAR (in yocto) it costs to register a new user on the wrapped NEAR contract. The funder isn't charged
+    /// For claimers that are already registered. If the Keypom contract isn't registered on the wrapped NEAR contract yet, this is also charged once when creating the drop.
+    pub registration_cost: U128,
 }
 
 /// Drop data being returned from view calls from Keypom
@@ -90,12 +123,86 @@ pub struct ExtDrop {
 
     pub nft_asset_data: Vec<InternalNFTData>,
     pub ft_asset_data: Vec<InternalFTData>,
+    pub mt_asset_data: Vec<InternalMTData>,
 
     /// Keep track of different configuration options for all the uses of a key in a given drop
     pub drop_config: Option<DropConfig>,
 
     /// Keep track of the next nonce to give out to a key
     pub next_key_id: u64,
+
+    /// Whether the funder has paused claims and key transfers for the drop
+    pub paused: bool,
+}
+
+/// Gas required to claim a specific use being returned from view calls from Keypom
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExtRequiredGas {
+    /// How much gas must be attached to `claim`. None if the use can't be claimed via `claim`
+    pub claim: Option<String>,
+    /// How much gas must be attached to `create_account_and_claim`. None if the use can't be claimed via `create_account_and_claim`
+    pub create_account_and_claim: Option<String>,
+}
+
+/// Signing key data being returned from view calls from Keypom
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExtSigningKey {
+    /// The public key that can sign transactions for the contract
+    pub public_key: PublicKey,
+    /// Which methods the key can call
+    pub scope: SigningKeyScope,
+    /// Block timestamp after which the key can no longer be used. If None, the key never expires.
+    pub expires_at: Option<u64>,
+    /// How much $NEAR (in yocto) the access key was given to spend on gas. If None, the allowance is unlimited
+    pub allowance: Option<U128>,
+}
+
+/// Rate limit status for a key and its drop being returned from view calls from Keypom
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExtRateLimitInfo {
+    /// The rate limit that applies to the key (either the drop's or the global one)
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Start of the current window. This is the block height if the window is per block, otherwise the block timestamp
+    pub window_start: U64,
+    /// How many times the key has been claimed in the current window
+    pub key_claims_in_window: u32,
+    /// How many claims have gone through across the whole drop in the current window
+    pub drop_claims_in_window: u32,
+}
+
+/// Claim receipt being returned from view calls from Keypom
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExtClaimReceipt {
+    /// Which drop was claimed
+    pub drop_id: DropId,
+    /// Account that received the assets
+    pub account_id: AccountId,
+    /// Which use of the key was claimed
+    pub use_number: UseNumber,
+    /// Block timestamp of the claim
+    pub claimed_at: u64,
+    /// Index of every asset in the use that failed to be claimed. Empty if everything was claimed successfully
+    pub failed_asset_indices: Vec<u32>,
+}
+
+/// Message passed to `ft_on_transfer` and `nft_on_transfer` when depositing assets into a drop.
+/// For backwards compatibility, a bare drop ID is also accepted as the message.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExtDepositMsg {
+    /// Which drop the assets should be deposited into
+    pub drop_id: DropId,
+    /// Which key the deposit is meant for. NFTs will be pinned to this key. Only applicable to NFTs since
+    /// Fungible and multi token balances are shared by every key in the drop (deposits that set this are rejected).
+    pub key_id: Option<u64>,
+    /// Which use of the key the deposit is meant for. Requires `key_id` to be set.
+    pub use_number: Option<UseNumber>,
+    /// Should fungible or multi tokens beyond what is still needed by the keys in the drop be returned to the sender? Defaults to true.
+    pub refund_overflow: Option<bool>,
 }
 
 /// Data for each key coming in (public key, password, metadata, owner etc.)
@@ -113,4 +220,19 @@ pub struct ExtKeyData {
     pub metadata: Option<String>,
     /// What account ID owns the given key (if any)
     pub key_owner: Option<AccountId>,
+    /// Per-use overrides for how many tokens this key should receive for a given asset (i.e referral bonuses or prize tiers).
+    /// Maps a use number to the asset ID and the amount that should be transferred instead of the drop's `tokens_per_use`.
+    /// Only applicable to assets that transfer an amount (FT, MT, wNEAR and $NEAR).
+    pub tokens_per_use_overrides: Option<HashMap<UseNumber, HashMap<AssetId, U128>>>,
+}
+
+/// Signature by a linkdrop key over the `SignedKeyPayload` for a call, along with the expiry that was signed
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct KeySignature {
+    /// Signature over the hash of the payload
+    pub signature: Base64VecU8,
+    /// Block timestamp after which the signature is no longer valid
+    /// Measured in number of non-leap-nanoseconds since January 1, 1970 0:00:00 UTC.
+    pub valid_until: U64,
 }
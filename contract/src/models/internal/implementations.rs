@@ -5,11 +5,13 @@ impl InternalDrop {
     pub fn to_external_drop(&self, drop_id: DropId) -> ExtDrop {
         let mut nft_list = vec![];
         let mut ft_list = vec![];
+        let mut mt_list = vec![];
 
         // Loop through all the values in the asset_by_id hashmap and add them to the corresponding vectors
         self.asset_by_id.values().for_each(|asset| match asset {
             InternalAsset::nft(nft_asset) => nft_list.push(nft_asset.clone()),
             InternalAsset::ft(ft_asset) => ft_list.push(ft_asset.clone()),
+            InternalAsset::mt(mt_asset) => mt_list.push(mt_asset.clone()),
             _ => {}
         });
 
@@ -37,10 +39,60 @@ impl InternalDrop {
             asset_data,
             nft_asset_data: nft_list,
             ft_asset_data: ft_list,
+            mt_asset_data: mt_list,
             drop_config: self.config.clone(),
             funder_id: self.funder_id.clone(),
             max_key_uses: self.max_key_uses,
             next_key_id: self.next_key_id,
+            paused: self.paused,
+        }
+    }
+
+    /// Ensure that the account sending assets via `ft_transfer_call` or `nft_transfer_call` is allowed to deposit into the drop
+    pub fn assert_can_deposit(&self, sender_id: &AccountId) {
+        let on_allowlist = self
+            .config
+            .as_ref()
+            .and_then(|c| c.deposit_allowlist.as_ref())
+            .map(|allowlist| allowlist.contains(sender_id))
+            .unwrap_or(false);
+        require!(
+            self.funder_id == *sender_id || on_allowlist,
+            "Only the funder or accounts on the deposit allowlist can add assets to the drop"
+        );
+    }
+
+    /// Query how many tokens of a given asset are still needed to cover every remaining claim across all the keys in the drop.
+    /// For NFTs, this is the number of token IDs.
+    pub fn get_tokens_needed_for_asset(&self, asset_id: &AssetId) -> Balance {
+        self.tokens_needed_by_asset.get(asset_id).unwrap_or(0)
+    }
+
+    /// Add or remove tokens from the running totals of what the keys in the drop still need. This should only ever be passed
+    /// What changed (i.e the single use that was consumed) so that the totals never have to be recomputed from every key.
+    pub fn modify_tokens_needed(
+        &mut self,
+        tokens_needed: HashMap<AssetId, Balance>,
+        decrement: bool,
+    ) {
+        for (asset_id, tokens) in tokens_needed {
+            if tokens == 0 {
+                continue;
+            }
+
+            let cur_tokens_needed = self.get_tokens_needed_for_asset(&asset_id);
+            let new_tokens_needed = if decrement {
+                cur_tokens_needed.checked_sub(tokens).unwrap_or_else(|| {
+                    env::panic_str(&format!(
+                        "Tokens needed for asset {} drifted below 0 ({} - {})",
+                        asset_id, cur_tokens_needed, tokens
+                    ))
+                })
+            } else {
+                cur_tokens_needed + tokens
+            };
+            self.tokens_needed_by_asset
+                .insert(&asset_id, &new_tokens_needed);
         }
     }
 }
@@ -55,11 +107,25 @@ impl InternalAsset {
             InternalAsset::ft(ft_data) => Some(ExtAssetForEvents::FTAsset(ExtFTData {
                 ft_contract_id: ft_data.contract_id.clone(),
                 registration_cost: ft_data.registration_cost.into(),
+                ft_registration_policy: Some(ft_data.registration_policy),
                 // FTs should ALWAYS have a tokens_per_use value
                 ft_amount: tokens_per_use.unwrap(),
             })),
             InternalAsset::nft(nft_data) => Some(ExtAssetForEvents::NFTAsset(ExtNFTData {
                 nft_contract_id: nft_data.contract_id.clone(),
+                nft_distribution: Some(nft_data.distribution),
+            })),
+            InternalAsset::mt(mt_data) => Some(ExtAssetForEvents::MTAsset(ExtMTData {
+                mt_contract_id: mt_data.contract_id.clone(),
+                mt_token_id: mt_data.token_id.clone(),
+                // MTs should ALWAYS have a tokens_per_use value
+                mt_amount: tokens_per_use.unwrap(),
+            })),
+            InternalAsset::wnear(wnear_data) => Some(ExtAssetForEvents::WNEARAsset(ExtWNEARData {
+                wnear_contract_id: wnear_data.contract_id.clone(),
+                // wNEAR should ALWAYS have a tokens_per_use value
+                wrapped_yoctonear: tokens_per_use.unwrap(),
+                registration_cost: wnear_data.registration_cost.into(),
             })),
             InternalAsset::fc(fc_data) => Some(ExtAssetForEvents::FCAsset(
                 fc_data
@@ -86,11 +152,25 @@ impl InternalAsset {
             InternalAsset::ft(ft_data) => Some(ExtAsset::FTAsset(ExtFTData {
                 ft_contract_id: ft_data.contract_id.clone(),
                 registration_cost: ft_data.registration_cost.into(),
+                ft_registration_policy: Some(ft_data.registration_policy),
                 // FTs should ALWAYS have a tokens_per_use value
                 ft_amount: tokens_per_use.unwrap(),
             })),
             InternalAsset::nft(nft_data) => Some(ExtAsset::NFTAsset(ExtNFTData {
                 nft_contract_id: nft_data.contract_id.clone(),
+                nft_distribution: Some(nft_data.distribution),
+            })),
+            InternalAsset::mt(mt_data) => Some(ExtAsset::MTAsset(ExtMTData {
+                mt_contract_id: mt_data.contract_id.clone(),
+                mt_token_id: mt_data.token_id.clone(),
+                // MTs should ALWAYS have a tokens_per_use value
+                mt_amount: tokens_per_use.unwrap(),
+            })),
+            InternalAsset::wnear(wnear_data) => Some(ExtAsset::WNEARAsset(ExtWNEARData {
+                wnear_contract_id: wnear_data.contract_id.clone(),
+                // wNEAR should ALWAYS have a tokens_per_use value
+                wrapped_yoctonear: tokens_per_use.unwrap(),
+                registration_cost: wnear_data.registration_cost.into(),
             })),
             InternalAsset::fc(fc_data) => Some(ExtAsset::FCAsset(fc_data.methods.clone())),
             InternalAsset::near => Some(ExtAsset::NearAsset(ExtNEARData {
@@ -107,18 +187,37 @@ impl InternalAsset {
         receiver_id: &AccountId,
         tokens_per_use: &Option<Balance>,
         fc_args: AssetSpecificFCArgs,
-        drop_id: DropId,
-        key_id: String,
-        funder_id: AccountId,
+        claim_context: &ClaimContext,
     ) -> Option<Promise> {
+        let ClaimContext {
+            drop_id,
+            key_id,
+            use_number,
+            funder_id,
+        } = claim_context;
+
         match self {
             InternalAsset::ft(ref mut ft_data) => {
-                ft_data.claim_ft_asset(receiver_id, &tokens_per_use.unwrap())
+                ft_data.claim_ft_asset(receiver_id, &tokens_per_use.unwrap(), funder_id)
             }
-            InternalAsset::nft(ref mut nft_data) => nft_data.claim_nft_asset(receiver_id),
-            InternalAsset::fc(ref mut fc_data) => {
-                fc_data.claim_fc_asset(fc_args, receiver_id.clone(), drop_id, key_id, funder_id)
+            InternalAsset::nft(ref mut nft_data) => {
+                nft_data.claim_nft_asset(receiver_id, *key_id, *use_number)
             }
+            InternalAsset::mt(ref mut mt_data) => {
+                mt_data.claim_mt_asset(receiver_id, &tokens_per_use.unwrap())
+            }
+            InternalAsset::wnear(ref wnear_data) => Some(wnear_data.claim_wnear_asset(
+                receiver_id,
+                &tokens_per_use.unwrap(),
+                funder_id,
+            )),
+            InternalAsset::fc(ref mut fc_data) => fc_data.claim_fc_asset(
+                fc_args,
+                receiver_id.clone(),
+                drop_id.clone(),
+                key_id.to_string(),
+                funder_id.clone(),
+            ),
             InternalAsset::near => Some(
                 Promise::new(receiver_id.clone())
                     .transfer(NearToken::from_yoctonear(tokens_per_use.unwrap())),
@@ -130,20 +229,47 @@ impl InternalAsset {
     /// Standard function outlining what should happen if a specific claim failed
     /// This should return the amount of $NEAR that should be refunded to the user's balance
     /// In addition, any internal state changes should be made (i.e balance_available incremented for FTs)
-    pub fn on_failed_claim(&mut self, tokens_per_use: &Option<String>) -> Balance {
+    /// For NFTs, `nft_pin` should be set if the token was pinned to the key so that the pin is restored
+    pub fn on_failed_claim(
+        &mut self,
+        tokens_per_use: &Option<String>,
+        nft_pin: Option<PinnedNFT>,
+    ) -> Balance {
         match self {
             InternalAsset::ft(ref mut ft_data) => {
                 let ft_to_refund = &tokens_per_use.as_ref().unwrap().parse::<u128>().unwrap();
                 near_sdk::log!("Failed claim for FT asset. Refunding {} to the user's balance and incrementing balance available by {}", 0, ft_to_refund);
                 ft_data.add_to_balance_avail(ft_to_refund);
-                ft_data.registration_cost
+                ft_data.get_charged_registration_cost()
             }
             InternalAsset::nft(ref mut nft_data) => {
                 let token_id = &tokens_per_use.as_ref().unwrap();
                 near_sdk::log!("Failed claim NFT asset with Token ID {}", token_id);
-                nft_data.add_to_token_ids(token_id);
+                if let Some(pin) = nft_pin {
+                    nft_data.pinned_tokens.push(pin);
+                } else {
+                    nft_data.add_to_token_ids(token_id);
+                }
+                0
+            }
+            InternalAsset::mt(ref mut mt_data) => {
+                let mt_to_refund = &tokens_per_use.as_ref().unwrap().parse::<u128>().unwrap();
+                near_sdk::log!(
+                    "Failed claim for MT asset. Incrementing balance available by {}",
+                    mt_to_refund
+                );
+                mt_data.add_to_balance_avail(mt_to_refund);
                 0
             }
+            InternalAsset::wnear(ref wnear_data) => {
+                let near_tokens = tokens_per_use.as_ref().unwrap().parse::<u128>().unwrap();
+                let yocto_to_refund = wnear_data.get_yocto_cost(near_tokens);
+                near_sdk::log!(
+                    "Failed claim for wNEAR asset. Refunding {} to the user's balance",
+                    yocto_to_refund
+                );
+                yocto_to_refund
+            }
             InternalAsset::near => {
                 let near_tokens = tokens_per_use.as_ref().unwrap().parse::<u128>().unwrap();
                 near_sdk::log!(
@@ -168,19 +294,36 @@ impl InternalAsset {
         match self {
             InternalAsset::ft(ft) => !ft.enough_balance(&1),
             InternalAsset::nft(nft) => nft.is_empty(),
+            InternalAsset::mt(mt) => !mt.enough_balance(&1),
+            InternalAsset::wnear(_) => true,
             InternalAsset::fc(_) => true,
             InternalAsset::near => true,
             InternalAsset::none => true,
         }
     }
 
+    /// Query how many tokens of a deposited asset (FTs, NFTs and MTs) are transferred for 1 key use.
+    /// Every NFT claim transfers exactly 1 token. Assets that are paid for in $NEAR when keys are added return None
+    pub fn get_deposited_tokens_per_use(
+        &self,
+        tokens_per_use: &Option<Balance>,
+    ) -> Option<Balance> {
+        match self {
+            InternalAsset::ft(_) | InternalAsset::mt(_) => Some(tokens_per_use.unwrap_or(0)),
+            InternalAsset::nft(_) => Some(1),
+            _ => None,
+        }
+    }
+
     /// Standard function for refunding assets
     /// This does not include any ext assets such as FTs or NFTs.
     /// This simply refunds the funder for the $NEAR cost associated with 1 key use for the given asset
     pub fn get_yocto_refund_amount(&self, tokens_per_use: &Option<Balance>) -> Balance {
         match self {
-            InternalAsset::ft(ft_data) => ft_data.registration_cost,
+            InternalAsset::ft(ft_data) => ft_data.get_charged_registration_cost(),
             InternalAsset::nft(_) => 0,
+            InternalAsset::mt(_) => 0,
+            InternalAsset::wnear(wnear_data) => wnear_data.get_yocto_cost(tokens_per_use.unwrap()),
             InternalAsset::near => tokens_per_use.unwrap(),
             InternalAsset::fc(fc_data) => fc_data.get_yocto_refund_amount(),
             InternalAsset::none => 0,
@@ -192,6 +335,8 @@ impl InternalAsset {
         match self {
             InternalAsset::ft(ft_data) => ft_data.get_total_required_gas(),
             InternalAsset::nft(nft_data) => nft_data.get_total_required_gas(),
+            InternalAsset::mt(mt_data) => mt_data.get_total_required_gas(),
+            InternalAsset::wnear(wnear_data) => wnear_data.get_total_required_gas(),
             InternalAsset::fc(fc_data) => fc_data.get_total_required_gas(),
             InternalAsset::near => GAS_FOR_NEAR_TRANSFER,
             InternalAsset::none => GAS_FOR_NONE_ASSET,
@@ -203,9 +348,330 @@ impl InternalAsset {
         match self {
             InternalAsset::ft(ft_data) => ft_data.get_required_asset_gas(),
             InternalAsset::nft(nft_data) => nft_data.get_required_asset_gas(),
+            InternalAsset::mt(mt_data) => mt_data.get_required_asset_gas(),
+            InternalAsset::wnear(wnear_data) => wnear_data.get_required_asset_gas(),
             InternalAsset::fc(fc_data) => fc_data.get_required_asset_gas(),
             InternalAsset::near => GAS_FOR_NEAR_TRANSFER,
             InternalAsset::none => GAS_FOR_NONE_ASSET,
         }
     }
 }
+
+impl InternalAssetDataForUses {
+    /// Gas required to release the assets for this set of uses, including any membership check beforehand
+    pub fn get_total_asset_gas(&self) -> Gas {
+        let gas_for_rules = self
+            .config
+            .as_ref()
+            .and_then(|c| c.claimer_rules.as_ref())
+            .map(|r| r.get_required_gas().as_gas())
+            .unwrap_or(0);
+
+        Gas::from_gas(self.required_asset_gas.as_gas() + gas_for_rules)
+    }
+
+    /// Total gas that must be attached to `claim` for this set of uses. None if the uses can't be claimed via `claim`
+    pub fn get_required_gas_for_claim(&self) -> Option<Gas> {
+        match self.config.as_ref().and_then(|c| c.permissions.as_ref()) {
+            Some(ClaimPermissions::create_account_and_claim) => None,
+            _ => Some(Gas::from_gas(
+                BASE_GAS_FOR_CLAIM.as_gas() + self.get_total_asset_gas().as_gas(),
+            )),
+        }
+    }
+
+    /// Total gas that must be attached to `create_account_and_claim` for this set of uses.
+    /// None if the uses can't be claimed via `create_account_and_claim`
+    pub fn get_required_gas_for_create_account_and_claim(&self) -> Option<Gas> {
+        match self.config.as_ref().and_then(|c| c.permissions.as_ref()) {
+            Some(ClaimPermissions::claim) => None,
+            _ => Some(Gas::from_gas(
+                BASE_GAS_FOR_CREATE_ACC_AND_CLAIM.as_gas() + self.get_total_asset_gas().as_gas(),
+            )),
+        }
+    }
+
+    /// Ensure that every method the uses can be claimed with fits within the maximum gas attachable to a transaction.
+    /// Otherwise the keys could never be claimed and the funder's deposit would be locked.
+    pub fn assert_within_gas_limit(&self) {
+        let required_gas = [
+            ("claim", self.get_required_gas_for_claim()),
+            (
+                "create_account_and_claim",
+                self.get_required_gas_for_create_account_and_claim(),
+            ),
+        ];
+
+        for (method_name, gas) in required_gas {
+            if let Some(gas) = gas {
+                require!(
+                    gas <= MAX_GAS_ATTACHABLE,
+                    format!(
+                        "Calling {} requires {} gas which exceeds the maximum 300 TGas limit",
+                        method_name,
+                        gas.as_gas()
+                    )
+                );
+            }
+        }
+    }
+}
+
+impl PauseState {
+    /// Returns a pause state where either everything or nothing is paused
+    pub fn all(paused: bool) -> Self {
+        Self {
+            drop_creation: paused,
+            claims: paused,
+            key_transfers: paused,
+            deposits: paused,
+            withdrawals: paused,
+        }
+    }
+
+    /// Whether the given set of entry points is currently paused
+    pub fn is_paused(&self, action: PausableAction) -> bool {
+        match action {
+            PausableAction::drop_creation => self.drop_creation,
+            PausableAction::claims => self.claims,
+            PausableAction::key_transfers => self.key_transfers,
+            PausableAction::deposits => self.deposits,
+            PausableAction::withdrawals => self.withdrawals,
+        }
+    }
+}
+
+impl SigningKeyScope {
+    /// Comma separated list of methods that the on-chain access key should be restricted to
+    pub fn method_names(&self) -> &'static str {
+        match self {
+            SigningKeyScope::claim => CLAIM_KEY_METHOD_NAMES,
+            SigningKeyScope::transfer => TRANSFER_KEY_METHOD_NAMES,
+            SigningKeyScope::all => GLOBAL_KEY_METHOD_NAMES,
+        }
+    }
+
+    /// Whether a key with this scope can authorize a call to `method_name`
+    pub fn allows_method(&self, method_name: &str) -> bool {
+        match self {
+            SigningKeyScope::all => true,
+            _ => self.method_names().split(',').any(|m| m == method_name),
+        }
+    }
+}
+
+impl SigningKeyInfo {
+    /// Validate the configurations passed in and turn them into the info stored for a signing key
+    pub fn new(config: Option<SigningKeyConfig>) -> Self {
+        let SigningKeyConfig {
+            scope,
+            expires_at,
+            allowance,
+        } = config.unwrap_or_default();
+
+        if let Some(expiry) = expires_at {
+            require!(
+                expiry > env::block_timestamp(),
+                "The signing key expiry must be greater than the current block timestamp"
+            );
+        }
+
+        Self {
+            scope: scope.unwrap_or(SigningKeyScope::all),
+            expires_at,
+            allowance,
+        }
+    }
+
+    /// Whether the key has passed its expiry
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .map(|expiry| env::block_timestamp() > expiry)
+            .unwrap_or(false)
+    }
+
+    /// Add an access key for `public_key` onto the promise, restricted to the methods in the key's scope
+    pub fn add_access_key(&self, promise: Promise, public_key: PublicKey) -> Promise {
+        let allowance = match self.allowance {
+            Some(amount) => Allowance::limited(NearToken::from_yoctonear(amount.0))
+                .expect("Signing key allowance must be greater than 0"),
+            None => Allowance::Unlimited,
+        };
+
+        promise.add_access_key_allowance(
+            public_key,
+            allowance,
+            env::current_account_id(),
+            self.scope.method_names().to_string(),
+        )
+    }
+}
+
+impl InternalKeyInfo {
+    /// Query how many tokens should be transferred for a given asset in a given use, taking any overrides for the key into account
+    pub fn get_tokens_per_use(
+        &self,
+        use_number: UseNumber,
+        asset_idx: usize,
+        metadata: &AssetMetadata,
+    ) -> Option<U128> {
+        self.tokens_per_use_overrides
+            .iter()
+            .find(|o| o.use_number == use_number && o.asset_idx as usize == asset_idx)
+            .map(|o| o.tokens_per_use)
+            .or(metadata.tokens_per_use)
+    }
+
+    /// Number of bytes used to keep track of a rolled back or reserved use
+    pub fn retry_state_len(&self) -> usize {
+        near_sdk::borsh::to_vec(&self.retryable_use).unwrap().len()
+            + near_sdk::borsh::to_vec(&self.reserved_use).unwrap().len()
+    }
+
+    /// Query the rolled back use if it's the key's current use. While the retry is being claimed, the use
+    /// Was already consumed so it shouldn't be taken into account anymore.
+    pub fn get_pending_retryable_use(&self, cur_key_use: UseNumber) -> Option<&RetryableUse> {
+        self.retryable_use
+            .as_ref()
+            .filter(|r| r.use_number == cur_key_use)
+    }
+
+    /// Whether an asset still needs to be claimed in a given use. Assets that were already claimed in a use that
+    /// Was rolled back are skipped on the next attempt.
+    pub fn is_asset_pending(&self, use_number: UseNumber, asset_idx: usize) -> bool {
+        self.get_pending_retryable_use(use_number)
+            .map(|r| r.asset_indices.contains(&(asset_idx as u32)))
+            .unwrap_or(true)
+    }
+
+    /// Query the $NEAR cost of the assets that were already claimed in the key's current use if it was rolled back.
+    /// Since these assets were sent out, they shouldn't be refunded if the key is deleted.
+    pub fn get_retryable_use_claimed_cost(
+        &self,
+        cur_key_use: UseNumber,
+        asset_by_id: &UnorderedMap<AssetId, InternalAsset>,
+        asset_data_for_uses: &[InternalAssetDataForUses],
+    ) -> Balance {
+        let Some(retryable_use) = self.get_pending_retryable_use(cur_key_use) else {
+            return 0;
+        };
+
+        get_asset_data_for_specific_use(asset_data_for_uses, &retryable_use.use_number)
+            .assets_metadata
+            .iter()
+            .enumerate()
+            .filter(|(asset_idx, _)| !retryable_use.asset_indices.contains(&(*asset_idx as u32)))
+            .map(|(asset_idx, metadata)| {
+                let tokens_per_use =
+                    self.get_tokens_per_use(retryable_use.use_number, asset_idx, metadata);
+                asset_by_id
+                    .get(&metadata.asset_id)
+                    .expect("Asset not found")
+                    .get_yocto_refund_amount(&tokens_per_use.map(|t| t.into()))
+            })
+            .sum()
+    }
+}
+
+impl ClaimerRules {
+    /// Make sure the receiving account passes the allowlist, denylist and suffix rules.
+    /// The membership check is done separately through a callback.
+    pub fn assert_claimer_allowed(&self, account_id: &AccountId) {
+        if let Some(list_id) = self.allowlist_id {
+            require!(
+                LookupSet::new(StorageKeys::ClaimerList { list_id }).contains(account_id),
+                format!(
+                    "Account {} is not on the allowlist for this use",
+                    account_id
+                )
+            );
+        }
+
+        if let Some(list_id) = self.denylist_id {
+            require!(
+                !LookupSet::new(StorageKeys::ClaimerList { list_id }).contains(account_id),
+                format!("Account {} is on the denylist for this use", account_id)
+            );
+        }
+
+        if let Some(suffixes) = self.allowed_suffixes.as_ref() {
+            require!(
+                suffixes
+                    .iter()
+                    .any(|s| account_id.as_str().ends_with(s.as_str())),
+                format!(
+                    "Account {} must end with one of {:?} for this use",
+                    account_id, suffixes
+                )
+            );
+        }
+    }
+
+    /// Extra gas needed to check membership before the assets are released
+    pub fn get_required_gas(&self) -> Gas {
+        match self.membership_check {
+            Some(_) => Gas::from_gas(
+                GAS_FOR_MEMBERSHIP_CHECK.as_gas()
+                    + MIN_BASE_GAS_FOR_RECEIPT_SPIN_UP.as_gas()
+                    + BASE_GAS_FOR_RESOLVE_MEMBERSHIP_CHECK.as_gas(),
+            ),
+            None => Gas::from_gas(0),
+        }
+    }
+}
+
+impl AccountClaimLimit {
+    /// Which claims should be counted towards the limit for a given drop
+    pub fn get_scope(&self, drop: &InternalDrop) -> AccountClaimScope {
+        match &self.group {
+            Some(group) => AccountClaimScope::group(drop.funder_id.clone(), group.clone()),
+            None => AccountClaimScope::drop(drop.nonce),
+        }
+    }
+}
+
+impl ClaimReceipt {
+    /// Convert the stored receipt into the data returned from view calls
+    pub fn to_external(&self, drop_id: &DropId) -> ExtClaimReceipt {
+        ExtClaimReceipt {
+            drop_id: drop_id.clone(),
+            account_id: self.account_id.clone(),
+            use_number: self.use_number,
+            claimed_at: self.claimed_at,
+            failed_asset_indices: self.failed_asset_indices.clone(),
+        }
+    }
+}
+
+impl ClaimWindow {
+    /// How many claims have been recorded in the window starting at `window_start`
+    pub fn claims_in_window(&self, window_start: u64) -> u32 {
+        if self.started_at == window_start {
+            self.claims
+        } else {
+            0
+        }
+    }
+
+    /// Record a new claim in the window starting at `window_start`, resetting the count if a new window has begun.
+    /// Returns false if doing so would exceed `max_claims`
+    pub fn try_record_claim(&mut self, window_start: u64, max_claims: Option<u32>) -> bool {
+        let claims = self.claims_in_window(window_start);
+        if max_claims.map(|max| claims >= max).unwrap_or(false) {
+            return false;
+        }
+
+        self.started_at = window_start;
+        self.claims = claims + 1;
+        true
+    }
+}
+
+impl SignedKeyPayload {
+    /// Hash of the tagged, borsh serialized payload. This is the message that the linkdrop key signs.
+    pub fn to_signable_hash(&self) -> Vec<u8> {
+        let mut bytes = near_sdk::borsh::to_vec(&KEYPOM_SIGNATURE_TAG).unwrap();
+        bytes.extend(near_sdk::borsh::to_vec(self).unwrap());
+        env::sha256(&bytes)
+    }
+}
@@ -16,11 +16,26 @@ pub struct InternalDrop {
 
     /// Set of public keys associated with this drop mapped to their specific key information.
     pub key_info_by_token_id: UnorderedMap<TokenId, InternalKeyInfo>,
+    /// How many tokens of every deposited asset (FTs, NFTs and MTs) the remaining uses of all the keys still need.
+    /// For NFTs, this is the number of token IDs. Used to refund deposits that the drop doesn't need.
+    pub tokens_needed_by_asset: LookupMap<AssetId, Balance>,
     /// Keep track of the next nonce to give out to a key
     pub next_key_id: u64,
+    /// Unique across every drop the contract has stored. Drop IDs can be reused once a drop is deleted so
+    /// Data that outlives the drop (i.e claim receipts) is keyed by this instead
+    pub nonce: u64,
 
     /// Keep track of different configuration options for all the uses of a key in a given drop
     pub config: Option<DropConfig>,
+
+    /// How many claims have gone through across all keys in the current rate limit window
+    pub claim_window: ClaimWindow,
+    /// Whether any key in the drop has been claimed. Settings that claims are counted against
+    /// (i.e the limit on claims per account) are locked once this is set
+    pub has_claims: bool,
+
+    /// Whether the funder has paused claims and key transfers for the drop
+    pub paused: bool,
 }
 
 /// Keep track of different configuration options for each key in a drop
@@ -50,6 +65,97 @@ pub struct InternalKeyInfo {
 
     /// The next approval ID to give out.
     pub next_approval_id: u64,
+
+    /// How many claims have gone through for this key in the current rate limit window
+    pub claim_window: ClaimWindow,
+
+    /// Amounts that should be transferred for specific assets in specific uses instead of the drop's `tokens_per_use`
+    pub tokens_per_use_overrides: Vec<TokensPerUseOverride>,
+
+    /// If an atomic use was rolled back after some of its assets were already claimed, which assets still need to be claimed
+    pub retryable_use: Option<RetryableUse>,
+
+    /// If `create_account` failed, the use that is reserved for the key to retry with a different account name
+    pub reserved_use: Option<ReservedUse>,
+}
+
+/// A use of a key that was rolled back after some (but not all) of its assets were claimed
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct RetryableUse {
+    /// Which use of the key was rolled back
+    pub use_number: UseNumber,
+    /// Index (in the use's `assets_metadata`) of every asset that failed and should be claimed on the next attempt
+    pub asset_indices: Vec<u32>,
+}
+
+/// A use of a key that is held after `create_account` failed so that it can only be retried with `create_account_and_claim`
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct ReservedUse {
+    /// Which use of the key is reserved
+    pub use_number: UseNumber,
+    /// Block timestamp after which the reservation expires and the use is forfeited
+    pub expires_at: u64,
+}
+
+/// Compact record of a claim that is stored on-chain for drops with `record_claims` enabled
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct ClaimReceipt {
+    /// Account that received the assets
+    pub account_id: AccountId,
+    /// Which use of the key was claimed
+    pub use_number: UseNumber,
+    /// Block timestamp of the claim
+    pub claimed_at: u64,
+    /// Index (in the use's `assets_metadata`) of every asset that failed to be claimed
+    pub failed_asset_indices: Vec<u32>,
+}
+
+/// What claims are counted towards an account's claim limit
+#[allow(non_camel_case_types)]
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum AccountClaimScope {
+    /// Only claims from a single drop. This is the drop's nonce so a new drop that reuses the ID starts from scratch
+    drop(u64),
+    /// Claims from any drop by the funder with the given group
+    group(AccountId, String),
+}
+
+/// Points to a receipt in a drop's list of claim receipts
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct ClaimReceiptId {
+    /// Which drop the receipt belongs to
+    pub drop_id: DropId,
+    /// Nonce of the drop at the time of the claim
+    pub drop_nonce: u64,
+    /// Index of the receipt in the drop's list
+    pub receipt_idx: u64,
+}
+
+/// Overrides the amount of tokens transferred for a single asset in a single use of a key
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct TokensPerUseOverride {
+    /// Which use of the key the override applies to
+    pub use_number: UseNumber,
+    /// Index of the asset in the use's `assets_metadata`
+    pub asset_idx: u16,
+    /// How many tokens should be transferred instead
+    pub tokens_per_use: U128,
+}
+
+/// Keeps track of the number of claims within a rate limit window
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct ClaimWindow {
+    /// Start of the window. This is the block height if the window is per block, otherwise the block timestamp
+    pub started_at: u64,
+    /// Number of claims recorded since the window started
+    pub claims: u32,
 }
 
 /// Outlines the asset data for a set of uses
@@ -119,6 +225,8 @@ pub enum InternalAsset {
     fc(FCData),
     near,
     none,
+    mt(InternalMTData),
+    wnear(InternalWNEARData),
 }
 
 /// Metadata corresponding to a specific asset. This keeps track of the ID and optionally tokens being transferred per use
@@ -128,7 +236,7 @@ pub struct AssetMetadata {
     /// What asset is mapped to this specific use
     pub asset_id: AssetId,
     /// How many tokens should be transferred in this use?
-    /// This is only relevant for FT and $NEAR assets
+    /// This is only relevant for FT, MT and $NEAR assets
     pub tokens_per_use: Option<U128>,
 }
 
@@ -166,6 +274,76 @@ pub struct KeypomFees {
     pub per_key: u128,
 }
 
+/// Payload that a linkdrop key signs to authorize `claim`, `create_account_and_claim` and NFT key transfers / approvals.
+/// Binding the chain, contract, method and an expiry (on top of the key's nonce) prevents a signature from being replayed
+/// against another network or deployment, a different method or after it has expired.
+#[derive(BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct SignedKeyPayload {
+    /// Network that the Keypom contract is deployed on (i.e `mainnet` or `testnet`)
+    pub chain_id: String,
+    /// Account ID of the Keypom contract that the signature is meant for
+    pub contract_id: AccountId,
+    /// Which method the signature authorizes
+    pub method_name: String,
+    /// Stringified JSON of the method arguments (excluding the signature itself)
+    pub args: String,
+    /// The key's current message nonce
+    pub nonce: u32,
+    /// Block timestamp after which the signature is no longer valid
+    /// Measured in number of non-leap-nanoseconds since January 1, 1970 0:00:00 UTC.
+    pub valid_until: u64,
+}
+
+/// Keeps track of which parts of the contract are currently paused
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Clone, Copy, Debug, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseState {
+    /// Drops can't be created and keys can't be added
+    pub drop_creation: bool,
+    /// Keys can't be claimed
+    pub claims: bool,
+    /// NFT keys can't be transferred or approved
+    pub key_transfers: bool,
+    /// FTs / NFTs can't be deposited into drops and funders can't add to their balance
+    pub deposits: bool,
+    /// Assets can't be withdrawn from drops, keys can't be deleted and funders can't withdraw their balance
+    pub withdrawals: bool,
+}
+
+/// Information stored for every key that is allowed to sign transactions for the contract
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct SigningKeyInfo {
+    /// Which methods the key can call
+    pub scope: SigningKeyScope,
+    /// Block timestamp after which the key can no longer be used. If None, the key never expires.
+    pub expires_at: Option<u64>,
+    /// How much $NEAR (in yocto) the access key was given to spend on gas. If None, the allowance is unlimited
+    pub allowance: Option<U128>,
+}
+
+/// Identifies the key use that assets are being claimed for
+pub struct ClaimContext {
+    pub drop_id: DropId,
+    pub key_id: u64,
+    /// Which use of the key is being claimed
+    pub use_number: UseNumber,
+    pub funder_id: AccountId,
+}
+
+/// How a key use whose claim failed should be rolled back
+pub struct UseRollback {
+    /// Index (in the use's `assets_metadata`) of every asset that should be claimed on the next attempt
+    pub asset_indices: Vec<u32>,
+    /// $NEAR the funder is charged on top of the retry storage (i.e to fire FC assets again)
+    pub extra_cost: Balance,
+    /// Reservation to hold the use under after a failed account creation
+    pub reserved_use: Option<ReservedUse>,
+}
+
 /// Data returned from the `before_claim_logic` function
 pub struct BeforeClaimData {
     pub drop_id: DropId,
@@ -196,4 +374,14 @@ pub enum StorageKeys {
     FunderInfoById,
     SigningPks,
     SigningAdmins,
+    MigratedDropById,
+    RegisteredWNEARContracts,
+    TokensNeededByAsset { drop_id_hash: CryptoHash },
+    ClaimReceiptsByDrop,
+    ClaimReceiptsByDropInner { drop_nonce: u64 },
+    ClaimReceiptsByAccount,
+    ClaimReceiptsByAccountInner { account_id_hash: CryptoHash },
+    ClaimsPerAccount,
+    ClaimsPerAccountInner { scope_hash: CryptoHash },
+    ClaimerList { list_id: u64 },
 }
@@ -23,6 +23,10 @@ pub struct ExtKeyInfo {
     /// existing) when the key is successfully used.
     pub nft_list: Vec<NFTListData>,
 
+    /// A set of NEP-245 multi tokens that will be sent to the account that claims the linkdrop
+    /// when the key is successfully used.
+    pub mt_list: Vec<MTListData>,
+
     /* CUSTOM */
     pub drop_id: DropId,
     pub pub_key: PublicKey,
@@ -63,3 +67,21 @@ pub struct NFTListData {
     /// The valid NEAR account indicating the Non-Fungible Token contract.
     pub contract_id: String,
 }
+
+/// Data outlining NEP-245 multi tokens that should be sent to the claiming account
+/// (either new or existing) when a key is successfully used.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct MTListData {
+    /// The number of tokens to transfer, wrapped in quotes and treated
+    /// like a string, although the number will be stored as an unsigned integer
+    /// with 128 bits.
+    pub amount: String,
+
+    /// The ID of the token on the multi token contract
+    pub token_id: String,
+
+    /// The valid NEAR account indicating the multi token contract.
+    pub contract_id: String,
+}
@@ -6,6 +6,8 @@ use crate::*;
 pub struct NftApproveMsg {
     pub linkdrop_pk: PublicKey,
     pub signature: Base64VecU8,
+    /// Block timestamp after which the signature is no longer valid
+    pub valid_until: U64,
     pub msg: Option<String>,
 }
 
@@ -14,13 +16,14 @@ impl Keypom {
     /// Allow a specific account ID to transfer a token on your behalf
     #[payable]
     pub fn nft_approve(&mut self, account_id: AccountId, msg: String) {
-        self.assert_no_global_freeze();
+        self.assert_not_paused(PausableAction::key_transfers);
         // Deserialize the msg string into the NftApproveMsg struct
         let nft_approve_msg: NftApproveMsg =
             serde_json::from_str(&msg).expect("Invalid message format");
         let NftApproveMsg {
             linkdrop_pk,
             signature,
+            valid_until,
             msg: msg_str,
         } = nft_approve_msg;
 
@@ -33,7 +36,15 @@ impl Keypom {
         }).to_string();
     
         require!(
-            self.verify_signature(signature, linkdrop_pk.clone(), args_string),
+            self.verify_signature(
+                KeySignature {
+                    signature,
+                    valid_until,
+                },
+                linkdrop_pk.clone(),
+                "nft_approve",
+                args_string
+            ),
             "Invalid signature for public key"
         );
 
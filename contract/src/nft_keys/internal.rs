@@ -69,6 +69,7 @@ impl Keypom {
 
         // Get drop in order to get key info (and royalties if applicable)
         let mut drop = self.drop_by_id.get(&drop_id).expect("Drop not found");
+        require!(!drop.paused, "Drop is paused");
 
         // Check that if the drop config has a resale set, the approval ID is in that set
         if let Some(resale_allowlist) = drop
@@ -143,6 +144,10 @@ impl Keypom {
             metadata: key_info.metadata,
             last_claimed: key_info.last_claimed,
             pw_by_use: key_info.pw_by_use,
+            claim_window: key_info.claim_window,
+            tokens_per_use_overrides: key_info.tokens_per_use_overrides,
+            retryable_use: key_info.retryable_use,
+            reserved_use: key_info.reserved_use,
         };
 
         // Reinsert key info mapping to NFT and then add token ID mapping to public key
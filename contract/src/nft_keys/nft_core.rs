@@ -6,6 +6,8 @@ use crate::*;
 pub struct NftTransferMemo {
     pub linkdrop_pk: PublicKey,
     pub signature: Option<Base64VecU8>,
+    /// Block timestamp after which the signature is no longer valid. Required whenever a signature is passed in
+    pub valid_until: Option<U64>,
     pub new_public_key: PublicKey,
 }
 
@@ -23,13 +25,14 @@ impl Keypom {
         approval_id: Option<u64>,
         memo: String,
     ) {
-        self.assert_no_global_freeze();
+        self.assert_not_paused(PausableAction::key_transfers);
         // Deserialize the msg string into the NftApproveMsg struct
         let nft_transfer_memo: NftTransferMemo =
             serde_json::from_str(&memo).expect("Invalid message format");
         let NftTransferMemo {
             linkdrop_pk,
             signature,
+            valid_until,
             new_public_key: new_pk,
         } = nft_transfer_memo;
 
@@ -53,7 +56,15 @@ impl Keypom {
             let args_string = args_json.to_string();
         
             require!(
-                self.verify_signature(signature.expect("Missing signature"), linkdrop_pk.clone(), args_string),
+                self.verify_signature(
+                    KeySignature {
+                        signature: signature.expect("Missing signature"),
+                        valid_until: valid_until.expect("Missing signature expiry"),
+                    },
+                    linkdrop_pk.clone(),
+                    "nft_transfer",
+                    args_string
+                ),
                 "Invalid signature for public key"
             );
         }
@@ -11,6 +11,8 @@ const MAX_LEN_PAYOUT: u32 = 10;
 pub struct NftTransferPayoutMemo {
     pub linkdrop_pk: PublicKey,
     pub signature: Option<Base64VecU8>,
+    /// Block timestamp after which the signature is no longer valid. Required whenever a signature is passed in
+    pub valid_until: Option<U64>,
     pub new_public_key: PublicKey,
 }
 
@@ -30,13 +32,14 @@ impl Keypom {
         balance: U128,
         max_len_payout: Option<u32>,
     ) -> Payout {
-        self.assert_no_global_freeze();
+        self.assert_not_paused(PausableAction::key_transfers);
         // Deserialize the msg string into the NftApproveMsg struct
         let nft_transfer_memo: NftTransferPayoutMemo =
             serde_json::from_str(&memo).expect("Invalid message format");
         let NftTransferPayoutMemo {
             linkdrop_pk,
             signature,
+            valid_until,
             new_public_key: new_pk,
         } = nft_transfer_memo;
         if env::signer_account_pk() == linkdrop_pk {
@@ -59,7 +62,15 @@ impl Keypom {
             let args_string = args_json.to_string();
         
             require!(
-                self.verify_signature(signature.expect("Missing signature"), linkdrop_pk.clone(), args_string),
+                self.verify_signature(
+                    KeySignature {
+                        signature: signature.expect("Missing signature"),
+                        valid_until: valid_until.expect("Missing signature expiry"),
+                    },
+                    linkdrop_pk.clone(),
+                    "nft_transfer_payout",
+                    args_string
+                ),
                 "Invalid signature for public key"
             );
         }
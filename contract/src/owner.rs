@@ -30,16 +30,92 @@ impl Keypom {
         );
     }
 
-    /// Set the contract to be frozen thus not allowing any drops to be created or keys added
+    /// Set the default rate limit for claims on drops that don't specify their own. Passing in None removes it
+    pub fn set_global_rate_limit(&mut self, rate_limit: Option<RateLimitConfig>) {
+        self.assert_owner();
+        if let Some(config) = rate_limit.as_ref() {
+            assert_valid_rate_limit(config);
+        }
+        self.global_rate_limit = rate_limit;
+    }
+
+    /// Set the contract to be frozen thus pausing every entry point (creation, claims, transfers, deposits and withdrawals)
     pub fn freeze_contract(&mut self) {
         self.assert_owner();
-        self.global_freeze = true
+        self.pause_state = PauseState::all(true);
     }
 
-    /// Set the contract to be unfrozen thus resuming the ability for drops and keys to be created
+    /// Set the contract to be unfrozen thus resuming every entry point
     pub fn unfreeze_contract(&mut self) {
         self.assert_owner();
-        self.global_freeze = false;
+        self.pause_state = PauseState::all(false);
+    }
+
+    /// Pause or resume specific parts of the contract. Any flags not passed in are left unchanged
+    pub fn set_pause_flags(
+        &mut self,
+        drop_creation: Option<bool>,
+        claims: Option<bool>,
+        key_transfers: Option<bool>,
+        deposits: Option<bool>,
+        withdrawals: Option<bool>,
+    ) {
+        self.assert_owner();
+        self.pause_state = PauseState {
+            drop_creation: drop_creation.unwrap_or(self.pause_state.drop_creation),
+            claims: claims.unwrap_or(self.pause_state.claims),
+            key_transfers: key_transfers.unwrap_or(self.pause_state.key_transfers),
+            deposits: deposits.unwrap_or(self.pause_state.deposits),
+            withdrawals: withdrawals.unwrap_or(self.pause_state.withdrawals),
+        };
+    }
+
+    /// Propose a new owner for the contract. Ownership is only transferred once the new owner calls `accept_ownership`.
+    /// Proposing a new owner overwrites any previously pending proposal.
+    pub fn propose_owner(&mut self, new_owner_id: AccountId) {
+        self.assert_owner();
+        require!(
+            new_owner_id != self.contract_owner_id,
+            "New owner must be different from the current owner"
+        );
+        self.pending_owner_id = Some(new_owner_id.clone());
+
+        self.log_ownership_event(EventLogVariant::OwnershipTransferProposed(OwnershipTransferLog {
+            owner_id: self.contract_owner_id.to_string(),
+            pending_owner_id: new_owner_id.to_string(),
+        }));
+    }
+
+    /// Accept a pending ownership transfer. Must be called by the proposed owner
+    pub fn accept_ownership(&mut self) {
+        let pending_owner_id = self
+            .pending_owner_id
+            .take()
+            .expect("No ownership transfer pending");
+        require!(
+            env::predecessor_account_id() == pending_owner_id,
+            "Only the pending owner can accept ownership"
+        );
+        let old_owner_id = std::mem::replace(&mut self.contract_owner_id, pending_owner_id.clone());
+
+        self.log_ownership_event(EventLogVariant::OwnershipTransferAccepted(OwnershipTransferLog {
+            owner_id: old_owner_id.to_string(),
+            pending_owner_id: pending_owner_id.to_string(),
+        }));
+    }
+
+    /// Cancel a pending ownership transfer
+    pub fn cancel_ownership_transfer(&mut self) {
+        self.assert_owner();
+        let pending_owner_id = self
+            .pending_owner_id
+            .take()
+            .expect("No ownership transfer pending");
+
+        self.log_ownership_event(EventLogVariant::OwnershipTransferCancelled(OwnershipTransferLog {
+            owner_id: self.contract_owner_id.to_string(),
+            pending_owner_id: pending_owner_id.to_string(),
+        }));
     }
 
     /// Withdraw the fees collected to the passed in Account Id
@@ -67,6 +143,14 @@ impl Keypom {
         true
     }
 
+    fn log_ownership_event(&self, event: EventLogVariant) {
+        log_events(vec![EventLog {
+            standard: KEYPOM_STANDARD_NAME.to_string(),
+            version: KEYPOM_STANDARD_VERSION.to_string(),
+            event,
+        }]);
+    }
+
     /// Helper method to check if the predecessor is the current contract owner
     pub(crate) fn assert_owner(&self) {
         assert_eq!(
@@ -76,11 +160,17 @@ impl Keypom {
         );
     }
 
-    /// Ensure that the contract key is the one that signed the message
-    pub(crate) fn assert_contract_key(&self) {
-        assert!(
-            self.signing_pks.contains(&env::signer_account_pk()),
-            "Only Contract Key Can Call This Method"
+    /// Ensure that a non-expired contract key whose scope covers `method_name` is the one that signed the message
+    pub(crate) fn assert_contract_key(&self, method_name: &str) {
+        let signing_key_info = self
+            .signing_pks
+            .get(&env::signer_account_pk())
+            .expect("Only Contract Key Can Call This Method");
+
+        require!(!signing_key_info.is_expired(), "Signing key has expired");
+        require!(
+            signing_key_info.scope.allows_method(method_name),
+            format!("Signing key is not allowed to call {}", method_name)
         );
     }
 }
@@ -0,0 +1,111 @@
+use crate::*;
+
+#[near_bindgen]
+impl Keypom {
+    /// Allows you to paginate through the claim receipts for a drop that has `record_claims` enabled.
+    /// Only the drop currently stored under the ID is covered. Receipts for deleted drops can be found through `get_claims_for_account`
+    ///
+    /// Arguments:
+    /// * `drop_id` the ID for the drop
+    /// * `from_index` where to start paginating from. If not specified, will start from 0 index.
+    /// * `limit` how many receipts to return. If not specified, will return 50 receipts.
+    ///
+    /// Returns a vector of `ExtClaimReceipt` objects in the order the claims happened
+    pub fn get_claims_for_drop(
+        &self,
+        drop_id: DropId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<ExtClaimReceipt> {
+        let Some(drop_receipts) = self
+            .drop_by_id
+            .get(&drop_id)
+            .and_then(|drop| self.claim_receipts_by_drop.get(&drop.nonce))
+        else {
+            return vec![];
+        };
+
+        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        drop_receipts
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|receipt| receipt.to_external(&drop_id))
+            .collect()
+    }
+
+    /// Allows you to paginate through the claim receipts for every drop an account has claimed from
+    ///
+    /// Arguments:
+    /// * `account_id` the account that received the assets
+    /// * `from_index` where to start paginating from. If not specified, will start from 0 index.
+    /// * `limit` how many receipts to return. If not specified, will return 50 receipts.
+    ///
+    /// Returns a vector of `ExtClaimReceipt` objects in the order the claims happened
+    pub fn get_claims_for_account(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<ExtClaimReceipt> {
+        let Some(account_receipts) = self.claim_receipts_by_account.get(&account_id) else {
+            return vec![];
+        };
+
+        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        account_receipts
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .filter_map(|receipt_id| {
+                self.claim_receipts_by_drop
+                    .get(&receipt_id.drop_nonce)
+                    .and_then(|receipts| receipts.get(receipt_id.receipt_idx))
+                    .map(|receipt| receipt.to_external(&receipt_id.drop_id))
+            })
+            .collect()
+    }
+
+    /// Query whether an account has received a claim from a drop that has `record_claims` enabled.
+    /// Claims from a deleted drop that used the same ID don't count
+    ///
+    /// Arguments:
+    /// * `drop_id` the ID for the drop
+    /// * `account_id` the account that received the assets
+    ///
+    /// Returns a boolean indicating whether there's a receipt for the account in the drop
+    pub fn has_account_claimed(&self, drop_id: DropId, account_id: AccountId) -> bool {
+        let Some(drop) = self.drop_by_id.get(&drop_id) else {
+            return false;
+        };
+
+        self.claim_receipts_by_account
+            .get(&account_id)
+            .map(|receipts| receipts.iter().any(|r| r.drop_nonce == drop.nonce))
+            .unwrap_or(false)
+    }
+
+    /// Query how many times an account has claimed towards a drop's limit on claims per account.
+    /// If the drop's limit is shared with a group, claims from every drop in the group are included.
+    ///
+    /// Requirements:
+    /// * Panics if the drop does not exist.
+    ///
+    /// Arguments:
+    /// * `drop_id` the ID for the drop
+    /// * `account_id` the account that received the assets
+    ///
+    /// Returns the number of claims counted for the account. Always 0 if the drop doesn't limit claims per account
+    pub fn get_account_claims_for_drop(&self, drop_id: DropId, account_id: AccountId) -> u32 {
+        let drop = self.drop_by_id.get(&drop_id).expect("Drop not found");
+        drop.config
+            .as_ref()
+            .and_then(|c| c.account_claim_limit.as_ref())
+            .map(|limit| self.get_account_claims(&limit.get_scope(&drop), &account_id))
+            .unwrap_or(0)
+    }
+}
@@ -89,4 +89,65 @@ impl Keypom {
             //since we turned the keys into an iterator, we need to turn it back into a vector to return
             .collect();
     }
+
+    /// Allows you to query for the gas required to claim a specific use of the keys in a drop.
+    ///
+    /// Requirements:
+    /// * Panics if the drop does not exist or the use number is out of range.
+    ///
+    /// Arguments:
+    /// * `drop_id` the ID for the drop
+    /// * `use_number` which use (starting at 1) to query for
+    ///
+    /// Returns the gas required for `claim` and `create_account_and_claim` separately
+    pub fn get_required_gas_for_use(
+        &self,
+        drop_id: DropId,
+        use_number: UseNumber,
+    ) -> ExtRequiredGas {
+        let drop = self.drop_by_id.get(&drop_id).expect("Drop not found");
+        require!(
+            use_number > 0 && use_number <= drop.max_key_uses,
+            format!(
+                "Use {} does not exist. The drop has {} uses",
+                use_number, drop.max_key_uses
+            )
+        );
+
+        let asset_data = get_asset_data_for_specific_use(&drop.asset_data_for_uses, &use_number);
+        ExtRequiredGas {
+            claim: asset_data
+                .get_required_gas_for_claim()
+                .map(|gas| gas.as_gas().to_string()),
+            create_account_and_claim: asset_data
+                .get_required_gas_for_create_account_and_claim()
+                .map(|gas| gas.as_gas().to_string()),
+        }
+    }
+
+    /// Allows you to query for the token IDs that have been pinned to specific keys for an NFT asset in a drop
+    ///
+    /// Requirements:
+    /// * Panics if the drop or NFT asset does not exist.
+    ///
+    /// Arguments:
+    /// * `drop_id` the ID for the drop
+    /// * `nft_contract_id` the NFT contract whose pins should be returned
+    ///
+    /// Returns a vector of `PinnedNFT` outlining which token ID is reserved for which key and use
+    pub fn get_pinned_nfts_for_drop(
+        &self,
+        drop_id: DropId,
+        nft_contract_id: AccountId,
+    ) -> Vec<PinnedNFT> {
+        let drop = self.drop_by_id.get(&drop_id).expect("Drop not found");
+        match drop
+            .asset_by_id
+            .get(&nft_contract_id.to_string())
+            .expect("Asset not found")
+        {
+            InternalAsset::nft(nft_data) => nft_data.pinned_tokens,
+            _ => env::panic_str("Asset is not an NFT"),
+        }
+    }
 }
@@ -17,6 +17,54 @@ impl Keypom {
         self.fees_per_user.get(&account_id)
     }
 
+    /// Returns which parts of the contract are currently paused
+    pub fn get_pause_state(&self) -> PauseState {
+        self.pause_state
+    }
+
+    /// Returns the current owner of the contract
+    pub fn get_contract_owner(&self) -> AccountId {
+        self.contract_owner_id.clone()
+    }
+
+    /// Returns the account that has been proposed as the next owner of the contract (if any)
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner_id.clone()
+    }
+
+    /// Returns the default rate limit applied to drops that don't specify their own
+    pub fn get_global_rate_limit(&self) -> Option<RateLimitConfig> {
+        self.global_rate_limit.clone()
+    }
+
+    /// Paginate through the keys that can sign transactions for the contract along with their scope, expiry and allowance
+    pub fn get_signing_keys(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<ExtSigningKey> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.signing_pks
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|(public_key, info)| ExtSigningKey {
+                public_key,
+                scope: info.scope,
+                expires_at: info.expires_at,
+                allowance: info.allowance,
+            })
+            .collect()
+    }
+
+    /// Paginate through the accounts that can manage the contract's signing keys
+    pub fn get_signing_admins(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.signing_admins
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+
     pub(crate) fn parse_key_or_token_id(&self, key_or_token_id: String) -> String {
         let is_token_id = parse_token_id(&key_or_token_id).is_ok();
 
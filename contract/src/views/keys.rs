@@ -30,7 +30,7 @@ impl Keypom {
     #[handle_result]
     pub fn get_key_information(&self, key: String) -> Result<ExtKeyInfo, String> {
         let token_id = self.parse_key_or_token_id(key);
-        let (drop_id, _) = parse_token_id(&token_id)?;
+        let (drop_id, key_id) = parse_token_id(&token_id)?;
 
         let drop = self
             .drop_by_id
@@ -41,12 +41,15 @@ impl Keypom {
             .get(&token_id)
             .expect("Key not found");
         let cur_key_use = get_key_cur_use(&drop, &key_info);
+        let asset_data = get_asset_data_for_specific_use(&drop.asset_data_for_uses, &cur_key_use);
+        // Include any membership check that happens before the assets are released
+        let required_asset_gas = asset_data.get_total_asset_gas();
         let InternalAssetDataForUses {
             uses: _,
             config: use_config,
             assets_metadata,
-            required_asset_gas,
-        } = get_asset_data_for_specific_use(&drop.asset_data_for_uses, &cur_key_use);
+            required_asset_gas: _,
+        } = asset_data;
 
         // If the config's permission field is set to Claim, the base should be set accordingly. In all other cases, it should be the base for CAAC
         let base_gas_for_use =
@@ -65,11 +68,14 @@ impl Keypom {
 
         let mut ft_list: Vec<FTListData> = Vec::new();
         let mut nft_list: Vec<NFTListData> = Vec::new();
+        let mut mt_list: Vec<MTListData> = Vec::new();
         let mut fc_list: Vec<FCData> = Vec::new();
 
         let mut yoctonear = 0;
         let mut num_nfts = 0;
-        for metadata in assets_metadata {
+        for (asset_idx, metadata) in assets_metadata.iter().enumerate() {
+            // Take any per-key overrides into account for how many tokens are transferred
+            let tokens_per_use = key_info.get_tokens_per_use(cur_key_use, asset_idx, metadata);
             let internal_asset = drop
                 .asset_by_id
                 .get(&metadata.asset_id)
@@ -78,15 +84,31 @@ impl Keypom {
             match internal_asset {
                 InternalAsset::ft(ft) => {
                     ft_list.push(FTListData {
-                        amount: metadata.tokens_per_use.unwrap().0.to_string(),
+                        amount: tokens_per_use.unwrap().0.to_string(),
                         contract_id: ft.contract_id.to_string(),
                     });
                 }
                 InternalAsset::nft(nft) => {
-                    let last_idx = nft.token_ids.len().saturating_sub(1);
-                    let idx = last_idx
-                        .checked_sub(num_nfts)
-                        .unwrap_or(nft.token_ids.len());
+                    // Tokens pinned to this key are claimed before anything in the shared pool
+                    if let Some(pin_idx) = nft.get_pinned_token_idx(key_id, cur_key_use) {
+                        nft_list.push(NFTListData {
+                            token_id: nft.pinned_tokens[pin_idx].token_id.clone(),
+                            contract_id: nft.contract_id.to_string(),
+                        });
+                        continue;
+                    }
+
+                    // Random distributions can't be predicted ahead of time so no token is shown
+                    let idx = match nft.distribution {
+                        NFTDistribution::lifo => nft
+                            .token_ids
+                            .len()
+                            .saturating_sub(1)
+                            .checked_sub(num_nfts)
+                            .unwrap_or(nft.token_ids.len()),
+                        NFTDistribution::fifo => num_nfts,
+                        NFTDistribution::random => nft.token_ids.len(),
+                    };
 
                     if let Some(token_id) = nft.token_ids.get(idx) {
                         nft_list.push(NFTListData {
@@ -97,11 +119,25 @@ impl Keypom {
 
                     num_nfts += 1;
                 }
+                InternalAsset::mt(mt) => {
+                    mt_list.push(MTListData {
+                        amount: tokens_per_use.unwrap().0.to_string(),
+                        token_id: mt.token_id,
+                        contract_id: mt.contract_id.to_string(),
+                    });
+                }
+                InternalAsset::wnear(wnear) => {
+                    // The claimer receives the $NEAR as wrapped NEAR fungible tokens
+                    ft_list.push(FTListData {
+                        amount: tokens_per_use.unwrap().0.to_string(),
+                        contract_id: wnear.contract_id.to_string(),
+                    });
+                }
                 InternalAsset::fc(fc) => {
                     fc_list.push(fc.clone());
                 }
                 InternalAsset::near => {
-                    yoctonear += metadata.tokens_per_use.unwrap().0;
+                    yoctonear += tokens_per_use.unwrap().0;
                 }
                 InternalAsset::none => {}
             }
@@ -111,6 +147,7 @@ impl Keypom {
             yoctonear: U128(yoctonear),
             ft_list,
             nft_list,
+            mt_list,
             fc_list,
             metadata: key_info.metadata,
             required_gas: required_gas.as_gas().to_string(),
@@ -169,4 +206,39 @@ impl Keypom {
             .map(|key| self.get_key_information(key.clone()).ok())
             .collect()
     }
+
+    /// Allows you to query for how many claims a key and its drop have used up in the current rate limit window.
+    ///
+    /// Requirements:
+    /// * Panics if the key does not exist.
+    ///
+    /// Arguments:
+    /// * `key` Either the public counterpart of the key used to sign, expressed as a string with format "<key-type>:<base58-key-bytes>" (e.g. "ed25519:6TupyNrcHGTt5XRLmHTc2KGaiSbjhQi1KHtCXTgbcr4Y")
+    /// or the token ID corresponding to the key.
+    ///
+    /// Returns `ExtRateLimitInfo` containing the rate limit that applies and the claims made in the current window
+    #[handle_result]
+    pub fn get_rate_limit_info(&self, key: String) -> Result<ExtRateLimitInfo, String> {
+        let token_id = self.parse_key_or_token_id(key);
+        let (drop_id, _) = parse_token_id(&token_id)?;
+
+        let drop = self
+            .drop_by_id
+            .get(&drop_id)
+            .expect("no drop found for drop ID");
+        let key_info = drop
+            .key_info_by_token_id
+            .get(&token_id)
+            .expect("Key not found");
+
+        let rate_limit = self.get_rate_limit_for_drop(&drop);
+        let window_start = get_rate_limit_window_start(rate_limit.as_ref().and_then(|r| r.window));
+
+        Ok(ExtRateLimitInfo {
+            rate_limit,
+            window_start: U64(window_start),
+            key_claims_in_window: key_info.claim_window.claims_in_window(window_start),
+            drop_claims_in_window: drop.claim_window.claims_in_window(window_start),
+        })
+    }
 }
@@ -2,6 +2,7 @@ pub mod drops;
 pub mod keys;
 pub mod helpers;
 pub mod funder;
+pub mod claims;
 
 pub use drops::*;
 pub use keys::*;
@@ -127,6 +127,7 @@ export async function createContracts({
       owner_id: keypomContractId,
       signing_pks: publicKeys,
       signing_admins: ["minqi.testnet", "benjiman.testnet", "minqianlu.testnet"],
+      chain_id: "testnet",
       message: "Keypom is lit!",
     },
     deposit: "0",
@@ -0,0 +1,36 @@
+use crate::*;
+
+/// Methods that FC assets can call to check how Keypom chains them together
+#[near_bindgen]
+impl MockContract {
+    /// Record the call and return `value` as is (i.e to pipe it into the next method)
+    #[payable]
+    pub fn fc_return(&mut self, value: Value) -> Value {
+        self.internal_record_fc_call();
+        value
+    }
+
+    /// Record the call along with its raw args
+    #[payable]
+    pub fn fc_record(&mut self) {
+        self.internal_record_fc_call();
+    }
+
+    /// Always fails
+    #[payable]
+    pub fn fc_fail(&mut self) {
+        env::panic_str("FC method failed");
+    }
+
+    /// Raw args of every recorded call
+    pub fn get_fc_calls(&self) -> Vec<String> {
+        self.fc_calls.to_vec()
+    }
+}
+
+impl MockContract {
+    fn internal_record_fc_call(&mut self) {
+        let args = String::from_utf8(env::input().unwrap_or_default()).unwrap();
+        self.fc_calls.push(&args);
+    }
+}
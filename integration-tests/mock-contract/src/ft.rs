@@ -0,0 +1,160 @@
+use crate::*;
+
+/// How much $NEAR it costs to register an account. Matches `wrap.near`
+pub const FT_STORAGE_COST: NearToken = NearToken::from_yoctonear(1_250_000_000_000_000_000_000);
+
+impl MockContract {
+    fn internal_ft_register(&mut self, account_id: &AccountId) {
+        if self.ft_balances.get(account_id).is_none() {
+            self.ft_balances.insert(account_id, &0);
+        }
+    }
+
+    fn internal_ft_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+    ) {
+        let sender_balance = self
+            .ft_balances
+            .get(sender_id)
+            .expect("Sender is not registered");
+        require!(sender_balance >= amount, "Not enough balance");
+        let receiver_balance = self.ft_balances.get(receiver_id).unwrap_or_else(|| {
+            env::panic_str(&format!("The account {} is not registered", receiver_id))
+        });
+
+        self.ft_balances
+            .insert(sender_id, &(sender_balance - amount));
+        self.ft_balances
+            .insert(receiver_id, &(receiver_balance + amount));
+    }
+}
+
+#[near_bindgen]
+impl MockContract {
+    /// Register an account. Any $NEAR beyond the storage cost (or everything if the account is already registered) is refunded.
+    /// Accounts can only ever be registered so `registration_only` is implied
+    #[payable]
+    pub fn storage_deposit(&mut self, account_id: Option<AccountId>) -> Value {
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let deposit = env::attached_deposit();
+        let refund = if self.ft_balances.get(&account_id).is_some() {
+            deposit
+        } else {
+            require!(
+                deposit >= FT_STORAGE_COST,
+                "The attached deposit is less than the minimum storage balance"
+            );
+            self.internal_ft_register(&account_id);
+            deposit.saturating_sub(FT_STORAGE_COST)
+        };
+        if !refund.is_zero() {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+
+        self.storage_balance_of(account_id).unwrap()
+    }
+
+    pub fn storage_balance_of(&self, account_id: AccountId) -> Option<Value> {
+        self.ft_balances
+            .get(&account_id)
+            .map(|_| json!({"total": FT_STORAGE_COST, "available": "0"}))
+    }
+
+    pub fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+        U128(self.ft_balances.get(&account_id).unwrap_or(0))
+    }
+
+    #[payable]
+    pub fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
+        near_sdk::assert_one_yocto();
+        self.assert_transfers_not_paused();
+        if let Some(memo) = memo {
+            near_sdk::log!("Memo: {}", memo);
+        }
+        self.internal_ft_transfer(&env::predecessor_account_id(), &receiver_id, amount.0);
+    }
+
+    /// Create `amount` new tokens for `account_id`, registering them if needed
+    pub fn ft_mint(&mut self, account_id: AccountId, amount: U128) {
+        self.internal_ft_register(&account_id);
+        let balance = self.ft_balances.get(&account_id).unwrap();
+        self.ft_balances.insert(&account_id, &(balance + amount.0));
+    }
+
+    /// Transfer tokens and call `ft_on_transfer` on the receiver. Whatever the receiver returns is refunded
+    #[payable]
+    pub fn ft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> Promise {
+        near_sdk::assert_one_yocto();
+        if let Some(memo) = memo {
+            near_sdk::log!("Memo: {}", memo);
+        }
+        let sender_id = env::predecessor_account_id();
+        self.internal_ft_transfer(&sender_id, &receiver_id, amount.0);
+
+        Promise::new(receiver_id.clone())
+            .function_call(
+                "ft_on_transfer".to_string(),
+                json!({"sender_id": sender_id, "amount": amount, "msg": msg})
+                    .to_string()
+                    .into(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_ON_TRANSFER,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .ft_resolve_transfer(sender_id, receiver_id, amount),
+            )
+    }
+
+    /// Return the unused tokens to the sender. Returns how many tokens were kept by the receiver
+    #[private]
+    pub fn ft_resolve_transfer(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> U128 {
+        let unused = match env::promise_result(0) {
+            PromiseResult::Successful(value) => near_sdk::serde_json::from_slice::<U128>(&value)
+                .map(|unused| unused.0.min(amount.0))
+                .unwrap_or(amount.0),
+            PromiseResult::Failed => amount.0,
+        };
+
+        let refund = unused.min(self.ft_balance_of(receiver_id.clone()).0);
+        if refund > 0 {
+            self.internal_ft_transfer(&receiver_id, &sender_id, refund);
+        }
+
+        U128(amount.0 - refund)
+    }
+
+    /// Wrap the attached $NEAR. Like `wrap.near`, unregistered accounts are registered and charged the storage cost out of the deposit
+    #[payable]
+    pub fn near_deposit(&mut self) {
+        let account_id = env::predecessor_account_id();
+        let mut amount = env::attached_deposit();
+        if self.ft_balances.get(&account_id).is_none() {
+            require!(
+                amount > FT_STORAGE_COST,
+                "The attached deposit is less than the minimum storage balance"
+            );
+            self.internal_ft_register(&account_id);
+            amount = amount.saturating_sub(FT_STORAGE_COST);
+        }
+
+        let balance = self.ft_balances.get(&account_id).unwrap();
+        self.ft_balances
+            .insert(&account_id, &(balance + amount.as_yoctonear()));
+    }
+}
@@ -0,0 +1,75 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, LookupSet, Vector};
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::{json, Value};
+use near_sdk::{
+    env, near_bindgen, require, AccountId, BorshStorageKey, Gas, NearToken, PanicOnDefault,
+    Promise, PromiseResult, PublicKey,
+};
+
+mod fc;
+mod ft;
+mod linkdrop;
+mod membership;
+mod mt;
+mod nft;
+
+/// Gas attached to the receiver's `*_on_transfer` method
+const GAS_FOR_ON_TRANSFER: Gas = Gas::from_tgas(50);
+/// Gas attached to the callback that resolves a `*_transfer_call`
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas::from_tgas(10);
+
+#[derive(BorshSerialize, BorshStorageKey)]
+#[borsh(crate = "near_sdk::borsh")]
+enum StorageKeys {
+    MTBalances,
+    FTBalances,
+    NFTOwners,
+    FCCalls,
+    Members,
+}
+
+/// Bare-bones token and helper contract that Keypom interacts with in the integration tests.
+/// None of the standards are fully implemented. Only what Keypom calls (and what the tests need to set things up) is.
+#[near_bindgen]
+#[derive(BorshSerialize, BorshDeserialize, PanicOnDefault)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct MockContract {
+    /// Multi token balances keyed by `token_id:account_id`
+    pub mt_balances: LookupMap<String, u128>,
+    /// Fungible token balances. Accounts are registered once they have an entry
+    pub ft_balances: LookupMap<AccountId, u128>,
+    /// Owner of each non fungible token
+    pub nft_owners: LookupMap<String, AccountId>,
+    /// Whether plain FT, NFT and MT transfers should fail. Lets the tests exercise Keypom's refunds for failed claims
+    pub transfers_paused: bool,
+    /// Raw args of every recorded function call, in the order they were received
+    pub fc_calls: Vector<String>,
+    /// Accounts that pass the membership check
+    pub members: LookupSet<AccountId>,
+}
+
+#[near_bindgen]
+impl MockContract {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            mt_balances: LookupMap::new(StorageKeys::MTBalances),
+            ft_balances: LookupMap::new(StorageKeys::FTBalances),
+            nft_owners: LookupMap::new(StorageKeys::NFTOwners),
+            transfers_paused: false,
+            fc_calls: Vector::new(StorageKeys::FCCalls),
+            members: LookupSet::new(StorageKeys::Members),
+        }
+    }
+
+    pub fn set_transfers_paused(&mut self, paused: bool) {
+        self.transfers_paused = paused;
+    }
+}
+
+impl MockContract {
+    fn assert_transfers_not_paused(&self) {
+        require!(!self.transfers_paused, "Transfers are paused");
+    }
+}
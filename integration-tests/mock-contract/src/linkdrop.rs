@@ -0,0 +1,35 @@
+use crate::*;
+
+/// Gas attached to the callback that checks whether the account was created
+const GAS_FOR_RESOLVE_CREATE_ACCOUNT: Gas = Gas::from_tgas(5);
+
+#[near_bindgen]
+impl MockContract {
+    /// Create a sub-account of this contract with a full access key, the same way the `near` linkdrop contract does.
+    /// Returns whether the account was created
+    #[payable]
+    pub fn create_account(
+        &mut self,
+        new_account_id: AccountId,
+        new_public_key: PublicKey,
+    ) -> Promise {
+        let mut promise = Promise::new(new_account_id)
+            .create_account()
+            .add_full_access_key(new_public_key);
+        let deposit = env::attached_deposit();
+        if !deposit.is_zero() {
+            promise = promise.transfer(deposit);
+        }
+
+        promise.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_CREATE_ACCOUNT)
+                .on_account_created(),
+        )
+    }
+
+    #[private]
+    pub fn on_account_created(&mut self) -> bool {
+        matches!(env::promise_result(0), PromiseResult::Successful(_))
+    }
+}
@@ -0,0 +1,13 @@
+use crate::*;
+
+/// Membership list that Keypom can check before releasing assets
+#[near_bindgen]
+impl MockContract {
+    pub fn add_member(&mut self, account_id: AccountId) {
+        self.members.insert(&account_id);
+    }
+
+    pub fn is_member(&self, account_id: AccountId) -> bool {
+        self.members.contains(&account_id)
+    }
+}
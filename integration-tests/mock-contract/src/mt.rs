@@ -0,0 +1,140 @@
+use crate::*;
+
+fn mt_balance_key(token_id: &str, account_id: &AccountId) -> String {
+    format!("{}:{}", token_id, account_id)
+}
+
+impl MockContract {
+    fn internal_mt_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_id: &str,
+        amount: u128,
+    ) {
+        let sender_balance = self
+            .mt_balance_of(sender_id.clone(), token_id.to_string())
+            .0;
+        require!(sender_balance >= amount, "Not enough balance");
+        self.mt_balances.insert(
+            &mt_balance_key(token_id, sender_id),
+            &(sender_balance - amount),
+        );
+
+        let receiver_balance = self
+            .mt_balance_of(receiver_id.clone(), token_id.to_string())
+            .0;
+        self.mt_balances.insert(
+            &mt_balance_key(token_id, receiver_id),
+            &(receiver_balance + amount),
+        );
+    }
+}
+
+#[near_bindgen]
+impl MockContract {
+    /// Create `amount` new tokens for `account_id`
+    pub fn mt_mint(&mut self, account_id: AccountId, token_id: String, amount: U128) {
+        let balance = self.mt_balance_of(account_id.clone(), token_id.clone()).0;
+        self.mt_balances.insert(
+            &mt_balance_key(&token_id, &account_id),
+            &(balance + amount.0),
+        );
+    }
+
+    pub fn mt_balance_of(&self, account_id: AccountId, token_id: String) -> U128 {
+        U128(
+            self.mt_balances
+                .get(&mt_balance_key(&token_id, &account_id))
+                .unwrap_or(0),
+        )
+    }
+
+    #[payable]
+    pub fn mt_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: String,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        near_sdk::assert_one_yocto();
+        self.assert_transfers_not_paused();
+        if let Some(memo) = memo {
+            near_sdk::log!("Memo: {}", memo);
+        }
+        self.internal_mt_transfer(
+            &env::predecessor_account_id(),
+            &receiver_id,
+            &token_id,
+            amount.0,
+        );
+    }
+
+    /// Transfer a batch of tokens and call `mt_on_transfer` on the receiver. Whatever the receiver returns is refunded
+    #[payable]
+    pub fn mt_batch_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+        msg: String,
+    ) -> Promise {
+        near_sdk::assert_one_yocto();
+        require!(token_ids.len() == amounts.len(), "Length mismatch");
+        let sender_id = env::predecessor_account_id();
+        for (token_id, amount) in token_ids.iter().zip(amounts.iter()) {
+            self.internal_mt_transfer(&sender_id, &receiver_id, token_id, amount.0);
+        }
+
+        Promise::new(receiver_id.clone())
+            .function_call(
+                "mt_on_transfer".to_string(),
+                json!({
+                    "sender_id": sender_id,
+                    "previous_owner_ids": vec![sender_id.clone(); token_ids.len()],
+                    "token_ids": token_ids,
+                    "amounts": amounts,
+                    "msg": msg
+                })
+                .to_string()
+                .into(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_ON_TRANSFER,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .mt_resolve_transfer(sender_id, receiver_id, token_ids, amounts),
+            )
+    }
+
+    /// Return the refunded amounts to the sender. Returns how many of each token were kept by the receiver
+    #[private]
+    pub fn mt_resolve_transfer(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+    ) -> Vec<U128> {
+        let refunds = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                near_sdk::serde_json::from_slice::<Vec<U128>>(&value).unwrap_or(amounts.clone())
+            }
+            PromiseResult::Failed => amounts.clone(),
+        };
+
+        let mut used_amounts = vec![];
+        for ((token_id, amount), refund) in token_ids.iter().zip(amounts).zip(refunds) {
+            let receiver_balance = self.mt_balance_of(receiver_id.clone(), token_id.clone()).0;
+            let refund = refund.0.min(amount.0).min(receiver_balance);
+            if refund > 0 {
+                self.internal_mt_transfer(&receiver_id, &sender_id, token_id, refund);
+            }
+            used_amounts.push(U128(amount.0 - refund));
+        }
+
+        used_amounts
+    }
+}
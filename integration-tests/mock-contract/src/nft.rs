@@ -0,0 +1,101 @@
+use crate::*;
+
+impl MockContract {
+    fn internal_nft_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_id: &str,
+    ) {
+        let owner_id = self
+            .nft_owners
+            .get(&token_id.to_string())
+            .expect("Token not found");
+        require!(&owner_id == sender_id, "Sender does not own the token");
+        self.nft_owners.insert(&token_id.to_string(), receiver_id);
+    }
+}
+
+#[near_bindgen]
+impl MockContract {
+    /// Create a new token owned by `receiver_id`
+    pub fn nft_mint(&mut self, token_id: String, receiver_id: AccountId) {
+        require!(
+            self.nft_owners.get(&token_id).is_none(),
+            "Token already exists"
+        );
+        self.nft_owners.insert(&token_id, &receiver_id);
+    }
+
+    pub fn nft_token(&self, token_id: String) -> Option<Value> {
+        self.nft_owners
+            .get(&token_id)
+            .map(|owner_id| json!({"token_id": token_id, "owner_id": owner_id}))
+    }
+
+    #[payable]
+    pub fn nft_transfer(&mut self, receiver_id: AccountId, token_id: String, memo: Option<String>) {
+        near_sdk::assert_one_yocto();
+        self.assert_transfers_not_paused();
+        if let Some(memo) = memo {
+            near_sdk::log!("Memo: {}", memo);
+        }
+        self.internal_nft_transfer(&env::predecessor_account_id(), &receiver_id, &token_id);
+    }
+
+    /// Transfer a token and call `nft_on_transfer` on the receiver. The token is returned if the receiver returns true
+    #[payable]
+    pub fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: String,
+        msg: String,
+    ) -> Promise {
+        near_sdk::assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.internal_nft_transfer(&sender_id, &receiver_id, &token_id);
+
+        Promise::new(receiver_id.clone())
+            .function_call(
+                "nft_on_transfer".to_string(),
+                json!({
+                    "sender_id": sender_id,
+                    "previous_owner_id": sender_id,
+                    "token_id": token_id,
+                    "msg": msg
+                })
+                .to_string()
+                .into(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_ON_TRANSFER,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .nft_resolve_transfer(sender_id, receiver_id, token_id),
+            )
+    }
+
+    /// Return the token to the previous owner if the receiver asked for it back. Returns whether the receiver kept the token
+    #[private]
+    pub fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: String,
+    ) -> bool {
+        let should_return = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                near_sdk::serde_json::from_slice::<bool>(&value).unwrap_or(true)
+            }
+            PromiseResult::Failed => true,
+        };
+
+        if should_return && self.nft_owners.get(&token_id) == Some(receiver_id.clone()) {
+            self.internal_nft_transfer(&receiver_id, &previous_owner_id, &token_id);
+            return false;
+        }
+
+        !should_return
+    }
+}
@@ -0,0 +1,187 @@
+use crate::*;
+
+/// Keys can be used to create new accounts even if the use doesn't transfer any assets
+pub async fn test_create_account_without_assets(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let root_contract = deploy_mock_contract(worker).await?;
+    let funder = worker.dev_create_account().await?;
+
+    // 2 uses that only create accounts using the mock linkdrop contract as the root
+    let keys = generate_keypairs(1);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "none_drop",
+        vec![keys[0].public_key()],
+        json!([{
+            "uses": 2,
+            "assets": [null],
+            "config": {"root_account_id": root_contract.id()}
+        }]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    let new_account_id: AccountId = format!("alice.{}", root_contract.id()).parse()?;
+    let new_account_sk = generate_keypairs(1).remove(0);
+    let res = create_account_and_claim_with_key(
+        &relayer,
+        &keypom_contract,
+        &keys[0],
+        &new_account_id,
+        &new_account_sk.public_key(),
+    )
+    .await?;
+    assert!(res.into_result()?.json::<bool>()?);
+
+    // The account exists and is controlled by the new key
+    let new_account =
+        Account::from_secret_key(new_account_id.clone(), new_account_sk.clone(), worker);
+    new_account.view_account().await?;
+    let key_info = get_key_info(&keypom_contract, keys[0].public_key(), true)
+        .await?
+        .unwrap();
+    assert_eq!(key_info["uses_remaining"], 1);
+
+    // Creating an account that already exists fails and the use is consumed
+    let res = create_account_and_claim_with_key(
+        &relayer,
+        &keypom_contract,
+        &keys[0],
+        &new_account_id,
+        &new_account_sk.public_key(),
+    )
+    .await?;
+    assert!(!res.into_result()?.json::<bool>()?);
+    get_key_info(&keypom_contract, keys[0].public_key(), false).await?;
+
+    println!("      Passed ✅ test_create_account_without_assets");
+    Ok(())
+}
+
+/// Uses with a retry window are held for the key when account creation fails so the holder can pick another name.
+/// Once the window expires, the use can be released and its assets are refunded to the funder
+pub async fn test_account_creation_retries(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let root_contract = deploy_mock_contract(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    // The funder pays for the storage needed to keep track of the reservation
+    add_to_balance(&funder, &keypom_contract, NearToken::from_near(1)).await?;
+
+    // Take a name so that creating it through Keypom fails
+    let taken_account_id: AccountId = format!("taken.{}", root_contract.id()).parse()?;
+    let new_account_sk = generate_keypairs(1).remove(0);
+    funder
+        .call(root_contract.id(), "create_account")
+        .args_json(json!({
+            "new_account_id": taken_account_id,
+            "new_public_key": new_account_sk.public_key()
+        }))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    // Each key has 1 use that's held for 15 seconds if account creation fails
+    let keys = generate_keypairs(2);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "retry_drop",
+        keys.iter().map(|sk| sk.public_key()).collect(),
+        json!([{
+            "uses": 1,
+            "assets": [{"yoctonear": NearToken::from_millinear(100).as_yoctonear().to_string()}],
+            "config": {
+                "root_account_id": root_contract.id(),
+                "account_creation_retry_window": 15_000_000_000u64
+            }
+        }]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    let res = create_account_and_claim_with_key(
+        &relayer,
+        &keypom_contract,
+        &keys[0],
+        &taken_account_id,
+        &new_account_sk.public_key(),
+    )
+    .await?;
+    assert!(!res.into_result()?.json::<bool>()?);
+    let key_info = get_key_info(&keypom_contract, keys[0].public_key(), true)
+        .await?
+        .unwrap();
+    assert_eq!(key_info["uses_remaining"], 1);
+
+    // The reserved use can only be retried by creating an account and can't be released early
+    let claimer = worker.dev_create_account().await?;
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert_failed_with(
+        res,
+        "Reserved use can only be retried with `create_account_and_claim`",
+    );
+    let res = funder
+        .call(keypom_contract.id(), "release_expired_reservation")
+        .args_json(json!({"linkdrop_pk": keys[0].public_key()}))
+        .transact()
+        .await?;
+    assert_failed_with(res, "Reserved use has not expired yet");
+
+    // Retrying with a free name claims the held use
+    let new_account_id: AccountId = format!("bob.{}", root_contract.id()).parse()?;
+    let res = create_account_and_claim_with_key(
+        &relayer,
+        &keypom_contract,
+        &keys[0],
+        &new_account_id,
+        &new_account_sk.public_key(),
+    )
+    .await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    let new_account = Account::from_secret_key(new_account_id, new_account_sk.clone(), worker);
+    assert!(new_account.view_account().await?.balance >= NearToken::from_millinear(100));
+    get_key_info(&keypom_contract, keys[0].public_key(), false).await?;
+
+    // Once the window expires, the use can't be retried anymore and is released back to the funder
+    let res = create_account_and_claim_with_key(
+        &relayer,
+        &keypom_contract,
+        &keys[1],
+        &taken_account_id,
+        &new_account_sk.public_key(),
+    )
+    .await?;
+    assert!(!res.into_result()?.json::<bool>()?);
+    tokio::time::sleep(std::time::Duration::from_secs(20)).await;
+
+    let new_account_id: AccountId = format!("carol.{}", root_contract.id()).parse()?;
+    let res = create_account_and_claim_with_key(
+        &relayer,
+        &keypom_contract,
+        &keys[1],
+        &new_account_id,
+        &new_account_sk.public_key(),
+    )
+    .await?;
+    assert_failed_with(res, "Reserved use has expired");
+
+    let balance_before_release = get_user_balance(&keypom_contract, funder.id()).await?;
+    relayer
+        .call(keypom_contract.id(), "release_expired_reservation")
+        .args_json(json!({"linkdrop_pk": keys[1].public_key()}))
+        .transact()
+        .await?
+        .into_result()?;
+    get_key_info(&keypom_contract, keys[1].public_key(), false).await?;
+    assert!(
+        get_user_balance(&keypom_contract, funder.id()).await?
+            >= balance_before_release + NearToken::from_millinear(100).as_yoctonear()
+    );
+
+    println!("      Passed ✅ test_account_creation_retries");
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use crate::ft_assets::{ft_deposit, ft_mint, ft_register};
+use crate::wnear_assets::ft_balance_of;
+use crate::*;
+
+/// Atomic uses are restored when any of their assets fail so that only the failed assets are claimed on the next attempt.
+/// Regular uses are consumed and the failed assets go back to the drop
+pub async fn test_atomic_claims(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let ft_contract = deploy_mock_contract(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+    // The funder pays for the storage needed to keep track of which assets are left to retry
+    add_to_balance(&funder, &keypom_contract, NearToken::from_near(1)).await?;
+
+    ft_mint(&ft_contract, funder.id(), 100).await?;
+    ft_register(&funder, &ft_contract, keypom_contract.id()).await?;
+    ft_register(&claimer, &ft_contract, claimer.id()).await?;
+
+    // Each key sends 0.1 $NEAR and 10 FTs in a single use
+    let use_data = |config: Value| {
+        json!({
+            "uses": 1,
+            "assets": [
+                {"yoctonear": NearToken::from_millinear(100).as_yoctonear().to_string()},
+                {
+                    "ft_contract_id": ft_contract.id(),
+                    "registration_cost": "0",
+                    "ft_amount": "10",
+                    "ft_registration_policy": "claimer_registered"
+                }
+            ],
+            "config": config
+        })
+    };
+    let keys = generate_keypairs(2);
+    for (drop_id, key, config) in [
+        ("atomic_drop", &keys[0], json!({"atomic": true})),
+        ("regular_drop", &keys[1], Value::Null),
+    ] {
+        create_drop(
+            &funder,
+            &keypom_contract,
+            drop_id,
+            vec![key.public_key()],
+            json!([use_data(config)]),
+            Value::Null,
+            NearToken::from_near(1),
+        )
+        .await?;
+        ft_deposit(&funder, &ft_contract, &keypom_contract, 10, json!(drop_id)).await?;
+    }
+
+    // The $NEAR goes through but the FTs don't so the use is rolled back
+    let initial_near = claimer.view_account().await?.balance;
+    set_transfers_paused(&ft_contract, true).await?;
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(logged_claim_failed(&res, true));
+    assert!(!res.into_result()?.json::<bool>()?);
+    let key_info = get_key_info(&keypom_contract, keys[0].public_key(), true)
+        .await?
+        .unwrap();
+    assert_eq!(key_info["uses_remaining"], 1);
+    assert_eq!(ft_balance_of(&ft_contract, claimer.id()).await?, 0);
+    let near_after_failure = claimer.view_account().await?.balance;
+    assert_eq!(
+        near_after_failure.as_yoctonear() - initial_near.as_yoctonear(),
+        NearToken::from_millinear(100).as_yoctonear()
+    );
+
+    // Retrying only sends the FTs
+    set_transfers_paused(&ft_contract, false).await?;
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(ft_balance_of(&ft_contract, claimer.id()).await?, 10);
+    assert_eq!(claimer.view_account().await?.balance, near_after_failure);
+    get_key_info(&keypom_contract, keys[0].public_key(), false).await?;
+
+    // Without the flag, the use is consumed and the FTs stay with the drop
+    set_transfers_paused(&ft_contract, true).await?;
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[1], claimer.id()).await?;
+    assert!(logged_claim_failed(&res, false));
+    assert!(!res.into_result()?.json::<bool>()?);
+    set_transfers_paused(&ft_contract, false).await?;
+    get_key_info(&keypom_contract, keys[1].public_key(), false).await?;
+    assert_eq!(ft_balance_of(&ft_contract, claimer.id()).await?, 10);
+    assert_eq!(ft_balance_of(&ft_contract, keypom_contract.id()).await?, 10);
+
+    println!("      Passed ✅ test_atomic_claims");
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use crate::*;
+
+async fn get_account_claims_for_drop(
+    keypom_contract: &Contract,
+    drop_id: &str,
+    account_id: &AccountId,
+) -> anyhow::Result<u32> {
+    Ok(keypom_contract
+        .view("get_account_claims_for_drop")
+        .args_json(json!({"drop_id": drop_id, "account_id": account_id}))
+        .await?
+        .json::<u32>()?)
+}
+
+/// Drops can limit how many times the same account receives assets, either per drop or across a group of the funder's drops.
+/// Claims whose assets are never released don't count towards the limit and the limit can't change once the drop is claimed
+pub async fn test_account_claim_limits(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let root_contract = deploy_mock_contract(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let other_funder = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+    // Funders pay for keeping track of the claims per account
+    add_to_balance(&funder, &keypom_contract, NearToken::from_near(1)).await?;
+    add_to_balance(&other_funder, &keypom_contract, NearToken::from_near(1)).await?;
+
+    let keys = generate_keypairs(2);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "limited_drop",
+        keys.iter().map(|sk| sk.public_key()).collect(),
+        json!([near_asset_data(1, NearToken::from_millinear(100))]),
+        json!({"account_claim_limit": {"max_claims_per_account": 1}}),
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], alice.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(
+        get_account_claims_for_drop(&keypom_contract, "limited_drop", alice.id()).await?,
+        1
+    );
+
+    // The same account can't claim again with a different key but anyone else can
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[1], alice.id()).await?;
+    assert_failed_with(
+        res,
+        &format!("Account {} has reached the limit of 1 claims", alice.id()),
+    );
+
+    // The limit is locked once the drop has been claimed
+    let res = funder
+        .call(keypom_contract.id(), "update_drop_config")
+        .args_json(json!({
+            "drop_id": "limited_drop",
+            "drop_config": {"account_claim_limit": null}
+        }))
+        .max_gas()
+        .transact()
+        .await?;
+    assert_failed_with(
+        res,
+        "Cannot change the account claim limit once the drop has been claimed",
+    );
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[1], bob.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+
+    // Limits with a group are shared by every drop from the same funder in that group
+    let group_limit =
+        json!({"account_claim_limit": {"max_claims_per_account": 1, "group": "launch"}});
+    let keys = generate_keypairs(3);
+    for (funder, drop_id, key) in [
+        (&funder, "group_drop_a", &keys[0]),
+        (&funder, "group_drop_b", &keys[1]),
+        (&other_funder, "other_group_drop", &keys[2]),
+    ] {
+        create_drop(
+            funder,
+            &keypom_contract,
+            drop_id,
+            vec![key.public_key()],
+            json!([near_asset_data(1, NearToken::from_millinear(100))]),
+            group_limit.clone(),
+            NearToken::from_near(1),
+        )
+        .await?;
+    }
+
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], alice.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(
+        get_account_claims_for_drop(&keypom_contract, "group_drop_b", alice.id()).await?,
+        1
+    );
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[1], alice.id()).await?;
+    assert_failed_with(res, "has reached the limit of 1 claims");
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[2], alice.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+
+    // A failed account creation doesn't count towards the new account's limit
+    let taken_account_id: AccountId = format!("taken.{}", root_contract.id()).parse()?;
+    let new_account_sk = generate_keypairs(1).remove(0);
+    funder
+        .call(root_contract.id(), "create_account")
+        .args_json(json!({
+            "new_account_id": taken_account_id,
+            "new_public_key": new_account_sk.public_key()
+        }))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    let keys = generate_keypairs(2);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "limited_caac_drop",
+        keys.iter().map(|sk| sk.public_key()).collect(),
+        json!([{
+            "uses": 1,
+            "assets": [{"yoctonear": NearToken::from_millinear(100).as_yoctonear().to_string()}],
+            "config": {"root_account_id": root_contract.id()}
+        }]),
+        json!({"account_claim_limit": {"max_claims_per_account": 1}}),
+        NearToken::from_near(1),
+    )
+    .await?;
+    let res = create_account_and_claim_with_key(
+        &relayer,
+        &keypom_contract,
+        &keys[0],
+        &taken_account_id,
+        &new_account_sk.public_key(),
+    )
+    .await?;
+    assert!(!res.into_result()?.json::<bool>()?);
+    assert_eq!(
+        get_account_claims_for_drop(&keypom_contract, "limited_caac_drop", &taken_account_id)
+            .await?,
+        0
+    );
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[1], &taken_account_id).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+
+    println!("      Passed ✅ test_account_claim_limits");
+    Ok(())
+}
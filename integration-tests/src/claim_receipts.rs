@@ -0,0 +1,156 @@
+use crate::*;
+
+async fn get_claims(
+    keypom_contract: &Contract,
+    method_name: &str,
+    args: Value,
+) -> anyhow::Result<Vec<Value>> {
+    Ok(keypom_contract
+        .view(method_name)
+        .args_json(args)
+        .await?
+        .json::<Vec<Value>>()?)
+}
+
+async fn has_account_claimed(
+    keypom_contract: &Contract,
+    drop_id: &str,
+    account_id: &AccountId,
+) -> anyhow::Result<bool> {
+    Ok(keypom_contract
+        .view("has_account_claimed")
+        .args_json(json!({"drop_id": drop_id, "account_id": account_id}))
+        .await?
+        .json::<bool>()?)
+}
+
+/// Drops that record claims keep a receipt for every finished use, paid for by the funder's balance.
+/// If the funder can't cover the receipt, the claim still goes through without one.
+/// A new drop created under a deleted drop's ID starts without any receipts
+pub async fn test_claim_receipts(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let broke_funder = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+    add_to_balance(&funder, &keypom_contract, NearToken::from_near(1)).await?;
+
+    // The second use calls a method that doesn't exist on alice's account so it fails
+    let keys = generate_keypairs(1);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "recorded_drop",
+        vec![keys[0].public_key()],
+        json!([
+            near_asset_data(1, NearToken::from_millinear(100)),
+            {"uses": 1, "assets": [[{
+                "receiver_id": alice.id(),
+                "method_name": "ping",
+                "args": "{}",
+                "attached_deposit": "0",
+                "attached_gas": Gas::from_tgas(20).as_gas().to_string()
+            }]]}
+        ]),
+        json!({"record_claims": true, "delete_empty_drop": false}),
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], alice.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], alice.id()).await?;
+    assert!(!res.into_result()?.json::<bool>()?);
+
+    let receipts = get_claims(
+        &keypom_contract,
+        "get_claims_for_drop",
+        json!({"drop_id": "recorded_drop"}),
+    )
+    .await?;
+    assert_eq!(receipts.len(), 2);
+    assert_eq!(receipts[0]["account_id"], alice.id().to_string());
+    assert_eq!(receipts[0]["use_number"], 1);
+    assert_eq!(receipts[0]["failed_asset_indices"], json!([]));
+    assert_eq!(receipts[1]["use_number"], 2);
+    assert_eq!(receipts[1]["failed_asset_indices"], json!([0]));
+    assert!(
+        get_user_balance(&keypom_contract, funder.id()).await?
+            < NearToken::from_near(1).as_yoctonear()
+    );
+
+    assert!(has_account_claimed(&keypom_contract, "recorded_drop", alice.id()).await?);
+    assert!(!has_account_claimed(&keypom_contract, "recorded_drop", bob.id()).await?);
+
+    // Deleting the drop and re-using its ID doesn't carry the old receipts over
+    funder
+        .call(keypom_contract.id(), "delete_keys")
+        .args_json(json!({"drop_id": "recorded_drop"}))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "recorded_drop",
+        vec![generate_keypairs(1)[0].public_key()],
+        json!([near_asset_data(1, NearToken::from_millinear(100))]),
+        json!({"record_claims": true}),
+        NearToken::from_near(1),
+    )
+    .await?;
+    assert!(get_claims(
+        &keypom_contract,
+        "get_claims_for_drop",
+        json!({"drop_id": "recorded_drop"}),
+    )
+    .await?
+    .is_empty());
+    assert!(!has_account_claimed(&keypom_contract, "recorded_drop", alice.id()).await?);
+
+    // Nothing is recorded for drops that don't opt in or whose funder can't pay for the receipt
+    let keys = generate_keypairs(2);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "unrecorded_drop",
+        vec![keys[0].public_key()],
+        json!([near_asset_data(1, NearToken::from_millinear(100))]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+    create_drop(
+        &broke_funder,
+        &keypom_contract,
+        "unpaid_drop",
+        vec![keys[1].public_key()],
+        json!([near_asset_data(1, NearToken::from_millinear(100))]),
+        json!({"record_claims": true}),
+        NearToken::from_near(1),
+    )
+    .await?;
+    assert_eq!(
+        get_user_balance(&keypom_contract, broke_funder.id()).await?,
+        0
+    );
+
+    for (key, drop_id) in [(&keys[0], "unrecorded_drop"), (&keys[1], "unpaid_drop")] {
+        let res = claim_with_key(&relayer, &keypom_contract, key, alice.id()).await?;
+        assert!(res.into_result()?.json::<bool>()?);
+        assert!(!has_account_claimed(&keypom_contract, drop_id, alice.id()).await?);
+    }
+
+    let receipts = get_claims(
+        &keypom_contract,
+        "get_claims_for_account",
+        json!({"account_id": alice.id()}),
+    )
+    .await?;
+    assert_eq!(receipts.len(), 2);
+    assert!(receipts.iter().all(|r| r["drop_id"] == "recorded_drop"));
+
+    println!("      Passed ✅ test_claim_receipts");
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use crate::*;
+
+/// Create a drop with a single key whose only use sends 0.1 $NEAR with the given use config
+async fn create_rules_drop(
+    funder: &Account,
+    keypom_contract: &Contract,
+    drop_id: &str,
+    config: Value,
+) -> anyhow::Result<SecretKey> {
+    let key = generate_keypairs(1).remove(0);
+    let mut use_data = near_asset_data(1, NearToken::from_millinear(100));
+    use_data["config"] = config;
+    create_drop(
+        funder,
+        keypom_contract,
+        drop_id,
+        vec![key.public_key()],
+        json!([use_data]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+    Ok(key)
+}
+
+/// Uses can restrict who receives their assets through allowlists, denylists, account suffixes and external membership checks.
+/// Accounts that fail a membership check leave the use on the key for someone eligible
+pub async fn test_claimer_rules(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let mock_contract = deploy_mock_contract(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+    let carol = worker.dev_create_account().await?;
+
+    // Accounts must be on the allowlist and not on the denylist
+    let key = create_rules_drop(
+        &funder,
+        &keypom_contract,
+        "listed_drop",
+        json!({"claimer_rules": {
+            "allowlist": [alice.id(), bob.id()],
+            "denylist": [bob.id()]
+        }}),
+    )
+    .await?;
+    let res = claim_with_key(&relayer, &keypom_contract, &key, carol.id()).await?;
+    assert_failed_with(
+        res,
+        &format!(
+            "Account {} is not on the allowlist for this use",
+            carol.id()
+        ),
+    );
+    let res = claim_with_key(&relayer, &keypom_contract, &key, bob.id()).await?;
+    assert_failed_with(
+        res,
+        &format!("Account {} is on the denylist for this use", bob.id()),
+    );
+    let res = claim_with_key(&relayer, &keypom_contract, &key, alice.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+
+    // Suffixes must include the leading period so that `sweat` can't match an account like `notsweat`
+    let res = create_rules_drop(
+        &funder,
+        &keypom_contract,
+        "bare_suffix_drop",
+        json!({"claimer_rules": {"allowed_suffixes": ["sweat"]}}),
+    )
+    .await;
+    let error = res.expect_err("Suffix without a period should be rejected");
+    assert!(format!("{error:?}").contains("Allowed suffix sweat must start with a period"));
+
+    // Only sub-accounts of the mock contract can receive the assets
+    let key = create_rules_drop(
+        &funder,
+        &keypom_contract,
+        "suffix_drop",
+        json!({
+            "root_account_id": mock_contract.id(),
+            "claimer_rules": {"allowed_suffixes": [format!(".{}", mock_contract.id())]}
+        }),
+    )
+    .await?;
+    let res = claim_with_key(&relayer, &keypom_contract, &key, alice.id()).await?;
+    assert_failed_with(res, "must end with one of");
+    let new_account_id: AccountId = format!("dave.{}", mock_contract.id()).parse()?;
+    let new_account_sk = generate_keypairs(1).remove(0);
+    let res = create_account_and_claim_with_key(
+        &relayer,
+        &keypom_contract,
+        &key,
+        &new_account_id,
+        &new_account_sk.public_key(),
+    )
+    .await?;
+    assert!(res.into_result()?.json::<bool>()?);
+
+    // Non-members don't get anything and the use stays on the key
+    let key = create_rules_drop(
+        &funder,
+        &keypom_contract,
+        "members_drop",
+        json!({"claimer_rules": {"membership_check": {"contract_id": mock_contract.id()}}}),
+    )
+    .await?;
+    let initial_near = carol.view_account().await?.balance;
+    let res = claim_with_key(&relayer, &keypom_contract, &key, carol.id()).await?;
+    assert!(!res.into_result()?.json::<bool>()?);
+    assert_eq!(carol.view_account().await?.balance, initial_near);
+    let key_info = get_key_info(&keypom_contract, key.public_key(), true)
+        .await?
+        .unwrap();
+    assert_eq!(key_info["uses_remaining"], 1);
+
+    mock_contract
+        .call("add_member")
+        .args_json(json!({"account_id": carol.id()}))
+        .transact()
+        .await?
+        .into_result()?;
+    let res = claim_with_key(&relayer, &keypom_contract, &key, carol.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(
+        carol.view_account().await?.balance.as_yoctonear() - initial_near.as_yoctonear(),
+        NearToken::from_millinear(100).as_yoctonear()
+    );
+    get_key_info(&keypom_contract, key.public_key(), false).await?;
+
+    // A membership check that fails outright is treated the same as not being a member
+    let key = create_rules_drop(
+        &funder,
+        &keypom_contract,
+        "broken_check_drop",
+        json!({"claimer_rules": {"membership_check": {
+            "contract_id": mock_contract.id(),
+            "method_name": "missing_method"
+        }}}),
+    )
+    .await?;
+    let res = claim_with_key(&relayer, &keypom_contract, &key, carol.id()).await?;
+    assert!(!res.into_result()?.json::<bool>()?);
+    get_key_info(&keypom_contract, key.public_key(), true).await?;
+
+    println!("      Passed ✅ test_claimer_rules");
+    Ok(())
+}
@@ -0,0 +1,336 @@
+use crate::*;
+
+async fn update_drop_config(
+    account: &Account,
+    keypom_contract: &Contract,
+    args: Value,
+) -> anyhow::Result<near_workspaces::result::ExecutionFinalResult> {
+    Ok(account
+        .call(keypom_contract.id(), "update_drop_config")
+        .args_json(args)
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await?)
+}
+
+/// Funders can change the drop config and use configs after the drop has been created
+pub async fn test_update_drop_config(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+
+    let keys = generate_keypairs(1);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "editable_drop",
+        vec![keys[0].public_key()],
+        json!([near_asset_data(2, NearToken::from_millinear(100))]),
+        json!({"metadata": "before"}),
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    // Only the funder can update the config
+    let res = update_drop_config(
+        &claimer,
+        &keypom_contract,
+        json!({"drop_id": "editable_drop", "drop_config": {"metadata": "after"}}),
+    )
+    .await?;
+    assert_failed_with(res, "Only drop funder can update the drop config");
+
+    // Fields that aren't passed in keep their current value
+    update_drop_config(
+        &funder,
+        &keypom_contract,
+        json!({"drop_id": "editable_drop", "drop_config": {"delete_empty_drop": false}}),
+    )
+    .await?
+    .into_result()?;
+    let drop_config =
+        get_drop_info(&keypom_contract, "editable_drop").await?["drop_config"].clone();
+    assert_eq!(drop_config["metadata"], "before");
+    assert_eq!(drop_config["delete_empty_drop"], false);
+
+    // Fields that are passed in as null are cleared
+    update_drop_config(
+        &funder,
+        &keypom_contract,
+        json!({"drop_id": "editable_drop", "drop_config": {"metadata": null}}),
+    )
+    .await?
+    .into_result()?;
+    let drop_config =
+        get_drop_info(&keypom_contract, "editable_drop").await?["drop_config"].clone();
+    assert!(drop_config["metadata"].is_null());
+    assert_eq!(drop_config["delete_empty_drop"], false);
+
+    // Use configs can be updated to restrict how keys are claimed
+    update_drop_config(
+        &funder,
+        &keypom_contract,
+        json!({
+            "drop_id": "editable_drop",
+            "use_configs": {"1": {"permissions": "create_account_and_claim"}}
+        }),
+    )
+    .await?
+    .into_result()?;
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert_failed_with(
+        res,
+        "Cannot call `claim` when key permission is set to only create_account_and_claim",
+    );
+
+    // Uses that don't exist and invalid time configs are rejected
+    let res = update_drop_config(
+        &funder,
+        &keypom_contract,
+        json!({"drop_id": "editable_drop", "use_configs": {"3": {"permissions": "claim"}}}),
+    )
+    .await?;
+    assert_failed_with(res, "Use 3 does not exist. The drop has 2 uses");
+    let res = update_drop_config(
+        &funder,
+        &keypom_contract,
+        json!({"drop_id": "editable_drop", "use_configs": {"1": {"time": {"start": 1}}}}),
+    )
+    .await?;
+    assert_failed_with(
+        res,
+        "The start timestamp must be greater than the current block timestamp",
+    );
+
+    // Lifting the restriction lets the key be claimed again
+    update_drop_config(
+        &funder,
+        &keypom_contract,
+        json!({"drop_id": "editable_drop", "use_configs": {"1": {"permissions": "claim"}}}),
+    )
+    .await?
+    .into_result()?;
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+
+    println!("      Passed ✅ test_update_drop_config");
+    Ok(())
+}
+
+/// Create a drop expecting it to fail with `message`. The drop shouldn't exist afterwards
+async fn assert_drop_rejected(
+    funder: &Account,
+    keypom_contract: &Contract,
+    asset_data: Value,
+    message: &str,
+) -> anyhow::Result<()> {
+    let keys = generate_keypairs(1);
+    let res = create_drop(
+        funder,
+        keypom_contract,
+        "rejected_drop",
+        vec![keys[0].public_key()],
+        asset_data,
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await;
+    let error = res.expect_err(message);
+    assert!(
+        format!("{error:?}").contains(message),
+        "Expected error containing {message:?}, got {error:?}"
+    );
+    assert!(get_drop_info(keypom_contract, "rejected_drop")
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+/// Invalid time configs and assets are rejected before the drop is created
+pub async fn test_asset_data_validation(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+    let one_near_use = near_asset_data(1, NearToken::from_millinear(100));
+
+    let mut past_start = one_near_use.clone();
+    past_start["config"] = json!({"time": {"start": 1}});
+    assert_drop_rejected(
+        &funder,
+        &keypom_contract,
+        json!([past_start]),
+        "The start timestamp must be greater than the current block timestamp",
+    )
+    .await?;
+
+    // Validation runs before any storage is written or charged for, so an invalid drop with no deposit
+    // fails on its config rather than on the funder's balance
+    let keys = generate_keypairs(1);
+    let res = create_drop(
+        &funder,
+        &keypom_contract,
+        "rejected_drop",
+        vec![keys[0].public_key()],
+        json!([past_start]),
+        Value::Null,
+        NearToken::from_yoctonear(0),
+    )
+    .await;
+    let error = format!("{:?}", res.expect_err("Drop with no deposit should be rejected"));
+    assert!(error.contains("The start timestamp must be greater than the current block timestamp"));
+    assert!(!error.contains("is less than required"), "{error}");
+
+    let now = worker.view_block().await?.timestamp();
+    let mut interval_without_start = one_near_use.clone();
+    interval_without_start["config"] = json!({"time": {"interval": 1_000_000_000u64}});
+    assert_drop_rejected(
+        &funder,
+        &keypom_contract,
+        json!([interval_without_start]),
+        "If you want to set a claim interval, you must also set a start timestamp",
+    )
+    .await?;
+
+    let mut start_after_end = one_near_use.clone();
+    start_after_end["config"] =
+        json!({"time": {"start": now + 20_000_000_000_000u64, "end": now + 10_000_000_000_000u64}});
+    assert_drop_rejected(
+        &funder,
+        &keypom_contract,
+        json!([start_after_end]),
+        "The start timestamp must be less than the end timestamp",
+    )
+    .await?;
+
+    // Only the invalid asset data entry needs to be wrong for the drop to be rejected
+    assert_drop_rejected(
+        &funder,
+        &keypom_contract,
+        json!([
+            one_near_use,
+            near_asset_data(0, NearToken::from_millinear(100))
+        ]),
+        "Asset data at index 1 must apply to at least 1 use",
+    )
+    .await?;
+    assert_drop_rejected(
+        &funder,
+        &keypom_contract,
+        json!([{"uses": 1, "assets": [{"yoctonear": "0"}]}]),
+        "NEAR asset at index 0 must transfer more than 0 yoctoNEAR",
+    )
+    .await?;
+
+    // Valid time configs are enforced once the drop is created
+    let keys = generate_keypairs(1);
+    let mut future_start = one_near_use.clone();
+    future_start["config"] = json!({"time": {"start": now + 10_000_000_000u64}});
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "timed_drop",
+        vec![keys[0].public_key()],
+        json!([future_start]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert_failed_with(res, "Key isn't claimable until");
+
+    tokio::time::sleep(std::time::Duration::from_secs(15)).await;
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+
+    println!("      Passed ✅ test_asset_data_validation");
+    Ok(())
+}
+
+async fn get_required_gas_for_use(
+    keypom_contract: &Contract,
+    drop_id: &str,
+    use_number: u32,
+) -> anyhow::Result<Value> {
+    Ok(keypom_contract
+        .view("get_required_gas_for_use")
+        .args_json(json!({"drop_id": drop_id, "use_number": use_number}))
+        .await?
+        .json::<Value>()?)
+}
+
+/// Drops that could never be claimed within 300 TGas are rejected and the gas per use can be queried
+pub async fn test_gas_limits(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+
+    // A single method that fits within `claim` but not `create_account_and_claim`
+    let heavy_fc_asset = json!([{
+        "receiver_id": claimer.id(),
+        "method_name": "ping",
+        "args": "{}",
+        "attached_deposit": "0",
+        "attached_gas": Gas::from_tgas(250).as_gas().to_string()
+    }]);
+
+    // By default keys can be claimed with either method so both must fit
+    assert_drop_rejected(
+        &funder,
+        &keypom_contract,
+        json!([{"uses": 1, "assets": [heavy_fc_asset]}]),
+        "Calling create_account_and_claim requires",
+    )
+    .await?;
+
+    let keys = generate_keypairs(1);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "heavy_drop",
+        vec![keys[0].public_key()],
+        json!([
+            {"uses": 1, "assets": [heavy_fc_asset], "config": {"permissions": "claim"}},
+            near_asset_data(1, NearToken::from_millinear(100))
+        ]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    let heavy_use = get_required_gas_for_use(&keypom_contract, "heavy_drop", 1).await?;
+    assert!(heavy_use["create_account_and_claim"].is_null());
+    let claim_gas: u64 = heavy_use["claim"].as_str().unwrap().parse()?;
+    assert!(claim_gas > Gas::from_tgas(250).as_gas() && claim_gas <= Gas::from_tgas(300).as_gas());
+
+    // 30 TGas of base claim logic plus 9 TGas for the $NEAR transfer. Creating an account adds another 33 TGas
+    let near_use = get_required_gas_for_use(&keypom_contract, "heavy_drop", 2).await?;
+    assert_eq!(near_use["claim"], Gas::from_tgas(39).as_gas().to_string());
+    assert_eq!(
+        near_use["create_account_and_claim"],
+        Gas::from_tgas(72).as_gas().to_string()
+    );
+    assert!(get_required_gas_for_use(&keypom_contract, "heavy_drop", 3)
+        .await
+        .is_err());
+
+    // Updating the permissions can't make the use unclaimable either
+    let res = update_drop_config(
+        &funder,
+        &keypom_contract,
+        json!({"drop_id": "heavy_drop", "use_configs": {"1": {"permissions": "create_account_and_claim"}}}),
+    )
+    .await?;
+    assert_failed_with(res, "Calling create_account_and_claim requires");
+
+    // The restricted use can still be claimed within the gas limit. The claimer has no `ping` method
+    // So the FC asset itself fails but the use is consumed and the next one can be claimed
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(!res.into_result()?.json::<bool>()?);
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    get_key_info(&keypom_contract, keys[0].public_key(), false).await?;
+
+    println!("      Passed ✅ test_gas_limits");
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use crate::*;
+
+/// A method on the mock contract for FC assets with 20 TGas attached. `extra` is merged into the method data
+fn fc_method(
+    mock_contract: &Contract,
+    method_name: &str,
+    args: Value,
+    deposit: NearToken,
+    extra: Value,
+) -> Value {
+    let mut method = json!({
+        "receiver_id": mock_contract.id(),
+        "method_name": method_name,
+        "args": args.to_string(),
+        "attached_deposit": deposit.as_yoctonear().to_string(),
+        "attached_gas": Gas::from_tgas(20).as_gas().to_string()
+    });
+    if let Value::Object(extra) = extra {
+        method.as_object_mut().unwrap().extend(extra);
+    }
+    method
+}
+
+/// Raw args of every method the mock contract has recorded
+async fn get_fc_calls(mock_contract: &Contract) -> anyhow::Result<Vec<String>> {
+    Ok(mock_contract
+        .view("get_fc_calls")
+        .await?
+        .json::<Vec<String>>()?)
+}
+
+/// Methods with `halt_on_failure` stop the rest of the FC asset from running and refund the skipped deposits to the funder
+pub async fn test_fc_halt_on_failure(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let mock_contract = deploy_mock_contract(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+
+    // A failing method with 1 $NEAR followed by a method with 2 $NEAR. Only the first drop halts on failure
+    let fc_asset = |halt_on_failure: bool| {
+        json!([
+            fc_method(
+                &mock_contract,
+                "fc_fail",
+                json!({}),
+                NearToken::from_near(1),
+                json!({ "halt_on_failure": halt_on_failure })
+            ),
+            fc_method(
+                &mock_contract,
+                "fc_record",
+                json!({}),
+                NearToken::from_near(2),
+                Value::Null
+            )
+        ])
+    };
+    let keys = generate_keypairs(2);
+    for (drop_id, key, halt_on_failure) in [
+        ("halt_drop", &keys[0], true),
+        ("no_halt_drop", &keys[1], false),
+    ] {
+        create_drop(
+            &funder,
+            &keypom_contract,
+            drop_id,
+            vec![key.public_key()],
+            json!([{"uses": 1, "assets": [fc_asset(halt_on_failure)]}]),
+            Value::Null,
+            NearToken::from_near(5),
+        )
+        .await?;
+    }
+    assert_eq!(get_user_balance(&keypom_contract, funder.id()).await?, 0);
+
+    // The second method is never called, both deposits are credited back to the funder and the claim counts as failed
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(logged_claim_failed(&res, false));
+    assert!(!res.into_result()?.json::<bool>()?);
+    assert!(get_fc_calls(&mock_contract).await?.is_empty());
+    assert_eq!(
+        get_user_balance(&keypom_contract, funder.id()).await?,
+        NearToken::from_near(3).as_yoctonear()
+    );
+    get_key_info(&keypom_contract, keys[0].public_key(), false).await?;
+
+    // Without it, the chain keeps going, nothing is refunded and the claim succeeds since the last method did
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[1], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(get_fc_calls(&mock_contract).await?.len(), 1);
+    assert_eq!(
+        get_user_balance(&keypom_contract, funder.id()).await?,
+        NearToken::from_near(3).as_yoctonear()
+    );
+
+    println!("      Passed ✅ test_fc_halt_on_failure");
+    Ok(())
+}
+
+/// Return values of FC methods can be inserted into the args of the next method. If the value isn't available,
+/// The dependent method is skipped and its deposit is refunded to the funder
+pub async fn test_fc_result_piping(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let mock_contract = deploy_mock_contract(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+
+    let record_ticket = fc_method(
+        &mock_contract,
+        "fc_record",
+        json!({"event": "launch"}),
+        NearToken::from_near(1),
+        json!({"keypom_args": {
+            "previous_result_field": "ticket_id",
+            "previous_result_path": "tokens.0.token_id"
+        }}),
+    );
+
+    // There's no previous result for the first method
+    let keys = generate_keypairs(1);
+    let error = create_drop(
+        &funder,
+        &keypom_contract,
+        "invalid_drop",
+        vec![keys[0].public_key()],
+        json!([{"uses": 1, "assets": [[record_ticket]]}]),
+        Value::Null,
+        NearToken::from_near(5),
+    )
+    .await
+    .expect_err("First FC method cannot use the previous result");
+    assert!(format!("{error:?}").contains("First FC method cannot use the previous result"));
+
+    // The first use mints a ticket and records it. The second use fails to mint so nothing is recorded
+    let mint_ticket = fc_method(
+        &mock_contract,
+        "fc_return",
+        json!({"value": {"tokens": [{"token_id": "ticket-1"}]}}),
+        NearToken::from_near(0),
+        Value::Null,
+    );
+    let failed_mint = fc_method(
+        &mock_contract,
+        "fc_fail",
+        json!({}),
+        NearToken::from_near(0),
+        Value::Null,
+    );
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "ticket_drop",
+        vec![keys[0].public_key()],
+        json!([
+            {"uses": 1, "assets": [[mint_ticket, record_ticket]]},
+            {"uses": 1, "assets": [[failed_mint, record_ticket]]}
+        ]),
+        Value::Null,
+        NearToken::from_near(5),
+    )
+    .await?;
+
+    claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id())
+        .await?
+        .into_result()?;
+    let fc_calls = get_fc_calls(&mock_contract).await?;
+    assert_eq!(fc_calls.len(), 2);
+    let recorded_args: Value = serde_json::from_str(&fc_calls[1])?;
+    assert_eq!(recorded_args["event"], "launch");
+    assert_eq!(recorded_args["ticket_id"], "ticket-1");
+    assert_eq!(get_user_balance(&keypom_contract, funder.id()).await?, 0);
+
+    claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id())
+        .await?
+        .into_result()?;
+    assert_eq!(get_fc_calls(&mock_contract).await?.len(), 2);
+    assert_eq!(
+        get_user_balance(&keypom_contract, funder.id()).await?,
+        NearToken::from_near(1).as_yoctonear()
+    );
+
+    println!("      Passed ✅ test_fc_result_piping");
+    Ok(())
+}
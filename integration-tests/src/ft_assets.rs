@@ -0,0 +1,395 @@
+use crate::wnear_assets::{ft_balance_of, is_registered};
+use crate::*;
+
+/// Storage cost charged by the mock FT contract for registering an account
+const FT_STORAGE_COST: NearToken = NearToken::from_yoctonear(1_250_000_000_000_000_000_000);
+
+pub async fn ft_mint(
+    mock_contract: &Contract,
+    account_id: &AccountId,
+    amount: u128,
+) -> anyhow::Result<()> {
+    mock_contract
+        .call("ft_mint")
+        .args_json(json!({"account_id": account_id, "amount": amount.to_string()}))
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+/// Register an account on the mock FT contract, paid for by `payer`
+pub async fn ft_register(
+    payer: &Account,
+    mock_contract: &Contract,
+    account_id: &AccountId,
+) -> anyhow::Result<()> {
+    payer
+        .call(mock_contract.id(), "storage_deposit")
+        .args_json(json!({ "account_id": account_id }))
+        .deposit(FT_STORAGE_COST)
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+/// Send fungible tokens to Keypom with the given message. Any refunds are processed by the mock contract
+pub async fn ft_deposit(
+    sender: &Account,
+    mock_contract: &Contract,
+    keypom_contract: &Contract,
+    amount: u128,
+    msg: Value,
+) -> anyhow::Result<()> {
+    let msg = match msg {
+        Value::String(drop_id) => drop_id,
+        msg => msg.to_string(),
+    };
+    sender
+        .call(mock_contract.id(), "ft_transfer_call")
+        .args_json(json!({
+            "receiver_id": keypom_contract.id(),
+            "amount": amount.to_string(),
+            "msg": msg
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+/// Asset data for a set of uses that each transfer `ft_amount` tokens from the mock FT contract
+fn ft_asset_data(
+    mock_contract: &Contract,
+    uses: u32,
+    ft_amount: u128,
+    registration_policy: Value,
+) -> Value {
+    json!({
+        "uses": uses,
+        "assets": [{
+            "ft_contract_id": mock_contract.id(),
+            "registration_cost": FT_STORAGE_COST.as_yoctonear().to_string(),
+            "ft_amount": ft_amount.to_string(),
+            "ft_registration_policy": registration_policy
+        }]
+    })
+}
+
+/// FT deposits accept bare drop IDs or JSON messages, are only kept from the funder or allowlisted accounts
+/// And anything the drop doesn't need is returned
+pub async fn test_ft_deposit_messages(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let ft_contract = deploy_mock_contract(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let depositor = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+
+    for account in [&funder, &depositor, &claimer] {
+        ft_mint(&ft_contract, account.id(), 100).await?;
+    }
+    ft_register(&funder, &ft_contract, keypom_contract.id()).await?;
+
+    // 2 keys with 1 use that each transfer 10 tokens. The depositor can top up the drop as well
+    let keys = generate_keypairs(2);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "ft_drop",
+        keys.iter().map(|sk| sk.public_key()).collect(),
+        json!([ft_asset_data(&ft_contract, 1, 10, Value::Null)]),
+        json!({"deposit_allowlist": [depositor.id()]}),
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    // Accounts that aren't the funder or on the allowlist have their tokens returned
+    ft_deposit(
+        &claimer,
+        &ft_contract,
+        &keypom_contract,
+        10,
+        json!("ft_drop"),
+    )
+    .await?;
+    assert_eq!(ft_balance_of(&ft_contract, claimer.id()).await?, 100);
+    assert_eq!(ft_balance_of(&ft_contract, keypom_contract.id()).await?, 0);
+
+    // Allowlisted accounts can deposit using a JSON message
+    ft_deposit(
+        &depositor,
+        &ft_contract,
+        &keypom_contract,
+        5,
+        json!({"drop_id": "ft_drop"}),
+    )
+    .await?;
+    assert_eq!(ft_balance_of(&ft_contract, depositor.id()).await?, 95);
+    assert_eq!(ft_balance_of(&ft_contract, keypom_contract.id()).await?, 5);
+
+    // FT balances are shared by every key so deposits targeting a key or use are rejected and returned.
+    // Deposits for drops that don't exist are rejected as well
+    for msg in [
+        json!({"drop_id": "ft_drop", "key_id": 0}),
+        json!({"drop_id": "ft_drop", "use_number": 1}),
+        json!("missing_drop"),
+    ] {
+        ft_deposit(&funder, &ft_contract, &keypom_contract, 5, msg).await?;
+        assert_eq!(ft_balance_of(&ft_contract, funder.id()).await?, 100);
+    }
+    assert_eq!(ft_balance_of(&ft_contract, keypom_contract.id()).await?, 5);
+
+    // The funder can still use the bare drop ID. The drop only needs 15 more tokens so the rest is returned
+    ft_deposit(
+        &funder,
+        &ft_contract,
+        &keypom_contract,
+        25,
+        json!("ft_drop"),
+    )
+    .await?;
+    assert_eq!(ft_balance_of(&ft_contract, funder.id()).await?, 85);
+    assert_eq!(ft_balance_of(&ft_contract, keypom_contract.id()).await?, 20);
+
+    // Unless the funder opts out of overflow refunds
+    ft_deposit(
+        &funder,
+        &ft_contract,
+        &keypom_contract,
+        5,
+        json!({"drop_id": "ft_drop", "refund_overflow": false}),
+    )
+    .await?;
+    assert_eq!(ft_balance_of(&ft_contract, funder.id()).await?, 80);
+    assert_eq!(ft_balance_of(&ft_contract, keypom_contract.id()).await?, 25);
+
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(ft_balance_of(&ft_contract, claimer.id()).await?, 110);
+
+    println!("      Passed ✅ test_ft_deposit_messages");
+    Ok(())
+}
+
+/// What the drop still needs goes down as keys are claimed and deposits for drops without a matching FT asset are returned
+pub async fn test_ft_overflow_refunds(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let ft_contract = deploy_mock_contract(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+
+    ft_mint(&ft_contract, funder.id(), 100).await?;
+    ft_register(&funder, &ft_contract, keypom_contract.id()).await?;
+
+    // 2 keys with 2 uses that each transfer 10 tokens so the drop needs 40 tokens in total
+    let keys = generate_keypairs(2);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "ft_drop",
+        keys.iter().map(|sk| sk.public_key()).collect(),
+        json!([ft_asset_data(&ft_contract, 2, 10, Value::Null)]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    ft_deposit(
+        &funder,
+        &ft_contract,
+        &keypom_contract,
+        50,
+        json!("ft_drop"),
+    )
+    .await?;
+    assert_eq!(ft_balance_of(&ft_contract, keypom_contract.id()).await?, 40);
+    assert_eq!(ft_balance_of(&ft_contract, funder.id()).await?, 60);
+
+    // Claiming lowers what the drop needs along with its balance so it stays full
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    ft_deposit(&funder, &ft_contract, &keypom_contract, 5, json!("ft_drop")).await?;
+    assert_eq!(ft_balance_of(&ft_contract, keypom_contract.id()).await?, 30);
+    assert_eq!(ft_balance_of(&ft_contract, funder.id()).await?, 60);
+
+    // Drops without an asset for this FT contract return the entire deposit
+    let keys = generate_keypairs(1);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "near_drop",
+        vec![keys[0].public_key()],
+        json!([near_asset_data(1, NearToken::from_millinear(100))]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+    ft_deposit(
+        &funder,
+        &ft_contract,
+        &keypom_contract,
+        10,
+        json!("near_drop"),
+    )
+    .await?;
+    assert_eq!(ft_balance_of(&ft_contract, keypom_contract.id()).await?, 30);
+    assert_eq!(ft_balance_of(&ft_contract, funder.id()).await?, 60);
+
+    println!("      Passed ✅ test_ft_overflow_refunds");
+    Ok(())
+}
+
+/// Claimers are only registered on the FT contract when the drop's registration policy calls for it.
+/// Registration costs that end up unused are credited back to the funder's balance
+pub async fn test_ft_registration_policies(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let ft_contract = deploy_mock_contract(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+    let unregistered_claimer = worker.dev_create_account().await?;
+
+    ft_mint(&ft_contract, funder.id(), 100).await?;
+    ft_register(&funder, &ft_contract, keypom_contract.id()).await?;
+
+    // By default, the claimer is only registered if they need to be
+    let keys = generate_keypairs(3);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "when_needed_drop",
+        keys.iter().map(|sk| sk.public_key()).collect(),
+        json!([ft_asset_data(&ft_contract, 1, 10, Value::Null)]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+    ft_deposit(
+        &funder,
+        &ft_contract,
+        &keypom_contract,
+        30,
+        json!("when_needed_drop"),
+    )
+    .await?;
+
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert!(is_registered(&ft_contract, claimer.id()).await?);
+    assert_eq!(ft_balance_of(&ft_contract, claimer.id()).await?, 10);
+    assert_eq!(get_user_balance(&keypom_contract, funder.id()).await?, 0);
+
+    // Now that they're registered, the registration cost is credited back to the funder
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[1], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(ft_balance_of(&ft_contract, claimer.id()).await?, 20);
+    assert_eq!(
+        get_user_balance(&keypom_contract, funder.id()).await?,
+        FT_STORAGE_COST.as_yoctonear()
+    );
+
+    // If the transfer fails, the registration cost is refunded once and the tokens stay with the drop
+    set_transfers_paused(&ft_contract, true).await?;
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[2], claimer.id()).await?;
+    assert!(!res.into_result()?.json::<bool>()?);
+    set_transfers_paused(&ft_contract, false).await?;
+    assert_eq!(ft_balance_of(&ft_contract, claimer.id()).await?, 20);
+    assert_eq!(ft_balance_of(&ft_contract, keypom_contract.id()).await?, 10);
+    assert_eq!(
+        get_user_balance(&keypom_contract, funder.id()).await?,
+        2 * FT_STORAGE_COST.as_yoctonear()
+    );
+
+    // Drops can require claimers to already be registered. The funder is never charged for registration
+    let keys = generate_keypairs(2);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "claimer_registered_drop",
+        keys.iter().map(|sk| sk.public_key()).collect(),
+        json!([ft_asset_data(
+            &ft_contract,
+            1,
+            10,
+            json!("claimer_registered")
+        )]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+    ft_deposit(
+        &funder,
+        &ft_contract,
+        &keypom_contract,
+        20,
+        json!("claimer_registered_drop"),
+    )
+    .await?;
+
+    let res = claim_with_key(
+        &relayer,
+        &keypom_contract,
+        &keys[0],
+        unregistered_claimer.id(),
+    )
+    .await?;
+    assert!(!res.into_result()?.json::<bool>()?);
+    assert!(!is_registered(&ft_contract, unregistered_claimer.id()).await?);
+    assert_eq!(ft_balance_of(&ft_contract, keypom_contract.id()).await?, 30);
+    assert_eq!(
+        get_user_balance(&keypom_contract, funder.id()).await?,
+        2 * FT_STORAGE_COST.as_yoctonear()
+    );
+
+    ft_register(
+        &unregistered_claimer,
+        &ft_contract,
+        unregistered_claimer.id(),
+    )
+    .await?;
+    let res = claim_with_key(
+        &relayer,
+        &keypom_contract,
+        &keys[1],
+        unregistered_claimer.id(),
+    )
+    .await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(
+        ft_balance_of(&ft_contract, unregistered_claimer.id()).await?,
+        10
+    );
+
+    // Drops that always register the claimer don't credit anything back, even for registered claimers
+    let keys = generate_keypairs(1);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "always_drop",
+        vec![keys[0].public_key()],
+        json!([ft_asset_data(&ft_contract, 1, 10, json!("always"))]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+    ft_deposit(
+        &funder,
+        &ft_contract,
+        &keypom_contract,
+        10,
+        json!("always_drop"),
+    )
+    .await?;
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(ft_balance_of(&ft_contract, claimer.id()).await?, 30);
+    assert_eq!(
+        get_user_balance(&keypom_contract, funder.id()).await?,
+        2 * FT_STORAGE_COST.as_yoctonear()
+    );
+
+    println!("      Passed ✅ test_ft_registration_policies");
+    Ok(())
+}
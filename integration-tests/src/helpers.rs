@@ -1,10 +1,78 @@
 use crate::*;
 use models::*;
-use near_workspaces::types::{KeyType, PublicKey, SecretKey};
-use near_workspaces::Contract;
+use near_workspaces::result::ExecutionFinalResult;
 
-pub fn sign_kp_message(sk: &near_crypto::SecretKey, nonce: u32, message: &String) -> Base64VecU8 {
-    let signature = match sk.sign(&format!("{}{}", message, nonce).as_bytes()) {
+/// Deploy and initialize a fresh Keypom contract owned by the sandbox's root account.
+/// Returns the contract along with an account that signs transactions for the contract using a registered
+/// Signing key (i.e what a relayer would use to submit claims).
+pub async fn deploy_keypom(worker: &Worker<Sandbox>) -> anyhow::Result<(Contract, Account)> {
+    let keypom_wasm = match std::fs::read(KEYPOM_WASM_PATH) {
+        Ok(content) => content,
+        Err(err) => {
+            eprintln!("Error reading wasm file: {}", err);
+            std::process::exit(1);
+        }
+    };
+    let keypom_contract = worker.dev_deploy(&keypom_wasm).await?;
+    let owner = worker.root_account()?;
+
+    let signing_sk = SecretKey::from_random(KeyType::ED25519);
+    keypom_contract
+        .call("new")
+        .args_json(json!({
+            "root_account": owner.id(),
+            "owner_id": owner.id(),
+            "signing_pks": [signing_sk.public_key()],
+            "signing_admins": [owner.id()],
+            "chain_id": CHAIN_ID
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let mut relayer = keypom_contract.as_account().clone();
+    relayer.set_secret_key(signing_sk);
+    Ok((keypom_contract, relayer))
+}
+
+/// Deploy and initialize the mock token contract that Keypom interacts with for FT, NFT, MT and FC assets
+pub async fn deploy_mock_contract(worker: &Worker<Sandbox>) -> anyhow::Result<Contract> {
+    let mock_wasm = std::fs::read(MOCK_CONTRACT_WASM_PATH)?;
+    let mock_contract = worker.dev_deploy(&mock_wasm).await?;
+    mock_contract
+        .call("new")
+        .args_json(json!({}))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(mock_contract)
+}
+
+/// Sign the payload that authorizes a call to `method_name` with the given arguments for a linkdrop key
+pub fn sign_kp_payload(
+    sk: &SecretKey,
+    chain_id: &str,
+    contract_id: &AccountId,
+    method_name: &str,
+    args: &Value,
+    nonce: u32,
+    valid_until: u64,
+) -> Base64VecU8 {
+    let payload = SignedKeyPayload {
+        chain_id: chain_id.to_string(),
+        contract_id: contract_id.to_string(),
+        method_name: method_name.to_string(),
+        args: args.to_string(),
+        nonce,
+        valid_until,
+    };
+    let mut bytes = near_sdk::borsh::to_vec(&KEYPOM_SIGNATURE_TAG).unwrap();
+    bytes.extend(near_sdk::borsh::to_vec(&payload).unwrap());
+    let hash = near_sdk::env::sha256(&bytes);
+
+    let signing_key: near_crypto::SecretKey = sk.to_string().parse().unwrap();
+    let signature = match signing_key.sign(&hash) {
         near_crypto::Signature::ED25519(sig) => sig,
         _ => panic!("Invalid signature type"),
     };
@@ -12,39 +80,178 @@ pub fn sign_kp_message(sk: &near_crypto::SecretKey, nonce: u32, message: &String
     Base64VecU8(signature.to_bytes().to_vec())
 }
 
-pub fn verify_kp_signature(
-    pk: &near_crypto::PublicKey,
-    sig: &near_crypto::Signature,
-    nonce: u32,
-    message: &String,
+/// Sign `args` with the linkdrop key's current nonce and call `method_name` on Keypom through the relayer.
+/// Signed arguments should be written in alphabetical order so they stringify the same way as on the contract.
+pub async fn call_with_key(
+    relayer: &Account,
+    keypom_contract: &Contract,
+    sk: &SecretKey,
+    method_name: &str,
+    args: Value,
+) -> anyhow::Result<ExecutionFinalResult> {
+    let key_info = get_key_info(keypom_contract, sk.public_key(), true)
+        .await?
+        .unwrap();
+    let nonce = key_info["message_nonce"].as_u64().unwrap() as u32;
+    let signature = sign_kp_payload(
+        sk,
+        CHAIN_ID,
+        keypom_contract.id(),
+        method_name,
+        &args,
+        nonce,
+        u64::MAX,
+    );
+
+    let mut call_args = args;
+    call_args["signature"] = json!({
+        "signature": signature,
+        "valid_until": u64::MAX.to_string()
+    });
+    Ok(relayer
+        .call(keypom_contract.id(), method_name)
+        .args_json(call_args)
+        .max_gas()
+        .transact()
+        .await?)
+}
+
+/// Claim a linkdrop key to an existing account through the relayer
+pub async fn claim_with_key(
+    relayer: &Account,
+    keypom_contract: &Contract,
+    sk: &SecretKey,
+    account_id: &AccountId,
+) -> anyhow::Result<ExecutionFinalResult> {
+    call_with_key(
+        relayer,
+        keypom_contract,
+        sk,
+        "claim",
+        json!({"account_id": account_id, "linkdrop_pk": sk.public_key()}),
+    )
+    .await
+}
+
+/// Whether the claim logged a `claim_failed` event with the given rollback outcome
+pub fn logged_claim_failed(
+    res: &ExecutionFinalResult,
+    rolled_back: bool,
 ) -> bool {
-    let msg = format!("{}{}", message, nonce);
-    sig.verify(msg.as_bytes(), pk)
+    res.logs().iter().any(|log| {
+        log.contains("\"event\":\"claim_failed\"")
+            && log.contains(&format!("\"rolled_back\":{rolled_back}"))
+    })
 }
 
-pub async fn get_sig_meta(contract: Contract) -> Result<SignatureMeta, anyhow::Error> {
-    let global_sk = contract
-        .view("get_global_secret_key")
+/// Create a new sub-account of the drop's root account and claim a linkdrop key to it through the relayer
+pub async fn create_account_and_claim_with_key(
+    relayer: &Account,
+    keypom_contract: &Contract,
+    sk: &SecretKey,
+    new_account_id: &AccountId,
+    new_public_key: &PublicKey,
+) -> anyhow::Result<ExecutionFinalResult> {
+    call_with_key(
+        relayer,
+        keypom_contract,
+        sk,
+        "create_account_and_claim",
+        json!({
+            "linkdrop_pk": sk.public_key(),
+            "new_account_id": new_account_id,
+            "new_public_key": new_public_key
+        }),
+    )
+    .await
+}
+
+/// Create a drop where every key shares the same asset data and drop config. Fails if the drop couldn't be created
+pub async fn create_drop(
+    funder: &Account,
+    keypom_contract: &Contract,
+    drop_id: &str,
+    public_keys: Vec<PublicKey>,
+    asset_data: Value,
+    drop_config: Value,
+    deposit: NearToken,
+) -> anyhow::Result<()> {
+    let key_data: Vec<Value> = public_keys
+        .into_iter()
+        .map(|pk| json!({ "public_key": pk }))
+        .collect();
+    funder
+        .call(keypom_contract.id(), "create_drop")
+        .args_json(json!({
+            "drop_id": drop_id,
+            "key_data": key_data,
+            "asset_data": asset_data,
+            "drop_config": drop_config
+        }))
+        .deposit(deposit)
+        .max_gas()
+        .transact()
         .await?
-        .json::<String>()?;
-    let message = contract
-        .view("get_signing_message")
+        .into_result()?;
+
+    Ok(())
+}
+
+/// Asset data for a set of uses that each transfer `amount` of $NEAR to the claimer
+pub fn near_asset_data(uses: u32, amount: NearToken) -> Value {
+    json!({
+        "uses": uses,
+        "assets": [{
+            "yoctonear": amount.as_yoctonear().to_string()
+        }]
+    })
+}
+
+/// Deposit $NEAR into the account's Keypom balance (i.e to pay for the storage of deposited NFTs)
+pub async fn add_to_balance(
+    account: &Account,
+    keypom_contract: &Contract,
+    amount: NearToken,
+) -> anyhow::Result<()> {
+    account
+        .call(keypom_contract.id(), "add_to_balance")
+        .deposit(amount)
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+/// How much $NEAR the account has stored in its Keypom balance
+pub async fn get_user_balance(
+    keypom_contract: &Contract,
+    account_id: &AccountId,
+) -> anyhow::Result<u128> {
+    let balance = keypom_contract
+        .view("get_user_balance")
+        .args_json(json!({ "account_id": account_id }))
         .await?
         .json::<String>()?;
+    Ok(balance.parse()?)
+}
 
-    let sk: SecretKey = global_sk.parse().unwrap();
-    Ok(SignatureMeta {
-        message,
-        secret_key: sk,
-    })
+/// Make every plain FT, NFT and MT transfer on the mock contract fail (i.e to simulate failed claims)
+pub async fn set_transfers_paused(mock_contract: &Contract, paused: bool) -> anyhow::Result<()> {
+    mock_contract
+        .call("set_transfers_paused")
+        .args_json(json!({ "paused": paused }))
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
 }
 
-pub async fn get_drop_info(contract: Contract, drop_id: String) -> Result<ExtDrop, anyhow::Error> {
+pub async fn get_drop_info(contract: &Contract, drop_id: &str) -> Result<Value, anyhow::Error> {
     let drop_info = contract
         .view("get_drop_information")
         .args_json(json!({"drop_id": drop_id}))
         .await?
-        .json::<ExtDrop>()?;
+        .json::<Value>()?;
 
     Ok(drop_info)
 }
@@ -53,7 +260,7 @@ pub async fn get_key_info(
     contract: &Contract,
     key: PublicKey,
     should_exist: bool,
-) -> Result<Option<ExtKeyInfo>, anyhow::Error> {
+) -> Result<Option<Value>, anyhow::Error> {
     let key_info = contract
         .view("get_key_information")
         .args_json(json!({"key": key}))
@@ -66,7 +273,16 @@ pub async fn get_key_info(
         assert!(key_info.is_ok())
     }
 
-    Ok(Some(key_info?.json::<ExtKeyInfo>()?))
+    Ok(Some(key_info?.json::<Value>()?))
+}
+
+/// Assert that a transaction failed with an error containing `message`
+pub fn assert_failed_with(result: ExecutionFinalResult, message: &str) {
+    let error = result.into_result().expect_err(message);
+    assert!(
+        format!("{error:?}").contains(message),
+        "Expected error containing {message:?}, got {error:?}"
+    );
 }
 
 pub fn generate_keypairs(num_keys: u16) -> Vec<SecretKey> {
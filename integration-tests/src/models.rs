@@ -10,11 +10,17 @@ use near_sdk::{
 };
 use std::collections::{HashMap, HashSet};
 
-use near_workspaces::types::{KeyType, SecretKey};
-
-pub struct SignatureMeta {
-    pub message: String,
-    pub secret_key: SecretKey,
+/// Payload that a linkdrop key signs to authorize calls such as `claim` and `create_account_and_claim`.
+/// The contract ID is kept as a string since it borsh serializes the same way as an `AccountId`.
+#[derive(BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct SignedKeyPayload {
+    pub chain_id: String,
+    pub contract_id: String,
+    pub method_name: String,
+    pub args: String,
+    pub nonce: u32,
+    pub valid_until: u64,
 }
 
 /// Which specific use is something being acted on. This is not zero indexed (i.e the first use is 1)
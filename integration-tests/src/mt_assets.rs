@@ -0,0 +1,221 @@
+use crate::*;
+
+async fn mt_balance_of(
+    mock_contract: &Contract,
+    account_id: &AccountId,
+    token_id: &str,
+) -> anyhow::Result<u128> {
+    let balance = mock_contract
+        .view("mt_balance_of")
+        .args_json(json!({"account_id": account_id, "token_id": token_id}))
+        .await?
+        .json::<String>()?;
+    Ok(balance.parse()?)
+}
+
+async fn mt_mint(
+    mock_contract: &Contract,
+    account_id: &AccountId,
+    token_id: &str,
+    amount: u128,
+) -> anyhow::Result<()> {
+    mock_contract
+        .call("mt_mint")
+        .args_json(
+            json!({"account_id": account_id, "token_id": token_id, "amount": amount.to_string()}),
+        )
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+/// Send multi tokens to Keypom with the given message. Any refunds are processed by the mock contract
+async fn mt_deposit(
+    sender: &Account,
+    mock_contract: &Contract,
+    keypom_contract: &Contract,
+    tokens: &[(&str, u128)],
+    msg: Value,
+) -> anyhow::Result<()> {
+    let token_ids: Vec<&str> = tokens.iter().map(|(token_id, _)| *token_id).collect();
+    let amounts: Vec<String> = tokens
+        .iter()
+        .map(|(_, amount)| amount.to_string())
+        .collect();
+    let msg = match msg {
+        Value::String(drop_id) => drop_id,
+        msg => msg.to_string(),
+    };
+    sender
+        .call(mock_contract.id(), "mt_batch_transfer_call")
+        .args_json(json!({
+            "receiver_id": keypom_contract.id(),
+            "token_ids": token_ids,
+            "amounts": amounts,
+            "msg": msg
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+/// Multi tokens can be deposited into drops, claimed and withdrawn. Anything the drop doesn't need is returned
+pub async fn test_mt_assets(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let mock_contract = deploy_mock_contract(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+
+    mt_mint(&mock_contract, funder.id(), "gold", 100).await?;
+    mt_mint(&mock_contract, funder.id(), "silver", 5).await?;
+    mt_mint(&mock_contract, claimer.id(), "gold", 10).await?;
+
+    // 2 keys with 1 use that each transfer 10 gold
+    let keys = generate_keypairs(2);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "mt_drop",
+        keys.iter().map(|sk| sk.public_key()).collect(),
+        json!([{
+            "uses": 1,
+            "assets": [{"mt_contract_id": mock_contract.id(), "mt_token_id": "gold", "mt_amount": "10"}]
+        }]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    // Only the funder can deposit. Everything else is returned
+    mt_deposit(
+        &claimer,
+        &mock_contract,
+        &keypom_contract,
+        &[("gold", 10)],
+        json!("mt_drop"),
+    )
+    .await?;
+    assert_eq!(
+        mt_balance_of(&mock_contract, claimer.id(), "gold").await?,
+        10
+    );
+
+    // The drop only needs 20 gold and has no silver asset so the rest is returned
+    mt_deposit(
+        &funder,
+        &mock_contract,
+        &keypom_contract,
+        &[("gold", 30), ("silver", 5)],
+        json!("mt_drop"),
+    )
+    .await?;
+    assert_eq!(
+        mt_balance_of(&mock_contract, keypom_contract.id(), "gold").await?,
+        20
+    );
+    assert_eq!(
+        mt_balance_of(&mock_contract, funder.id(), "gold").await?,
+        80
+    );
+    assert_eq!(
+        mt_balance_of(&mock_contract, funder.id(), "silver").await?,
+        5
+    );
+
+    // MT balances are shared by every key so deposits targeting a key are rejected and returned
+    mt_deposit(
+        &funder,
+        &mock_contract,
+        &keypom_contract,
+        &[("gold", 5)],
+        json!({"drop_id": "mt_drop", "key_id": 0}),
+    )
+    .await?;
+    assert_eq!(
+        mt_balance_of(&mock_contract, funder.id(), "gold").await?,
+        80
+    );
+
+    // Unless the funder opts out of overflow refunds
+    mt_deposit(
+        &funder,
+        &mock_contract,
+        &keypom_contract,
+        &[("gold", 5)],
+        json!({"drop_id": "mt_drop", "refund_overflow": false}),
+    )
+    .await?;
+    assert_eq!(
+        mt_balance_of(&mock_contract, keypom_contract.id(), "gold").await?,
+        25
+    );
+
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(
+        mt_balance_of(&mock_contract, claimer.id(), "gold").await?,
+        20
+    );
+    assert_eq!(
+        mt_balance_of(&mock_contract, keypom_contract.id(), "gold").await?,
+        15
+    );
+
+    // Only the funder can withdraw and only what the drop still holds
+    let withdraw_args = |amount: u128| {
+        json!({
+            "drop_id": "mt_drop",
+            "mt_contract_id": mock_contract.id(),
+            "token_id": "gold",
+            "tokens_to_withdraw": amount.to_string()
+        })
+    };
+    let res = claimer
+        .call(keypom_contract.id(), "withdraw_mt_balance")
+        .args_json(withdraw_args(5))
+        .max_gas()
+        .transact()
+        .await?;
+    assert_failed_with(res, "Only drop funder can withdraw multi tokens");
+    let res = funder
+        .call(keypom_contract.id(), "withdraw_mt_balance")
+        .args_json(withdraw_args(20))
+        .max_gas()
+        .transact()
+        .await?;
+    assert_failed_with(
+        res,
+        "not enough balance to transfer. Found 15 but needed 20",
+    );
+
+    funder
+        .call(keypom_contract.id(), "withdraw_mt_balance")
+        .args_json(withdraw_args(5))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(
+        mt_balance_of(&mock_contract, funder.id(), "gold").await?,
+        85
+    );
+    assert_eq!(
+        mt_balance_of(&mock_contract, keypom_contract.id(), "gold").await?,
+        10
+    );
+
+    // The remaining key can still be claimed
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[1], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(
+        mt_balance_of(&mock_contract, claimer.id(), "gold").await?,
+        30
+    );
+
+    println!("      Passed ✅ test_mt_assets");
+    Ok(())
+}
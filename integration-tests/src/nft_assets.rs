@@ -0,0 +1,461 @@
+use crate::*;
+
+async fn nft_mint(
+    mock_contract: &Contract,
+    token_id: &str,
+    receiver: &Account,
+) -> anyhow::Result<()> {
+    mock_contract
+        .call("nft_mint")
+        .args_json(json!({"token_id": token_id, "receiver_id": receiver.id()}))
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+async fn nft_owner(mock_contract: &Contract, token_id: &str) -> anyhow::Result<AccountId> {
+    let token = mock_contract
+        .view("nft_token")
+        .args_json(json!({ "token_id": token_id }))
+        .await?
+        .json::<Value>()?;
+    Ok(token["owner_id"].as_str().unwrap().parse()?)
+}
+
+/// Mint a token to the sender and send it to Keypom with the given message
+async fn mint_and_deposit(
+    sender: &Account,
+    mock_contract: &Contract,
+    keypom_contract: &Contract,
+    token_id: &str,
+    msg: Value,
+) -> anyhow::Result<()> {
+    nft_mint(mock_contract, token_id, sender).await?;
+    let msg = match msg {
+        Value::String(drop_id) => drop_id,
+        msg => msg.to_string(),
+    };
+    sender
+        .call(mock_contract.id(), "nft_transfer_call")
+        .args_json(json!({"receiver_id": keypom_contract.id(), "token_id": token_id, "msg": msg}))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+/// Token IDs in the drop's shared pool for its first NFT asset
+async fn get_pooled_token_ids(
+    keypom_contract: &Contract,
+    drop_id: &str,
+) -> anyhow::Result<Vec<Value>> {
+    let drop_info = get_drop_info(keypom_contract, drop_id).await?;
+    Ok(drop_info["nft_asset_data"][0]["token_ids"]
+        .as_array()
+        .unwrap()
+        .clone())
+}
+
+/// Create a drop with `num_keys` 1 use keys that each transfer an NFT using the given distribution mode
+async fn create_nft_drop(
+    funder: &Account,
+    keypom_contract: &Contract,
+    mock_contract: &Contract,
+    drop_id: &str,
+    num_keys: u16,
+    distribution: Value,
+) -> anyhow::Result<Vec<SecretKey>> {
+    let keys = generate_keypairs(num_keys);
+    create_drop(
+        funder,
+        keypom_contract,
+        drop_id,
+        keys.iter().map(|sk| sk.public_key()).collect(),
+        json!([{
+            "uses": 1,
+            "assets": [{"nft_contract_id": mock_contract.id(), "nft_distribution": distribution}]
+        }]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+    Ok(keys)
+}
+
+/// NFTs are handed out in the order set by the drop's distribution mode and failed claims put the exact token back
+pub async fn test_nft_distribution(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let mock_contract = deploy_mock_contract(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+    // The funder pays for storing the deposited token IDs out of their balance
+    add_to_balance(&funder, &keypom_contract, NearToken::from_near(1)).await?;
+
+    // LIFO is the default and hands out the most recently deposited token first
+    let keys = create_nft_drop(
+        &funder,
+        &keypom_contract,
+        &mock_contract,
+        "lifo_drop",
+        2,
+        Value::Null,
+    )
+    .await?;
+    for token_id in ["lifo-1", "lifo-2"] {
+        mint_and_deposit(
+            &funder,
+            &mock_contract,
+            &keypom_contract,
+            token_id,
+            json!("lifo_drop"),
+        )
+        .await?;
+    }
+    let key_info = get_key_info(&keypom_contract, keys[0].public_key(), true)
+        .await?
+        .unwrap();
+    assert_eq!(key_info["nft_list"][0]["token_id"], "lifo-2");
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(&nft_owner(&mock_contract, "lifo-2").await?, claimer.id());
+
+    // FIFO hands out the first deposited token first
+    let keys = create_nft_drop(
+        &funder,
+        &keypom_contract,
+        &mock_contract,
+        "fifo_drop",
+        2,
+        json!("fifo"),
+    )
+    .await?;
+    for token_id in ["fifo-1", "fifo-2"] {
+        mint_and_deposit(
+            &funder,
+            &mock_contract,
+            &keypom_contract,
+            token_id,
+            json!("fifo_drop"),
+        )
+        .await?;
+    }
+    let key_info = get_key_info(&keypom_contract, keys[0].public_key(), true)
+        .await?
+        .unwrap();
+    assert_eq!(key_info["nft_list"][0]["token_id"], "fifo-1");
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(&nft_owner(&mock_contract, "fifo-1").await?, claimer.id());
+    assert_eq!(
+        get_pooled_token_ids(&keypom_contract, "fifo_drop").await?,
+        vec![json!("fifo-2")]
+    );
+
+    // Random distributions can't be predicted so the key info doesn't show a token
+    let random_tokens = ["random-1", "random-2", "random-3"];
+    let keys = create_nft_drop(
+        &funder,
+        &keypom_contract,
+        &mock_contract,
+        "random_drop",
+        4,
+        json!("random"),
+    )
+    .await?;
+    for token_id in random_tokens {
+        mint_and_deposit(
+            &funder,
+            &mock_contract,
+            &keypom_contract,
+            token_id,
+            json!("random_drop"),
+        )
+        .await?;
+    }
+    let key_info = get_key_info(&keypom_contract, keys[0].public_key(), true)
+        .await?
+        .unwrap();
+    assert!(key_info["nft_list"].as_array().unwrap().is_empty());
+
+    // If the transfer fails, whichever token was picked goes back into the pool
+    set_transfers_paused(&mock_contract, true).await?;
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(!res.into_result()?.json::<bool>()?);
+    let mut pooled = get_pooled_token_ids(&keypom_contract, "random_drop").await?;
+    pooled.sort_by_key(|t| t.as_str().unwrap().to_string());
+    assert_eq!(pooled, random_tokens.map(|t| json!(t)).to_vec());
+    for token_id in random_tokens {
+        assert_eq!(
+            &nft_owner(&mock_contract, token_id).await?,
+            keypom_contract.id()
+        );
+    }
+
+    // Every token is eventually handed out exactly once
+    set_transfers_paused(&mock_contract, false).await?;
+    for sk in &keys[1..] {
+        let res = claim_with_key(&relayer, &keypom_contract, sk, claimer.id()).await?;
+        assert!(res.into_result()?.json::<bool>()?);
+    }
+    for token_id in random_tokens {
+        assert_eq!(&nft_owner(&mock_contract, token_id).await?, claimer.id());
+    }
+    assert!(get_pooled_token_ids(&keypom_contract, "random_drop")
+        .await?
+        .is_empty());
+
+    println!("      Passed ✅ test_nft_distribution");
+    Ok(())
+}
+
+async fn get_pinned_nfts(
+    keypom_contract: &Contract,
+    mock_contract: &Contract,
+    drop_id: &str,
+) -> anyhow::Result<Vec<Value>> {
+    Ok(keypom_contract
+        .view("get_pinned_nfts_for_drop")
+        .args_json(json!({"drop_id": drop_id, "nft_contract_id": mock_contract.id()}))
+        .await?
+        .json::<Vec<Value>>()?)
+}
+
+/// NFTs can be pinned to specific keys and uses when they're deposited or afterwards by the funder
+pub async fn test_nft_pins(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let mock_contract = deploy_mock_contract(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+    add_to_balance(&funder, &keypom_contract, NearToken::from_near(1)).await?;
+
+    // 2 keys with 2 uses that each transfer an NFT
+    let keys = generate_keypairs(2);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "pinned_drop",
+        keys.iter().map(|sk| sk.public_key()).collect(),
+        json!([{"uses": 2, "assets": [{"nft_contract_id": mock_contract.id()}]}]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    // Tokens can be pinned to a specific use of a key as they're deposited
+    mint_and_deposit(
+        &funder,
+        &mock_contract,
+        &keypom_contract,
+        "gold-1",
+        json!({"drop_id": "pinned_drop", "key_id": 1, "use_number": 2}),
+    )
+    .await?;
+    assert_eq!(
+        get_pinned_nfts(&keypom_contract, &mock_contract, "pinned_drop").await?,
+        vec![json!({"token_id": "gold-1", "key_id": 1, "use_number": 2})]
+    );
+
+    // Pins for keys or uses that don't exist are rejected and the token is returned
+    mint_and_deposit(
+        &funder,
+        &mock_contract,
+        &keypom_contract,
+        "bad-key",
+        json!({"drop_id": "pinned_drop", "key_id": 5}),
+    )
+    .await?;
+    assert_eq!(&nft_owner(&mock_contract, "bad-key").await?, funder.id());
+    mint_and_deposit(
+        &funder,
+        &mock_contract,
+        &keypom_contract,
+        "bad-use",
+        json!({"drop_id": "pinned_drop", "key_id": 0, "use_number": 3}),
+    )
+    .await?;
+    assert_eq!(&nft_owner(&mock_contract, "bad-use").await?, funder.id());
+
+    for token_id in ["pool-1", "pool-2", "pool-3"] {
+        mint_and_deposit(
+            &funder,
+            &mock_contract,
+            &keypom_contract,
+            token_id,
+            json!("pinned_drop"),
+        )
+        .await?;
+    }
+
+    // Only the funder can pin tokens that were already deposited
+    let pin_args = json!({
+        "drop_id": "pinned_drop",
+        "nft_contract_id": mock_contract.id(),
+        "pins": [{"token_id": "pool-1", "key_id": 0}]
+    });
+    let res = claimer
+        .call(keypom_contract.id(), "pin_nft_tokens")
+        .args_json(pin_args.clone())
+        .transact()
+        .await?;
+    assert_failed_with(res, "Only the funder can pin NFTs");
+    funder
+        .call(keypom_contract.id(), "pin_nft_tokens")
+        .args_json(pin_args)
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(
+        get_pinned_nfts(&keypom_contract, &mock_contract, "pinned_drop")
+            .await?
+            .len(),
+        2
+    );
+    assert_eq!(
+        get_pooled_token_ids(&keypom_contract, "pinned_drop").await?,
+        vec![json!("pool-2"), json!("pool-3")]
+    );
+
+    // The first use of key 1 has no pin so it comes from the pool. The second use is pinned
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[1], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(&nft_owner(&mock_contract, "pool-3").await?, claimer.id());
+    let key_info = get_key_info(&keypom_contract, keys[1].public_key(), true)
+        .await?
+        .unwrap();
+    assert_eq!(key_info["nft_list"][0]["token_id"], "gold-1");
+
+    // Key 0 has a token pinned for any use
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(&nft_owner(&mock_contract, "pool-1").await?, claimer.id());
+
+    // If the pinned transfer fails, the token is pinned to the same key and use again
+    set_transfers_paused(&mock_contract, true).await?;
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[1], claimer.id()).await?;
+    assert!(!res.into_result()?.json::<bool>()?);
+    set_transfers_paused(&mock_contract, false).await?;
+    assert_eq!(
+        &nft_owner(&mock_contract, "gold-1").await?,
+        keypom_contract.id()
+    );
+    assert_eq!(
+        get_pinned_nfts(&keypom_contract, &mock_contract, "pinned_drop").await?,
+        vec![json!({"token_id": "gold-1", "key_id": 1, "use_number": 2})]
+    );
+
+    // Unpinned tokens go back into the shared pool
+    funder
+        .call(keypom_contract.id(), "unpin_nft_tokens")
+        .args_json(json!({
+            "drop_id": "pinned_drop",
+            "nft_contract_id": mock_contract.id(),
+            "token_ids": ["gold-1"]
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+    assert!(
+        get_pinned_nfts(&keypom_contract, &mock_contract, "pinned_drop")
+            .await?
+            .is_empty()
+    );
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(&nft_owner(&mock_contract, "gold-1").await?, claimer.id());
+
+    println!("      Passed ✅ test_nft_pins");
+    Ok(())
+}
+
+/// NFTs sent to a drop whose pool already covers every remaining claim are returned to the sender
+pub async fn test_nft_overflow_refunds(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let mock_contract = deploy_mock_contract(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+    add_to_balance(&funder, &keypom_contract, NearToken::from_near(1)).await?;
+
+    let keys = create_nft_drop(
+        &funder,
+        &keypom_contract,
+        &mock_contract,
+        "nft_drop",
+        1,
+        Value::Null,
+    )
+    .await?;
+
+    // The single claim only needs 1 token so the second one is returned
+    for token_id in ["nft-1", "nft-2"] {
+        mint_and_deposit(
+            &funder,
+            &mock_contract,
+            &keypom_contract,
+            token_id,
+            json!("nft_drop"),
+        )
+        .await?;
+    }
+    assert_eq!(
+        &nft_owner(&mock_contract, "nft-1").await?,
+        keypom_contract.id()
+    );
+    assert_eq!(&nft_owner(&mock_contract, "nft-2").await?, funder.id());
+    assert_eq!(
+        get_pooled_token_ids(&keypom_contract, "nft_drop").await?,
+        vec![json!("nft-1")]
+    );
+
+    // Unless the funder explicitly opts out of the refund
+    mint_and_deposit(
+        &funder,
+        &mock_contract,
+        &keypom_contract,
+        "nft-3",
+        json!({"drop_id": "nft_drop", "refund_overflow": false}),
+    )
+    .await?;
+    assert_eq!(
+        &nft_owner(&mock_contract, "nft-3").await?,
+        keypom_contract.id()
+    );
+    assert_eq!(
+        get_pooled_token_ids(&keypom_contract, "nft_drop")
+            .await?
+            .len(),
+        2
+    );
+
+    // Drops without an NFT asset for this contract return the token as well
+    let near_keys = generate_keypairs(1);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "near_drop",
+        vec![near_keys[0].public_key()],
+        json!([near_asset_data(1, NearToken::from_millinear(100))]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+    mint_and_deposit(
+        &funder,
+        &mock_contract,
+        &keypom_contract,
+        "nft-4",
+        json!("near_drop"),
+    )
+    .await?;
+    assert_eq!(&nft_owner(&mock_contract, "nft-4").await?, funder.id());
+
+    // The last deposited token is claimed as usual
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(&nft_owner(&mock_contract, "nft-3").await?, claimer.id());
+
+    println!("      Passed ✅ test_nft_overflow_refunds");
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use crate::*;
+
+async fn get_owners(keypom_contract: &Contract) -> anyhow::Result<(AccountId, Option<AccountId>)> {
+    let owner = keypom_contract
+        .view("get_contract_owner")
+        .await?
+        .json::<AccountId>()?;
+    let pending_owner = keypom_contract
+        .view("get_pending_owner")
+        .await?
+        .json::<Option<AccountId>>()?;
+    Ok((owner, pending_owner))
+}
+
+/// Ownership is only transferred once the proposed owner accepts it
+pub async fn test_ownership_transfer(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, _) = deploy_keypom(worker).await?;
+    let owner = worker.root_account()?;
+    let new_owner = worker.dev_create_account().await?;
+    let stranger = worker.dev_create_account().await?;
+
+    // Only the owner can propose a new owner and it must be a different account
+    let res = stranger
+        .call(keypom_contract.id(), "propose_owner")
+        .args_json(json!({"new_owner_id": stranger.id()}))
+        .transact()
+        .await?;
+    assert_failed_with(res, "Only the contract owner can call this function");
+    let res = owner
+        .call(keypom_contract.id(), "propose_owner")
+        .args_json(json!({"new_owner_id": owner.id()}))
+        .transact()
+        .await?;
+    assert_failed_with(res, "New owner must be different from the current owner");
+
+    owner
+        .call(keypom_contract.id(), "propose_owner")
+        .args_json(json!({"new_owner_id": new_owner.id()}))
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(
+        get_owners(&keypom_contract).await?,
+        (owner.id().clone(), Some(new_owner.id().clone()))
+    );
+
+    // Only the proposed owner can accept
+    let res = stranger
+        .call(keypom_contract.id(), "accept_ownership")
+        .transact()
+        .await?;
+    assert_failed_with(res, "Only the pending owner can accept ownership");
+
+    // A cancelled proposal can no longer be accepted
+    owner
+        .call(keypom_contract.id(), "cancel_ownership_transfer")
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(
+        get_owners(&keypom_contract).await?,
+        (owner.id().clone(), None)
+    );
+    let res = new_owner
+        .call(keypom_contract.id(), "accept_ownership")
+        .transact()
+        .await?;
+    assert_failed_with(res, "No ownership transfer pending");
+
+    // Propose again and accept this time
+    owner
+        .call(keypom_contract.id(), "propose_owner")
+        .args_json(json!({"new_owner_id": new_owner.id()}))
+        .transact()
+        .await?
+        .into_result()?;
+    new_owner
+        .call(keypom_contract.id(), "accept_ownership")
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(
+        get_owners(&keypom_contract).await?,
+        (new_owner.id().clone(), None)
+    );
+
+    // The old owner lost access to owner only methods while the new owner gained it
+    let res = owner
+        .call(keypom_contract.id(), "set_fees")
+        .args_json(json!({"drop_fee": "1"}))
+        .transact()
+        .await?;
+    assert_failed_with(res, "Only the contract owner can call this function");
+    new_owner
+        .call(keypom_contract.id(), "set_fees")
+        .args_json(json!({"drop_fee": "1"}))
+        .transact()
+        .await?
+        .into_result()?;
+
+    println!("      Passed ✅ test_ownership_transfer");
+    Ok(())
+}
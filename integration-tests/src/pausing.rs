@@ -0,0 +1,205 @@
+use crate::*;
+
+async fn set_pause_flags(
+    owner: &Account,
+    keypom_contract: &Contract,
+    flags: Value,
+) -> anyhow::Result<()> {
+    owner
+        .call(keypom_contract.id(), "set_pause_flags")
+        .args_json(flags)
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+/// The owner can pause individual parts of the contract without freezing everything
+pub async fn test_pause_flags(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let owner = worker.root_account()?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+
+    let keys = generate_keypairs(1);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "pause_drop",
+        vec![keys[0].public_key()],
+        json!([near_asset_data(2, NearToken::from_millinear(100))]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    // Only the owner can pause the contract
+    let res = funder
+        .call(keypom_contract.id(), "set_pause_flags")
+        .args_json(json!({"claims": true}))
+        .transact()
+        .await?;
+    assert_failed_with(res, "Only the contract owner can call this function");
+
+    // Pausing claims leaves everything else untouched
+    set_pause_flags(&owner, &keypom_contract, json!({"claims": true})).await?;
+    let pause_state = keypom_contract
+        .view("get_pause_state")
+        .await?
+        .json::<Value>()?;
+    assert_eq!(
+        pause_state,
+        json!({
+            "drop_creation": false,
+            "claims": true,
+            "key_transfers": false,
+            "deposits": false,
+            "withdrawals": false
+        })
+    );
+
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert_failed_with(res, "Contract is paused for claims");
+    funder
+        .call(keypom_contract.id(), "add_to_balance")
+        .deposit(NearToken::from_near(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    // Pausing deposits and withdrawals stops funders from moving their balance around
+    set_pause_flags(
+        &owner,
+        &keypom_contract,
+        json!({"deposits": true, "withdrawals": true}),
+    )
+    .await?;
+    let res = funder
+        .call(keypom_contract.id(), "add_to_balance")
+        .deposit(NearToken::from_near(1))
+        .transact()
+        .await?;
+    assert_failed_with(res, "Contract is paused for deposits");
+    let res = funder
+        .call(keypom_contract.id(), "withdraw_from_balance")
+        .args_json(json!({}))
+        .transact()
+        .await?;
+    assert_failed_with(res, "Contract is paused for withdrawals");
+
+    // Pausing drop creation blocks funders but not the owner
+    set_pause_flags(&owner, &keypom_contract, json!({"drop_creation": true})).await?;
+    let new_keys = generate_keypairs(1);
+    let res = create_drop(
+        &funder,
+        &keypom_contract,
+        "paused_creation",
+        vec![new_keys[0].public_key()],
+        json!([near_asset_data(1, NearToken::from_millinear(100))]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await;
+    assert!(
+        format!("{:?}", res.expect_err("Drop creation should be paused"))
+            .contains("Contract is paused for drop_creation")
+    );
+    create_drop(
+        &owner,
+        &keypom_contract,
+        "owner_drop",
+        vec![new_keys[0].public_key()],
+        json!([near_asset_data(1, NearToken::from_millinear(100))]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    // Unfreezing resumes every entry point
+    owner
+        .call(keypom_contract.id(), "freeze_contract")
+        .transact()
+        .await?
+        .into_result()?;
+    owner
+        .call(keypom_contract.id(), "unfreeze_contract")
+        .transact()
+        .await?
+        .into_result()?;
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+
+    println!("      Passed ✅ test_pause_flags");
+    Ok(())
+}
+
+/// Funders can pause claims on their own drops
+pub async fn test_drop_pause(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+
+    let keys = generate_keypairs(1);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "funder_pause",
+        vec![keys[0].public_key()],
+        json!([near_asset_data(2, NearToken::from_millinear(100))]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    // Only the funder can pause the drop
+    let res = claimer
+        .call(keypom_contract.id(), "pause_drop")
+        .args_json(json!({"drop_id": "funder_pause"}))
+        .transact()
+        .await?;
+    assert_failed_with(res, "Only drop funder can pause or unpause the drop");
+
+    funder
+        .call(keypom_contract.id(), "pause_drop")
+        .args_json(json!({"drop_id": "funder_pause"}))
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(
+        get_drop_info(&keypom_contract, "funder_pause").await?["paused"],
+        true
+    );
+    let res = funder
+        .call(keypom_contract.id(), "pause_drop")
+        .args_json(json!({"drop_id": "funder_pause"}))
+        .transact()
+        .await?;
+    assert_failed_with(res, "Drop is already paused");
+
+    // Claims are rejected without consuming a use
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert_failed_with(res, "Drop is paused");
+    let key_info = get_key_info(&keypom_contract, keys[0].public_key(), true)
+        .await?
+        .unwrap();
+    assert_eq!(key_info["uses_remaining"], 2);
+
+    // Once unpaused, the key can be claimed again
+    funder
+        .call(keypom_contract.id(), "unpause_drop")
+        .args_json(json!({"drop_id": "funder_pause"}))
+        .transact()
+        .await?
+        .into_result()?;
+    let res = funder
+        .call(keypom_contract.id(), "unpause_drop")
+        .args_json(json!({"drop_id": "funder_pause"}))
+        .transact()
+        .await?;
+    assert_failed_with(res, "Drop is not paused");
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+
+    println!("      Passed ✅ test_drop_pause");
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use crate::*;
+
+/// One hour in nanoseconds. Long enough that every claim in a test lands in the same window
+const ONE_HOUR: u64 = 3_600_000_000_000;
+
+async fn get_rate_limit_info(keypom_contract: &Contract, key: PublicKey) -> anyhow::Result<Value> {
+    Ok(keypom_contract
+        .view("get_rate_limit_info")
+        .args_json(json!({ "key": key }))
+        .await?
+        .json::<Value>()?)
+}
+
+/// Keys and drops can only be claimed a set number of times per window
+pub async fn test_rate_limits(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+
+    // Invalid windows are rejected at drop creation
+    let keys = generate_keypairs(1);
+    let res = create_drop(
+        &funder,
+        &keypom_contract,
+        "zero_window",
+        vec![keys[0].public_key()],
+        json!([near_asset_data(1, NearToken::from_millinear(100))]),
+        json!({"rate_limit": {"window": 0, "max_claims_per_key": 1}}),
+        NearToken::from_near(1),
+    )
+    .await;
+    assert!(format!("{:?}", res.expect_err("Zero window should fail"))
+        .contains("The rate limit window must be greater than 0"));
+
+    // A key can only be claimed once per hour
+    let keys = generate_keypairs(1);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "key_limit",
+        vec![keys[0].public_key()],
+        json!([near_asset_data(3, NearToken::from_millinear(100))]),
+        json!({"rate_limit": {"window": ONE_HOUR, "max_claims_per_key": 1}}),
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    let rate_limit_info = get_rate_limit_info(&keypom_contract, keys[0].public_key()).await?;
+    assert_eq!(rate_limit_info["key_claims_in_window"], 1);
+
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert_failed_with(res, "Key rate limit exceeded. Max 1 claims per window");
+    // The rejected claim didn't consume a use
+    let key_info = get_key_info(&keypom_contract, keys[0].public_key(), true)
+        .await?
+        .unwrap();
+    assert_eq!(key_info["uses_remaining"], 2);
+
+    // Only one claim can go through across all the keys in the drop per hour
+    let keys = generate_keypairs(2);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "drop_limit",
+        keys.iter().map(|sk| sk.public_key()).collect(),
+        json!([near_asset_data(1, NearToken::from_millinear(100))]),
+        json!({"rate_limit": {"window": ONE_HOUR, "max_claims_per_drop": 1}}),
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[1], claimer.id()).await?;
+    assert_failed_with(res, "Drop rate limit exceeded. Max 1 claims per window");
+    let rate_limit_info = get_rate_limit_info(&keypom_contract, keys[1].public_key()).await?;
+    assert_eq!(rate_limit_info["key_claims_in_window"], 0);
+    assert_eq!(rate_limit_info["drop_claims_in_window"], 1);
+
+    // Without a window, every block is its own window so claims in separate blocks go through
+    let keys = generate_keypairs(1);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "block_limit",
+        vec![keys[0].public_key()],
+        json!([near_asset_data(2, NearToken::from_millinear(100))]),
+        json!({"rate_limit": {"max_claims_per_key": 1}}),
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    for _ in 0..2 {
+        let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+        assert!(res.into_result()?.json::<bool>()?);
+    }
+    get_key_info(&keypom_contract, keys[0].public_key(), false).await?;
+
+    // Drops without their own rate limit fall back to the global one set by the owner
+    let owner = worker.root_account()?;
+    owner
+        .call(keypom_contract.id(), "set_global_rate_limit")
+        .args_json(json!({"rate_limit": {"window": ONE_HOUR, "max_claims_per_key": 1}}))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let keys = generate_keypairs(1);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "global_limit",
+        vec![keys[0].public_key()],
+        json!([near_asset_data(2, NearToken::from_millinear(100))]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert_failed_with(res, "Key rate limit exceeded. Max 1 claims per window");
+
+    println!("      Passed ✅ test_rate_limits");
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use crate::*;
+use near_workspaces::result::ExecutionFinalResult;
+
+/// Call `claim` with an explicit signature and expiry rather than letting the helpers sign the payload
+async fn claim_with_signature(
+    signer: &Account,
+    keypom_contract: &Contract,
+    args: &Value,
+    signature: Base64VecU8,
+    valid_until: u64,
+) -> anyhow::Result<ExecutionFinalResult> {
+    let mut call_args = args.clone();
+    call_args["signature"] = json!({
+        "signature": signature,
+        "valid_until": valid_until.to_string()
+    });
+    Ok(signer
+        .call(keypom_contract.id(), "claim")
+        .args_json(call_args)
+        .max_gas()
+        .transact()
+        .await?)
+}
+
+/// Key signatures are bound to the chain, contract, method, nonce and expiry so they can't be replayed elsewhere
+pub async fn test_signed_claims(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+
+    let sk = generate_keypairs(1).remove(0);
+    // Create a 1 $NEAR linkdrop with 2 uses
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "signed_drop",
+        vec![sk.public_key()],
+        json!([near_asset_data(2, NearToken::from_near(1))]),
+        Value::Null,
+        NearToken::from_near(3),
+    )
+    .await?;
+
+    let args = json!({"account_id": claimer.id(), "linkdrop_pk": sk.public_key()});
+    let valid_sig = sign_kp_payload(
+        &sk,
+        CHAIN_ID,
+        keypom_contract.id(),
+        "claim",
+        &args,
+        0,
+        u64::MAX,
+    );
+
+    // Only registered signing keys can submit claims
+    let res = claim_with_signature(
+        keypom_contract.as_account(),
+        &keypom_contract,
+        &args,
+        valid_sig.clone(),
+        u64::MAX,
+    )
+    .await?;
+    assert_failed_with(res, "Only Contract Key Can Call This Method");
+
+    // Signatures past their expiry are rejected
+    let expired_sig = sign_kp_payload(&sk, CHAIN_ID, keypom_contract.id(), "claim", &args, 0, 1);
+    let res = claim_with_signature(&relayer, &keypom_contract, &args, expired_sig, 1).await?;
+    assert_failed_with(res, "Signature expired at 1");
+
+    // Signatures for a different method can't be used to claim
+    let wrong_method_sig = sign_kp_payload(
+        &sk,
+        CHAIN_ID,
+        keypom_contract.id(),
+        "create_account_and_claim",
+        &args,
+        0,
+        u64::MAX,
+    );
+    let res = claim_with_signature(
+        &relayer,
+        &keypom_contract,
+        &args,
+        wrong_method_sig,
+        u64::MAX,
+    )
+    .await?;
+    assert_failed_with(res, "Invalid signature for public key");
+
+    // Signatures for a different contract can't be used to claim
+    let wrong_contract_sig =
+        sign_kp_payload(&sk, CHAIN_ID, funder.id(), "claim", &args, 0, u64::MAX);
+    let res = claim_with_signature(
+        &relayer,
+        &keypom_contract,
+        &args,
+        wrong_contract_sig,
+        u64::MAX,
+    )
+    .await?;
+    assert_failed_with(res, "Invalid signature for public key");
+
+    // Signatures for a different network can't be used to claim
+    let wrong_chain_sig = sign_kp_payload(
+        &sk,
+        "mainnet",
+        keypom_contract.id(),
+        "claim",
+        &args,
+        0,
+        u64::MAX,
+    );
+    let res =
+        claim_with_signature(&relayer, &keypom_contract, &args, wrong_chain_sig, u64::MAX).await?;
+    assert_failed_with(res, "Invalid signature for public key");
+
+    // Signatures that don't cover the arguments being passed in can't be used to claim
+    let other_args = json!({"account_id": funder.id(), "linkdrop_pk": sk.public_key()});
+    let res = claim_with_signature(
+        &relayer,
+        &keypom_contract,
+        &other_args,
+        valid_sig.clone(),
+        u64::MAX,
+    )
+    .await?;
+    assert_failed_with(res, "Invalid signature for public key");
+
+    // None of the failed attempts should have consumed the nonce or a use
+    let key_info = get_key_info(&keypom_contract, sk.public_key(), true)
+        .await?
+        .unwrap();
+    assert_eq!(key_info["message_nonce"], 0);
+    assert_eq!(key_info["uses_remaining"], 2);
+
+    // Now use the correct signature
+    let balance_before = claimer.view_account().await?.balance;
+    let res = claim_with_signature(
+        &relayer,
+        &keypom_contract,
+        &args,
+        valid_sig.clone(),
+        u64::MAX,
+    )
+    .await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    let balance_after = claimer.view_account().await?.balance;
+    assert_eq!(
+        balance_after.as_yoctonear() - balance_before.as_yoctonear(),
+        NearToken::from_near(1).as_yoctonear()
+    );
+
+    // The nonce was incremented so the same signature can't be replayed
+    let key_info = get_key_info(&keypom_contract, sk.public_key(), true)
+        .await?
+        .unwrap();
+    assert_eq!(key_info["message_nonce"], 1);
+    assert_eq!(key_info["uses_remaining"], 1);
+    let res = claim_with_signature(&relayer, &keypom_contract, &args, valid_sig, u64::MAX).await?;
+    assert_failed_with(res, "Invalid signature for public key");
+
+    // Signing over the new nonce claims the last use and deletes the key
+    let res = claim_with_key(&relayer, &keypom_contract, &sk, claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    get_key_info(&keypom_contract, sk.public_key(), false).await?;
+
+    println!("      Passed ✅ test_signed_claims");
+    Ok(())
+}
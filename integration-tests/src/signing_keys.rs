@@ -0,0 +1,261 @@
+use crate::*;
+use near_workspaces::types::AccessKeyPermission;
+
+async fn get_signing_keys(keypom_contract: &Contract) -> anyhow::Result<Vec<Value>> {
+    Ok(keypom_contract
+        .view("get_signing_keys")
+        .args_json(json!({}))
+        .await?
+        .json::<Vec<Value>>()?)
+}
+
+/// Method names that the on-chain access key for a signing key is restricted to
+async fn get_access_key_methods(
+    keypom_contract: &Contract,
+    public_key: &PublicKey,
+) -> anyhow::Result<Vec<String>> {
+    let access_key = keypom_contract.view_access_key(public_key).await?;
+    match access_key.permission {
+        AccessKeyPermission::FunctionCall(permission) => Ok(permission.method_names),
+        AccessKeyPermission::FullAccess => panic!("Signing keys should never be full access"),
+    }
+}
+
+/// Signing keys can be scoped to specific methods, given an allowance and set to expire. Expired keys can be pruned
+pub async fn test_signing_key_scopes(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, _) = deploy_keypom(worker).await?;
+    let owner = worker.root_account()?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+
+    let signing_keys = generate_keypairs(3);
+    let (claim_sk, transfer_sk, expiring_sk) =
+        (&signing_keys[0], &signing_keys[1], &signing_keys[2]);
+
+    // Only signing admins can add keys
+    let res = funder
+        .call(keypom_contract.id(), "add_signing_pks")
+        .args_json(json!({"public_keys": [claim_sk.public_key()]}))
+        .transact()
+        .await?;
+    assert_failed_with(res, "Only admins can add signing pks");
+
+    // Keys can't be added with an expiry that has already passed
+    let res = owner
+        .call(keypom_contract.id(), "add_signing_pks")
+        .args_json(json!({"public_keys": [claim_sk.public_key()], "config": {"expires_at": 1}}))
+        .transact()
+        .await?;
+    assert_failed_with(
+        res,
+        "The signing key expiry must be greater than the current block timestamp",
+    );
+
+    owner
+        .call(keypom_contract.id(), "add_signing_pks")
+        .args_json(json!({
+            "public_keys": [claim_sk.public_key()],
+            "config": {"scope": "claim", "allowance": NearToken::from_near(1).as_yoctonear().to_string()}
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+    owner
+        .call(keypom_contract.id(), "add_signing_pks")
+        .args_json(
+            json!({"public_keys": [transfer_sk.public_key()], "config": {"scope": "transfer"}}),
+        )
+        .transact()
+        .await?
+        .into_result()?;
+
+    // The scope and allowance are stored on the contract and reflected in the on-chain access keys
+    let stored_keys = get_signing_keys(&keypom_contract).await?;
+    let claim_key = stored_keys
+        .iter()
+        .find(|k| k["public_key"] == json!(claim_sk.public_key()))
+        .expect("Claim key should be stored");
+    assert_eq!(claim_key["scope"], "claim");
+    assert_eq!(
+        claim_key["allowance"],
+        NearToken::from_near(1).as_yoctonear().to_string()
+    );
+    assert_eq!(
+        get_access_key_methods(&keypom_contract, &claim_sk.public_key()).await?,
+        vec!["claim", "create_account_and_claim"]
+    );
+    assert_eq!(
+        get_access_key_methods(&keypom_contract, &transfer_sk.public_key()).await?,
+        vec!["nft_transfer", "nft_approve", "nft_transfer_payout"]
+    );
+
+    let keys = generate_keypairs(1);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "scoped_drop",
+        vec![keys[0].public_key()],
+        json!([near_asset_data(3, NearToken::from_millinear(100))]),
+        Value::Null,
+        NearToken::from_near(1),
+    )
+    .await?;
+
+    // Keys scoped to transfers can't submit claims
+    let transfer_relayer =
+        Account::from_secret_key(keypom_contract.id().clone(), transfer_sk.clone(), worker);
+    let res = claim_with_key(&transfer_relayer, &keypom_contract, &keys[0], claimer.id()).await;
+    assert!(res.is_err() || res.unwrap().is_failure());
+
+    // Keys scoped to claims can
+    let claim_relayer =
+        Account::from_secret_key(keypom_contract.id().clone(), claim_sk.clone(), worker);
+    let res = claim_with_key(&claim_relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+
+    // Keys stop working once they've expired
+    let now = worker.view_block().await?.timestamp();
+    owner
+        .call(keypom_contract.id(), "add_signing_pks")
+        .args_json(json!({
+            "public_keys": [expiring_sk.public_key()],
+            "config": {"scope": "claim", "expires_at": now + 5_000_000_000u64}
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+    let expiring_relayer =
+        Account::from_secret_key(keypom_contract.id().clone(), expiring_sk.clone(), worker);
+    tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+    let res = claim_with_key(&expiring_relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert_failed_with(res, "Signing key has expired");
+
+    // Expired keys can be pruned so their access keys stop spending the allowance
+    let res = funder
+        .call(keypom_contract.id(), "prune_expired_signing_keys")
+        .transact()
+        .await?;
+    assert_failed_with(res, "Only the owner or admins can prune signing pks");
+    let pruned = owner
+        .call(keypom_contract.id(), "prune_expired_signing_keys")
+        .transact()
+        .await?
+        .into_result()?
+        .json::<Vec<PublicKey>>()?;
+    assert_eq!(pruned, vec![expiring_sk.public_key()]);
+    assert!(keypom_contract
+        .view_access_key(&expiring_sk.public_key())
+        .await
+        .is_err());
+    assert!(!get_signing_keys(&keypom_contract)
+        .await?
+        .iter()
+        .any(|k| k["public_key"] == json!(expiring_sk.public_key())));
+
+    // Removed keys are deleted from the contract along with their access key
+    owner
+        .call(keypom_contract.id(), "remove_signing_pks")
+        .args_json(json!({"public_keys": [claim_sk.public_key()]}))
+        .transact()
+        .await?
+        .into_result()?;
+    let stored_keys = get_signing_keys(&keypom_contract).await?;
+    assert!(!stored_keys
+        .iter()
+        .any(|k| k["public_key"] == json!(claim_sk.public_key())));
+    assert!(keypom_contract
+        .view_access_key(&claim_sk.public_key())
+        .await
+        .is_err());
+
+    let key_info = get_key_info(&keypom_contract, keys[0].public_key(), true)
+        .await?
+        .unwrap();
+    assert_eq!(key_info["uses_remaining"], 2);
+
+    println!("      Passed ✅ test_signing_key_scopes");
+    Ok(())
+}
+
+async fn get_signing_admins(keypom_contract: &Contract) -> anyhow::Result<Vec<AccountId>> {
+    Ok(keypom_contract
+        .view("get_signing_admins")
+        .args_json(json!({}))
+        .await?
+        .json::<Vec<AccountId>>()?)
+}
+
+/// Only the owner can manage signing admins and only signing admins can manage signing keys
+pub async fn test_signing_admins(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, _) = deploy_keypom(worker).await?;
+    let owner = worker.root_account()?;
+    let admin = worker.dev_create_account().await?;
+    let signing_keys = generate_keypairs(2);
+
+    // Only the owner can add admins
+    let res = admin
+        .call(keypom_contract.id(), "add_signing_admins")
+        .args_json(json!({"account_ids": [admin.id()]}))
+        .transact()
+        .await?;
+    assert_failed_with(res, "Only the contract owner can call this function");
+
+    owner
+        .call(keypom_contract.id(), "add_signing_admins")
+        .args_json(json!({"account_ids": [admin.id()]}))
+        .transact()
+        .await?
+        .into_result()?;
+    assert!(get_signing_admins(&keypom_contract)
+        .await?
+        .contains(admin.id()));
+
+    let res = owner
+        .call(keypom_contract.id(), "add_signing_admins")
+        .args_json(json!({"account_ids": [admin.id()]}))
+        .transact()
+        .await?;
+    assert_failed_with(res, "is already a signing admin");
+
+    // The new admin can manage signing keys
+    admin
+        .call(keypom_contract.id(), "add_signing_pks")
+        .args_json(json!({"public_keys": [signing_keys[0].public_key()]}))
+        .transact()
+        .await?
+        .into_result()?;
+
+    // Once removed, the admin can no longer manage signing keys
+    owner
+        .call(keypom_contract.id(), "remove_signing_admins")
+        .args_json(json!({"account_ids": [admin.id()]}))
+        .transact()
+        .await?
+        .into_result()?;
+    assert!(!get_signing_admins(&keypom_contract)
+        .await?
+        .contains(admin.id()));
+
+    let res = admin
+        .call(keypom_contract.id(), "add_signing_pks")
+        .args_json(json!({"public_keys": [signing_keys[1].public_key()]}))
+        .transact()
+        .await?;
+    assert_failed_with(res, "Only admins can add signing pks");
+    let res = admin
+        .call(keypom_contract.id(), "remove_signing_pks")
+        .args_json(json!({"public_keys": [signing_keys[0].public_key()]}))
+        .transact()
+        .await?;
+    assert_failed_with(res, "Only admins can remove signing pks");
+
+    let res = owner
+        .call(keypom_contract.id(), "remove_signing_admins")
+        .args_json(json!({"account_ids": [admin.id()]}))
+        .transact()
+        .await?;
+    assert_failed_with(res, "is not a signing admin");
+
+    println!("      Passed ✅ test_signing_admins");
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use crate::*;
+use near_workspaces::result::ExecutionFinalResult;
+
+/// Create a drop with full control over the key data (i.e to pass in per-key overrides)
+async fn create_drop_with_key_data(
+    funder: &Account,
+    keypom_contract: &Contract,
+    drop_id: &str,
+    key_data: Value,
+    asset_data: Value,
+    deposit: NearToken,
+) -> anyhow::Result<ExecutionFinalResult> {
+    Ok(funder
+        .call(keypom_contract.id(), "create_drop")
+        .args_json(json!({"drop_id": drop_id, "key_data": key_data, "asset_data": asset_data}))
+        .deposit(deposit)
+        .max_gas()
+        .transact()
+        .await?)
+}
+
+/// Individual keys can transfer a different amount than the rest of the drop for specific uses
+pub async fn test_tokens_per_use_overrides(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+    let asset_data = json!([near_asset_data(2, NearToken::from_millinear(100))]);
+    let bonus = NearToken::from_millinear(500).as_yoctonear().to_string();
+
+    // Overrides must target uses and assets that exist in the drop
+    let keys = generate_keypairs(1);
+    let invalid_overrides = [
+        (
+            json!({"3": {"near": bonus}}),
+            "Use 3 does not exist in the drop".to_string(),
+        ),
+        (
+            json!({"1": {"nft.test.near": bonus}}),
+            "Asset nft.test.near is not part of use 1".to_string(),
+        ),
+        (
+            json!({"1": {"near": "0"}}),
+            "Override for near in use 1 must be more than 0 tokens".to_string(),
+        ),
+    ];
+    for (overrides, message) in invalid_overrides {
+        let res = create_drop_with_key_data(
+            &funder,
+            &keypom_contract,
+            "invalid_overrides",
+            json!([{"public_key": keys[0].public_key(), "tokens_per_use_overrides": overrides}]),
+            asset_data.clone(),
+            NearToken::from_near(1),
+        )
+        .await?;
+        assert_failed_with(res, &message);
+    }
+
+    // Keys with overrides cost more to add. Both drops are paid for out of the funder's balance
+    add_to_balance(&funder, &keypom_contract, NearToken::from_near(5)).await?;
+    let regular_key = generate_keypairs(1).remove(0);
+    let balance_before = get_user_balance(&keypom_contract, funder.id()).await?;
+    create_drop_with_key_data(
+        &funder,
+        &keypom_contract,
+        "regular_drop",
+        json!([{"public_key": regular_key.public_key()}]),
+        asset_data.clone(),
+        NearToken::from_yoctonear(0),
+    )
+    .await?
+    .into_result()?;
+    let regular_cost = balance_before - get_user_balance(&keypom_contract, funder.id()).await?;
+
+    // The second use of the bonus key transfers 0.5 $NEAR instead of 0.1
+    let bonus_key = generate_keypairs(1).remove(0);
+    let balance_before = get_user_balance(&keypom_contract, funder.id()).await?;
+    create_drop_with_key_data(
+        &funder,
+        &keypom_contract,
+        "bonus_drop",
+        json!([{
+            "public_key": bonus_key.public_key(),
+            "tokens_per_use_overrides": {"2": {"near": bonus}}
+        }]),
+        asset_data.clone(),
+        NearToken::from_yoctonear(0),
+    )
+    .await?
+    .into_result()?;
+    let bonus_cost = balance_before - get_user_balance(&keypom_contract, funder.id()).await?;
+    // The difference is the 0.4 $NEAR bonus plus a little storage for the override itself
+    let extra_cost = bonus_cost - regular_cost;
+    assert!(extra_cost >= NearToken::from_millinear(400).as_yoctonear());
+    assert!(extra_cost < NearToken::from_millinear(410).as_yoctonear());
+
+    // Uses without an override transfer the drop's amount
+    let key_info = get_key_info(&keypom_contract, bonus_key.public_key(), true)
+        .await?
+        .unwrap();
+    assert_eq!(
+        key_info["yoctonear"],
+        NearToken::from_millinear(100).as_yoctonear().to_string()
+    );
+    let balance_before = claimer.view_account().await?.balance;
+    let res = claim_with_key(&relayer, &keypom_contract, &bonus_key, claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(
+        claimer.view_account().await?.balance.as_yoctonear() - balance_before.as_yoctonear(),
+        NearToken::from_millinear(100).as_yoctonear()
+    );
+
+    let key_info = get_key_info(&keypom_contract, bonus_key.public_key(), true)
+        .await?
+        .unwrap();
+    assert_eq!(key_info["yoctonear"], bonus);
+    let balance_before = claimer.view_account().await?.balance;
+    let res = claim_with_key(&relayer, &keypom_contract, &bonus_key, claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(
+        claimer.view_account().await?.balance.as_yoctonear() - balance_before.as_yoctonear(),
+        NearToken::from_millinear(500).as_yoctonear()
+    );
+
+    // Keys without overrides are unaffected
+    for _ in 0..2 {
+        let balance_before = claimer.view_account().await?.balance;
+        let res = claim_with_key(&relayer, &keypom_contract, &regular_key, claimer.id()).await?;
+        assert!(res.into_result()?.json::<bool>()?);
+        assert_eq!(
+            claimer.view_account().await?.balance.as_yoctonear() - balance_before.as_yoctonear(),
+            NearToken::from_millinear(100).as_yoctonear()
+        );
+    }
+
+    println!("      Passed ✅ test_tokens_per_use_overrides");
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use crate::*;
+
+/// Storage cost charged by the mock wNEAR contract (same as `wrap.near`)
+const WNEAR_STORAGE_COST: NearToken = NearToken::from_yoctonear(1_250_000_000_000_000_000_000);
+
+pub async fn ft_balance_of(
+    mock_contract: &Contract,
+    account_id: &AccountId,
+) -> anyhow::Result<u128> {
+    let balance = mock_contract
+        .view("ft_balance_of")
+        .args_json(json!({ "account_id": account_id }))
+        .await?
+        .json::<String>()?;
+    Ok(balance.parse()?)
+}
+
+pub async fn is_registered(
+    mock_contract: &Contract,
+    account_id: &AccountId,
+) -> anyhow::Result<bool> {
+    Ok(!mock_contract
+        .view("storage_balance_of")
+        .args_json(json!({ "account_id": account_id }))
+        .await?
+        .json::<Value>()?
+        .is_null())
+}
+
+/// $NEAR held by wNEAR drops is wrapped and sent to the claimer. Registration is only paid for when needed
+/// And failed claims are refunded to the funder
+pub async fn test_wnear_assets(worker: &Worker<Sandbox>) -> anyhow::Result<()> {
+    let (keypom_contract, relayer) = deploy_keypom(worker).await?;
+    let wnear_contract = deploy_mock_contract(worker).await?;
+    let funder = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+
+    let wnear_asset = |registration_cost: NearToken| {
+        json!({
+            "wnear_contract_id": wnear_contract.id(),
+            "wrapped_yoctonear": NearToken::from_near(1).as_yoctonear().to_string(),
+            "registration_cost": registration_cost.as_yoctonear().to_string()
+        })
+    };
+
+    // The first drop for a wNEAR contract registers Keypom so that wrapping doesn't eat into the claims
+    let keys = generate_keypairs(2);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "wnear_drop",
+        keys.iter().map(|sk| sk.public_key()).collect(),
+        json!([{"uses": 1, "assets": [wnear_asset(WNEAR_STORAGE_COST)]}]),
+        Value::Null,
+        NearToken::from_near(5),
+    )
+    .await?;
+    assert!(is_registered(&wnear_contract, keypom_contract.id()).await?);
+
+    // The claimer isn't registered so the funder pays for it
+    assert!(!is_registered(&wnear_contract, claimer.id()).await?);
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[0], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert!(is_registered(&wnear_contract, claimer.id()).await?);
+    assert_eq!(
+        ft_balance_of(&wnear_contract, claimer.id()).await?,
+        NearToken::from_near(1).as_yoctonear()
+    );
+    assert_eq!(get_user_balance(&keypom_contract, funder.id()).await?, 0);
+
+    // Now that they're registered, the registration cost is credited back to the funder
+    let res = claim_with_key(&relayer, &keypom_contract, &keys[1], claimer.id()).await?;
+    assert!(res.into_result()?.json::<bool>()?);
+    assert_eq!(
+        ft_balance_of(&wnear_contract, claimer.id()).await?,
+        NearToken::from_near(2).as_yoctonear()
+    );
+    assert_eq!(
+        get_user_balance(&keypom_contract, funder.id()).await?,
+        WNEAR_STORAGE_COST.as_yoctonear()
+    );
+
+    // A registration cost that's too low makes the whole wrap and transfer fail for unregistered claimers.
+    // The wrapped amount, registration cost and 1 yoctoNEAR for the transfer are all refunded to the funder
+    let keys = generate_keypairs(1);
+    create_drop(
+        &funder,
+        &keypom_contract,
+        "underpriced_drop",
+        vec![keys[0].public_key()],
+        json!([{"uses": 1, "assets": [wnear_asset(NearToken::from_yoctonear(1))]}]),
+        Value::Null,
+        NearToken::from_near(5),
+    )
+    .await?;
+    let balance_before = get_user_balance(&keypom_contract, funder.id()).await?;
+
+    let unregistered_claimer = worker.dev_create_account().await?;
+    let res = claim_with_key(
+        &relayer,
+        &keypom_contract,
+        &keys[0],
+        unregistered_claimer.id(),
+    )
+    .await?;
+    assert!(!res.into_result()?.json::<bool>()?);
+    assert!(!is_registered(&wnear_contract, unregistered_claimer.id()).await?);
+    assert_eq!(
+        get_user_balance(&keypom_contract, funder.id()).await?,
+        balance_before + NearToken::from_near(1).as_yoctonear() + 2
+    );
+
+    println!("      Passed ✅ test_wnear_assets");
+    Ok(())
+}